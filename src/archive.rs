@@ -0,0 +1,147 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::util::calculate_dir_size;
+
+/// Default archive folder under the tries directory; never listed as a try.
+pub(crate) const ARCHIVE_DIR: &str = ".try_archive";
+const ARCHIVE_EXT: &str = "tar.zst";
+const COPY_CHUNK: usize = 64 * 1024;
+
+/// Where archives go: `archive.dir` / `TRY_ARCHIVE_DIR`, else `.try_archive` in the tries
+/// directory.
+pub(crate) fn archive_dir(base_path: &Path, config: &Config) -> PathBuf {
+    config
+        .archive_dir
+        .clone()
+        .unwrap_or_else(|| base_path.join(ARCHIVE_DIR))
+}
+
+/// First free `<name>.tar.zst` in `dir`, numbering repeats as `<name>-2.tar.zst` and so on.
+pub(crate) fn archive_path(dir: &Path, name: &str) -> PathBuf {
+    let mut path = dir.join(format!("{name}.{ARCHIVE_EXT}"));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{name}-{n}.{ARCHIVE_EXT}"));
+        n += 1;
+    }
+    path
+}
+
+/// Packs `src` into a zstd-compressed tarball in `dest_dir`, then removes `src`. `progress`
+/// receives bytes packed so far and the directory's total size; the tar stream adds headers,
+/// so callers should clamp. Uses the system `tar` and `zstd`; a failed run leaves `src`
+/// untouched and no partial archive behind.
+pub(crate) fn archive_try(
+    src: &Path,
+    dest_dir: &Path,
+    mut progress: impl FnMut(u64, u64),
+) -> Result<PathBuf> {
+    let (Some(parent), Some(name)) = (src.parent(), src.file_name()) else {
+        return Err(Error::Archive(format!("{} is not a try", src.display())));
+    };
+    let total = calculate_dir_size(src);
+    fs::create_dir_all(dest_dir).map_err(|e| Error::from_write(dest_dir, e))?;
+    let out = archive_path(dest_dir, &name.to_string_lossy());
+    let partial = out.with_extension("zst.partial");
+    let file = fs::File::create(&partial).map_err(|e| Error::from_write(dest_dir, e))?;
+
+    let packed = pack(parent, name.as_ref(), file, total, &mut progress);
+    if let Err(e) = packed {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, &out).map_err(|e| Error::from_write(dest_dir, e))?;
+    crate::mounts::remove_tree(src).map_err(|e| Error::from_write(src, e))?;
+    Ok(out)
+}
+
+/// Streams `tar -c` of `parent/name` through `zstd` into `out`, counting bytes on the way.
+fn pack(
+    parent: &Path,
+    name: &Path,
+    out: fs::File,
+    total: u64,
+    progress: &mut impl FnMut(u64, u64),
+) -> Result<()> {
+    let mut tar = Command::new("tar");
+    if crate::mounts::one_file_system() {
+        tar.arg("--one-file-system");
+    }
+    let mut tar = spawn(
+        tar.arg("-cf")
+            .arg("-")
+            .arg("-C")
+            .arg(parent)
+            .arg(name)
+            .stdout(Stdio::piped()),
+        "tar",
+    )?;
+    let zstd = spawn(
+        Command::new("zstd")
+            .args(["-q", "-c"])
+            .stdin(Stdio::piped())
+            .stdout(out),
+        "zstd",
+    );
+    let mut zstd = match zstd {
+        Ok(child) => child,
+        Err(e) => {
+            let _ = tar.kill();
+            let _ = tar.wait();
+            return Err(e);
+        }
+    };
+    let copied = copy_counting(
+        tar.stdout.as_mut().expect("piped above"),
+        zstd.stdin.as_mut().expect("piped above"),
+        total,
+        progress,
+    );
+    // Close zstd's input so it can finish the frame
+    drop(zstd.stdin.take());
+    let tar_ok = tar.wait()?.success();
+    let zstd_ok = zstd.wait()?.success();
+    copied?;
+    match (tar_ok, zstd_ok) {
+        (true, true) => Ok(()),
+        (false, _) => Err(Error::Archive("tar exited with an error".into())),
+        (_, false) => Err(Error::Archive("zstd exited with an error".into())),
+    }
+}
+
+fn spawn(cmd: &mut Command, tool: &str) -> Result<Child> {
+    cmd.stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                Error::Archive(format!("`{tool}` not found; install it to archive"))
+            }
+            _ => Error::Io(e),
+        })
+}
+
+fn copy_counting(
+    from: &mut impl Read,
+    to: &mut impl Write,
+    total: u64,
+    progress: &mut impl FnMut(u64, u64),
+) -> Result<()> {
+    let mut buf = vec![0u8; COPY_CHUNK];
+    let mut done = 0u64;
+    loop {
+        let n = match from.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        to.write_all(&buf[..n])?;
+        done += n as u64;
+        progress(done, total);
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::OnceLock;
+
+/// Punctuation try names may always hold besides ASCII letters and digits.
+pub(crate) const NAME_PUNCT: [char; 3] = ['-', '_', '.'];
+/// Punctuation `names.extra_chars` may add to names; none of it is special to paths, globs,
+/// or filter tokens.
+pub(crate) const EXTRA_PUNCT: [char; 6] = ['+', ',', '=', '~', '%', '^'];
+/// Sigils of filter tokens (`@week`, `>1G`, `<10M`, `#infra`): searchable, never part of a
+/// name.
+pub(crate) const FILTER_SIGILS: [char; 4] = ['@', '>', '<', '#'];
+/// Default `names.separators`: `lib_test` and `lib-test` are the same name, `v1.2` and
+/// `v1-2` are not.
+pub(crate) const DEFAULT_SEPARATORS: &str = "-_";
+
+static CHARSET: OnceLock<Charset> = OnceLock::new();
+
+/// What a character is to queries and try names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CharClass {
+    /// ASCII letter or digit.
+    Word,
+    /// A space: breaks words in a query and becomes a separator in a new name.
+    Space,
+    /// Punctuation between words (`names.separators`): runs of separators and spaces compare
+    /// alike when looking for an existing try.
+    Separator,
+    /// Punctuation kept in names and compared as typed, such as the `.` of `v1.2`.
+    Literal,
+    /// A filter sigil: taken by the selector's search, dropped from names.
+    Sigil,
+    /// Anything else; dropped from queries and names alike.
+    Rejected,
+}
+
+/// The characters names may hold and which of them separate words, from `names.separators`
+/// and `names.extra_chars`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Charset {
+    separators: Vec<char>,
+    extra: Vec<char>,
+}
+
+impl Default for Charset {
+    fn default() -> Self {
+        Charset::new(DEFAULT_SEPARATORS, "")
+    }
+}
+
+impl Charset {
+    /// `-` always separates words, since new names join words and the date with it.
+    pub(crate) fn new(separators: &str, extra: &str) -> Self {
+        let mut seps: Vec<char> = vec!['-'];
+        seps.extend(separators.chars().filter(|c| *c != '-'));
+        Charset {
+            separators: seps,
+            extra: extra.chars().collect(),
+        }
+    }
+
+    pub(crate) fn class(&self, ch: char) -> CharClass {
+        if ch.is_ascii_alphanumeric() {
+            CharClass::Word
+        } else if ch == ' ' {
+            CharClass::Space
+        } else if self.separators.contains(&ch) {
+            CharClass::Separator
+        } else if NAME_PUNCT.contains(&ch) || self.extra.contains(&ch) {
+            CharClass::Literal
+        } else if FILTER_SIGILS.contains(&ch) {
+            CharClass::Sigil
+        } else {
+            CharClass::Rejected
+        }
+    }
+}
+
+/// Checks a `names.separators` value: characters from [`NAME_PUNCT`] and [`EXTRA_PUNCT`].
+pub(crate) fn parse_separators(value: &str) -> Result<String, String> {
+    parse_punct(value, &[&NAME_PUNCT, &EXTRA_PUNCT])
+}
+
+/// Checks a `names.extra_chars` value: characters from [`EXTRA_PUNCT`].
+pub(crate) fn parse_extra_chars(value: &str) -> Result<String, String> {
+    parse_punct(value, &[&EXTRA_PUNCT])
+}
+
+fn parse_punct(value: &str, allowed: &[&[char]]) -> Result<String, String> {
+    let mut out = String::new();
+    for ch in value.chars().filter(|c| !c.is_whitespace()) {
+        if !allowed.iter().any(|set| set.contains(&ch)) {
+            let all: String = allowed.iter().flat_map(|set| set.iter()).collect();
+            return Err(format!("expects characters from `{all}`, got `{ch}`"));
+        }
+        if !out.contains(ch) {
+            out.push(ch);
+        }
+    }
+    Ok(out)
+}
+
+/// Sets the process-wide character classes; only the first call takes effect, and none may
+/// follow the first [`charset`] call.
+pub(crate) fn set_charset(charset: Charset) {
+    let _ = CHARSET.set(charset);
+}
+
+/// The character classes names and queries are filtered with; the defaults until set.
+pub(crate) fn charset() -> &'static Charset {
+    CHARSET.get_or_init(Charset::default)
+}
+
+/// Whether `ch` may be part of a try name.
+pub(crate) fn is_name_char(ch: char) -> bool {
+    matches!(
+        charset().class(ch),
+        CharClass::Word | CharClass::Separator | CharClass::Literal
+    )
+}
+
+/// Whether `ch` is kept in a query that names a try: a name character or a space.
+pub(crate) fn is_query_char(ch: char) -> bool {
+    is_name_char(ch) || ch == ' '
+}
+
+/// Like [`is_query_char`], also allowing filter sigils; what the selector's search accepts.
+pub(crate) fn is_search_char(ch: char) -> bool {
+    is_query_char(ch) || charset().class(ch) == CharClass::Sigil
+}
+
+/// `q` without the characters [`is_query_char`] drops.
+pub(crate) fn sanitize_query(q: &str) -> String {
+    q.chars().filter(|&c| is_query_char(c)).collect()
+}
+
+/// `q` without the characters [`is_search_char`] drops, keeping filter tokens.
+pub(crate) fn sanitize_search(q: &str) -> String {
+    q.chars().filter(|&c| is_search_char(c)).collect()
+}
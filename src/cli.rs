@@ -1,28 +1,108 @@
+use std::collections::HashMap;
 use std::ffi::OsString;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::error::Result;
+use clap::ValueEnum;
+use crossterm::{
+    terminal::{self, ClearType},
+    tty::IsTty,
+};
+
+use crate::archive::{archive_dir, archive_try};
+use crate::config::Config;
+use crate::copy::{CopyMode, copy_tree};
+use crate::dedupe::{Fingerprint, fingerprint, group_duplicates};
+use crate::diff::{diff_trees, run_diff_tool};
+use crate::error::{Error, Result};
+use crate::expiry::Expiries;
+use crate::import::{ImportMode, import_dir, imported_from};
+use crate::model::TryDir;
+use crate::notify::notify_if_slow;
+use crate::output::{self, Printer};
+use crate::plugin::{PLUGIN_PREFIX, find_plugin, run_plugin};
+use crate::profile::StartupProfile;
+use crate::projects::{Project, ProjectLinks};
+use crate::query::parse_query;
+use crate::score::{MatchState, TieBreak, try_score_parts};
+use crate::scratch::{create_scratch, list_scratches, scratch_dir};
 use crate::selector::{ActionType, TrySelector};
-use crate::storage::fast_create_target_if_no_exact;
-use crate::tui;
-use crate::util::{dir_assign_for_shell, generate_clone_directory_name, is_git_uri, join_shell};
+use crate::shell::{Arg, Shell, ShellPipeline};
+use crate::snapshot::{self, Manifest};
+use crate::stack::{DirStack, all_stacks, session_key};
+use crate::stats::{GroupBy, Period, bar, group_counts, histogram, to_csv};
+use crate::storage::{
+    CollisionPolicy, KEEP_MARKER, META_DIR, branch_try_name, collision_policy, containing_try,
+    fast_create_target_if_no_exact, forget_pending_clone, free_sibling, is_protected,
+    list_dir_names, name_collision, new_try_name, normalize_query_for_match, pending_clone_marker,
+    pending_clones, rank_tries, resolve_try, scan_tries, score_tries, suffixed_name, write_blocked,
+};
+use crate::tags::Tags;
+use crate::timestamps::Timestamps;
+use crate::trash::{
+    TrashItem, TrashPolicy, find_trashed, move_to_trash, restore as restore_from_trash,
+};
+use crate::tui::{self, TermCaps};
+use crate::util::{
+    TreeUrl, generate_clone_directory_name, glob_match, is_git_uri, json_string, parse_tree_url,
+    rewrite_url, split_date_prefixed, tilde_path,
+};
+use crate::visits::{Visits, record_visit};
+
+/// Environment variable holding the try the shell was in before its last `try cd`, set by
+/// the printed shell steps and read by `try cd -`.
+pub(crate) const PREVIOUS_VAR: &str = "TRY_PREVIOUS";
+
+/// Size of a `--render-once` or `try show` listing when there is no terminal to measure.
+const LISTING_SIZE: (u16, u16) = (80, 24);
+
+/// Global flags that change how `try cd` behaves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CdFlags {
+    /// Skip confirmation prompts (`--yes`).
+    pub assume_yes: bool,
+    /// Act on tries carrying a keep marker (`--force`).
+    pub force: bool,
+    /// Print the selector once instead of running it (`--render-once`).
+    pub render_once: bool,
+}
+
+/// Prints the first screen of `selector` to stdout for `--render-once`, instead of running it.
+fn print_first_screen(selector: &mut TrySelector) -> Result<()> {
+    let size = terminal::size().unwrap_or(LISTING_SIZE);
+    selector.render_once(&mut io::stdout().lock(), TermCaps::listing(), size)
+}
 
-pub(crate) fn run_cd_flow(query_str: String, base_path: &Path) -> Result<()> {
+/// `try cd`. With `project`, only tries linked to it are offered, and tries created along
+/// the way are linked to it.
+pub(crate) fn run_cd_flow(
+    query_str: String,
+    base_path: &Path,
+    mut profile: Option<StartupProfile>,
+    flags: CdFlags,
+    project: Option<&Project>,
+    config: &Config,
+) -> Result<()> {
+    let only = project.map(|p| ProjectLinks::load(base_path).linked_to(p));
+    if flags.render_once {
+        let mut selector = TrySelector::new(&query_str, base_path.to_path_buf())?;
+        selector.only = only;
+        selector.tie_break = config.tie_break().to_vec();
+        selector.collapse_older = config.collapse_older.unwrap_or(false);
+        selector.size_warning = config.size_warning();
+        return print_first_screen(&mut selector);
+    }
+    auto_prune_trash(base_path, config);
     let trimmed = query_str.trim();
+    if trimmed == "-" {
+        return run_cd_previous(base_path);
+    }
     // Shorthand: if query looks like a git URI, produce a clone pipeline
     if !trimmed.is_empty() && is_git_uri(trimmed) {
-        if let Some(dir_name) = generate_clone_directory_name(trimmed, None) {
-            let full = base_path.join(dir_name);
-            let parts: Vec<String> = vec![
-                dir_assign_for_shell(&full),
-                "mkdir -p \"$dir\"".into(),
-                format!("git clone '{}' \"$dir\"", trimmed),
-                "touch \"$dir\"".into(),
-                "cd \"$dir\"".into(),
-            ];
-            println!("{}", join_shell(&parts));
-            return Ok(());
+        if generate_clone_directory_name(trimmed, None).is_some() {
+            return run_clone(trimmed, None, None, base_path, config);
         } else {
             let mut err = io::stderr();
             let _ = tui::warn(&mut err, &format!("Unable to parse git URI: {trimmed}"));
@@ -30,41 +110,2414 @@ pub(crate) fn run_cd_flow(query_str: String, base_path: &Path) -> Result<()> {
         }
     }
 
+    // Filter tokens (e.g. `@week`) only make sense in the selector, never as a new name
     if !trimmed.is_empty()
+        && parse_query(trimmed).filters.is_empty()
+        && write_blocked(base_path).is_none()
         && let Some(dir) = fast_create_target_if_no_exact(base_path, trimmed)?
     {
-        let parts: Vec<String> = vec![
-            dir_assign_for_shell(&dir),
-            "mkdir -p \"$dir\"".into(),
-            "touch \"$dir\"".into(),
-            "cd \"$dir\"".into(),
-        ];
-        println!("{}", join_shell(&parts));
+        let dir = match offer_restore(base_path, trimmed, flags.assume_yes)? {
+            Some(restored) => {
+                enter_try(base_path, &restored, false);
+                restored
+            }
+            None => {
+                enter_try(base_path, &dir, true);
+                dir
+            }
+        };
+        if let Some(project) = project {
+            link_to_project(base_path, &dir, project);
+        }
         return Ok(());
     }
 
+    if config.refresh_auto == Some(true) {
+        let _ = crate::refresh::spawn_background(base_path);
+    }
+    // Same name under several dates: choose inline instead of opening the selector
+    if !trimmed.is_empty() && parse_query(trimmed).filters.is_empty() {
+        let mut exact = crate::storage::exact_matches(base_path, trimmed);
+        if let Some(only) = &only {
+            exact.retain(|t| only.contains(&t.basename));
+        }
+        let choice = match exact.len() {
+            0 | 1 => ExactChoice::Selector,
+            _ if config.pick_latest == Some(true) => ExactChoice::Open(0),
+            _ if io::stdin().is_tty() && io::stderr().is_tty() => ask_exact(&exact)?,
+            _ => ExactChoice::Selector,
+        };
+        match choice {
+            ExactChoice::Open(pos) => {
+                enter_try(base_path, &exact[pos].path, false);
+                return Ok(());
+            }
+            ExactChoice::Quit => return Ok(()),
+            ExactChoice::Selector => {}
+        }
+    }
+
     let mut selector = TrySelector::new(&query_str, base_path.to_path_buf())?;
-    if let Some(sel) = selector.run()?
+    if let Some(p) = &mut profile {
+        p.mark("cache read");
+    }
+    selector.profile = profile;
+    selector.only = only;
+    selector.assume_yes = flags.assume_yes;
+    selector.force = flags.force;
+    selector.debounce = Duration::from_millis(config.debounce_ms.unwrap_or(0));
+    selector.poll_interval = config.poll_interval();
+    selector.frame_interval = config.frame_interval();
+    selector.tie_break = config.tie_break().to_vec();
+    selector.collapse_older = config.collapse_older.unwrap_or(false);
+    selector.size_warning = config.size_warning();
+    selector.archive_dir = archive_dir(base_path, config);
+    selector.stay_open_after_create = config.stay_open_after_create.unwrap_or(false);
+    let selection = selector.run()?;
+    if let Some(p) = &selector.profile {
+        eprint!("{}", p.report());
+    }
+    if let Some(project) = project {
+        for dir in &selector.created {
+            link_to_project(base_path, dir, project);
+        }
+    }
+    if let Some(sel) = selection
         && let Some(dir) = sel.path
     {
-        let mut parts: Vec<String> = vec![dir_assign_for_shell(&dir)];
         match sel.kind {
             ActionType::Mkdir => {
-                parts.push(r#"mkdir -p "$dir""#.into());
-                parts.push(r#"touch "$dir""#.into());
-                parts.push(r#"cd "$dir""#.into());
+                enter_try(base_path, &dir, true);
+                if let Some(project) = project {
+                    link_to_project(base_path, &dir, project);
+                }
             }
-            ActionType::Cd => {
-                parts.push(r#"touch "$dir""#.into());
-                parts.push(r#"cd "$dir""#.into());
+            ActionType::Cd => enter_try(base_path, &dir, false),
+            ActionType::Cancel => println!("{}", ShellPipeline::new().assign_dir(&dir).line()),
+        }
+    }
+    Ok(())
+}
+
+/// Prints the steps that enter the try at `dir`, creating it first when `create` is set, and
+/// records the visit.
+fn enter_try(base_path: &Path, dir: &Path, create: bool) {
+    let mut pipeline = ShellPipeline::new();
+    pipeline.assign_dir(dir);
+    if create {
+        pipeline.mkdir(&[Arg::Dir]);
+    }
+    pipeline.touch(Arg::Dir);
+    remember_previous(&mut pipeline, base_path, dir);
+    pipeline.cd(Arg::Dir);
+    println!("{}", pipeline.line());
+    record_visit(base_path, dir);
+}
+
+/// Records that the try at `dir` belongs to `project`; a failure is only reported.
+fn link_to_project(base_path: &Path, dir: &Path, project: &Project) {
+    let name = dir.file_name().unwrap_or_default().to_string_lossy();
+    let mut links = ProjectLinks::load(base_path);
+    links.set(&name, project);
+    if let Err(e) = links.save() {
+        let _ = tui::warn(&mut io::stderr(), &format!("Could not link {name}: {e}"));
+    }
+}
+
+/// Adds a step keeping the try the shell is in as [`PREVIOUS_VAR`], unless it is `target`
+/// itself or the shell is outside the tries directory.
+fn remember_previous(pipeline: &mut ShellPipeline, base_path: &Path, target: &Path) {
+    let current = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| containing_try(base_path, &cwd));
+    if let Some(current) = current
+        && current.file_name() != target.file_name()
+    {
+        pipeline.set_env(PREVIOUS_VAR, Arg::path(current));
+    }
+}
+
+/// `try cd -`: goes back to the try the shell was in before the last `try cd`, like `cd -`.
+/// Without a record from this shell session, falls back to the most recently visited try.
+fn run_cd_previous(base_path: &Path) -> Result<()> {
+    let current = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| containing_try(base_path, &cwd));
+    let previous = match std::env::var_os(PREVIOUS_VAR).filter(|v| !v.is_empty()) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => {
+            let current = current.as_deref().and_then(Path::file_name);
+            Visits::load(base_path)
+                .latest(current.map(|n| n.to_string_lossy()).as_deref())
+                .map(|name| base_path.join(name))
+        }
+    };
+    let mut err = io::stderr();
+    let Some(previous) = previous else {
+        let _ = tui::error(&mut err, "No previous try");
+        std::process::exit(1);
+    };
+    if !previous.is_dir() {
+        let msg = format!("Previous try {} no longer exists", tilde_path(&previous));
+        let _ = tui::error(&mut err, &msg);
+        std::process::exit(1);
+    }
+    enter_try(base_path, &previous, false);
+    Ok(())
+}
+
+/// `try browse`: opens the git remote of the try best matching `query` in the web browser.
+pub(crate) fn run_browse(query: &str, base_path: &Path, config: &Config) -> Result<()> {
+    let mut err = io::stderr();
+    let Some(t) = resolve_try(base_path, query, config.tie_break()) else {
+        let _ = tui::error(&mut err, &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    };
+    match crate::git::browse_url(&t.path) {
+        Ok(url) => {
+            if !crate::output::quiet() {
+                eprintln!("Opening {url}");
             }
-            ActionType::Cancel => {}
+            if let Err(e) = crate::util::open_with_system(&url) {
+                let _ = tui::error(&mut err, &format!("Could not open {url}: {e}"));
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Err(msg) => {
+            let _ = tui::error(&mut err, &msg);
+            std::process::exit(1);
         }
-        println!("{}", parts.join(" && "));
+    }
+}
+
+/// `try archive`: packs the try best matching `query` into a zstd tarball in the archive
+/// directory and removes it from the tries directory.
+pub(crate) fn run_archive(
+    query: &str,
+    base_path: &Path,
+    force: bool,
+    config: &Config,
+) -> Result<()> {
+    let mut err = io::stderr();
+    let Some(t) = resolve_try(base_path, query, config.tie_break()) else {
+        let _ = tui::error(&mut err, &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    };
+    if !force && is_protected(&t.path) {
+        let _ = tui::error(
+            &mut err,
+            &format!(
+                "{} is protected by {KEEP_MARKER}; pass --force to archive it",
+                t.basename
+            ),
+        );
+        std::process::exit(1);
+    }
+    let started = Instant::now();
+    let dest = archive_try(&t.path, &archive_dir(base_path, config), |_, _| {})?;
+    forget_pending_clone(base_path, &t.path);
+    notify_if_slow(started, &format!("Archived {}", t.basename));
+    if !crate::output::quiet() {
+        eprintln!("Archived {} → {}", t.basename, tilde_path(&dest));
     }
     Ok(())
 }
 
+/// `try scratch`: creates a dated scratch file and opens it in the editor; with `list`, picks
+/// an existing scratch file to open instead, or with `render_once` only prints the picker.
+pub(crate) fn run_scratch(
+    ext: Option<&str>,
+    list: bool,
+    render_once: bool,
+    base_path: &Path,
+    config: &Config,
+) -> Result<()> {
+    let mut err = io::stderr();
+    let dir = scratch_dir(base_path, config);
+    let path = if list {
+        let names = list_scratches(&dir);
+        if names.is_empty() {
+            let _ = tui::error(
+                &mut err,
+                &format!("No scratch files in {}", tilde_path(&dir)),
+            );
+            std::process::exit(1);
+        }
+        let mut selector = TrySelector::for_lines("", names);
+        if render_once {
+            return print_first_screen(&mut selector);
+        }
+        match selector.run()? {
+            Some(sel) if sel.kind == ActionType::Cd => dir.join(sel.path.unwrap_or_default()),
+            _ => return Ok(()),
+        }
+    } else {
+        let path = create_scratch(&dir, ext).map_err(|e| Error::from_write(&dir, e))?;
+        if !crate::output::quiet() {
+            eprintln!("Created {}", tilde_path(&path));
+        }
+        path
+    };
+    if let Err(e) = crate::util::open_in_editor(&path) {
+        let _ = tui::error(&mut err, &format!("Could not start the editor: {e}"));
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// `try pick`: fuzzy-selects one of the lines read from stdin and prints it to stdout, or
+/// with `render_once` prints the picker's first screen. Exits with status 1 when nothing is
+/// selected.
+pub(crate) fn run_pick(query: &str, render_once: bool) -> Result<()> {
+    let mut err = io::stderr();
+    let stdin = io::stdin();
+    if stdin.is_tty() {
+        let _ = tui::error(
+            &mut err,
+            "try pick reads candidates from stdin, e.g. `ls | try pick`",
+        );
+        std::process::exit(1);
+    }
+    let lines = read_candidates(stdin.lock())?;
+    if lines.is_empty() {
+        let _ = tui::error(&mut err, "No candidates on stdin");
+        std::process::exit(1);
+    }
+    let mut selector = TrySelector::for_lines(query, lines);
+    if render_once {
+        return print_first_screen(&mut selector);
+    }
+    match selector.run()? {
+        Some(sel) if sel.kind == ActionType::Cd => {
+            let line = sel.path.unwrap_or_default();
+            println!("{}", line.to_string_lossy());
+            Ok(())
+        }
+        _ => std::process::exit(1),
+    }
+}
+
+/// `try select`: runs the selector over the tries and prints the absolute path of the chosen
+/// one, creating it first when "Create new" was picked, or with `render_once` prints the
+/// selector's first screen. Exits 1 when cancelled.
+pub(crate) fn run_select(
+    query: &str,
+    render_once: bool,
+    base_path: &Path,
+    config: &Config,
+) -> Result<()> {
+    let mut selector = TrySelector::new(query, base_path.to_path_buf())?;
+    selector.tie_break = config.tie_break().to_vec();
+    selector.collapse_older = config.collapse_older.unwrap_or(false);
+    selector.archive_dir = archive_dir(base_path, config);
+    if render_once {
+        return print_first_screen(&mut selector);
+    }
+    let Some(sel) = selector.run()? else {
+        std::process::exit(1);
+    };
+    let Some(dir) = sel.path.filter(|_| sel.kind != ActionType::Cancel) else {
+        std::process::exit(1);
+    };
+    if sel.kind == ActionType::Mkdir {
+        std::fs::create_dir_all(&dir).map_err(|e| Error::from_write(&dir, e))?;
+    }
+    println!("{}", std::path::absolute(&dir)?.display());
+    Ok(())
+}
+
+/// What `try resolve` prints (`--emit`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Emit {
+    /// One JSON object: `query`, `name`, `path`, and whether the try `exists`.
+    Json,
+    /// Commands entering the try for sh, bash, zsh, and other POSIX shells.
+    Sh,
+    Fish,
+    Nu,
+    Pwsh,
+}
+
+/// Where `try resolve` leads: an existing try, or a new one to create at `path`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Resolved {
+    pub path: PathBuf,
+    pub exists: bool,
+}
+
+/// Resolves `query` the way `try cd` would without asking: the newest try whose name
+/// matches exactly (date prefix ignored), then, with `create`, a new dated try named after
+/// the query, else the best fuzzy match ordered by `ties`. `None` when nothing matches.
+pub(crate) fn resolve_target(
+    base_path: &Path,
+    query: &str,
+    create: bool,
+    ties: &[TieBreak],
+) -> Option<Resolved> {
+    let existing = |t: TryDir| Resolved {
+        path: t.path,
+        exists: true,
+    };
+    if let Some(t) = crate::storage::exact_matches(base_path, query)
+        .into_iter()
+        .next()
+    {
+        return Some(existing(t));
+    }
+    let trimmed = query.trim();
+    if create && !trimmed.is_empty() && parse_query(trimmed).filters.is_empty() {
+        return Some(Resolved {
+            path: base_path.join(new_try_name(trimmed)),
+            exists: false,
+        });
+    }
+    resolve_try(base_path, query, ties).map(existing)
+}
+
+/// The `emit` form of `target` for `query`. Shell forms enter the try (creating it first
+/// when new) like `try cd`, without the steps that depend on the calling shell's state.
+pub(crate) fn resolve_output(emit: Emit, query: &str, target: &Resolved) -> String {
+    let shell = match emit {
+        Emit::Json => {
+            let name = target
+                .path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            return format!(
+                "{{\"query\": {}, \"name\": {}, \"path\": {}, \"exists\": {}}}",
+                json_string(query),
+                json_string(&name),
+                json_string(&target.path.to_string_lossy()),
+                target.exists
+            );
+        }
+        Emit::Sh => Shell::Posix,
+        Emit::Fish => Shell::Fish,
+        Emit::Nu => Shell::Nu,
+        Emit::Pwsh => Shell::Pwsh,
+    };
+    let mut pipeline = ShellPipeline::for_shell(shell);
+    pipeline.assign_dir(&target.path);
+    if !target.exists {
+        pipeline.mkdir(&[Arg::Dir]);
+    }
+    pipeline.touch(Arg::Dir).cd(Arg::Dir);
+    pipeline.line()
+}
+
+/// `try resolve`: prints [`resolve_output`] for [`resolve_target`] without running the
+/// selector or touching the filesystem. Exits 1 when nothing matches.
+pub(crate) fn run_resolve(
+    query: &str,
+    base_path: &Path,
+    emit: Emit,
+    create: bool,
+    config: &Config,
+) -> Result<()> {
+    let Some(mut target) = resolve_target(base_path, query, create, config.tie_break()) else {
+        let _ = tui::error(&mut io::stderr(), &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    };
+    target.path = std::path::absolute(&target.path)?;
+    println!("{}", resolve_output(emit, query, &target));
+    Ok(())
+}
+
+/// `try <name> ...` for a name that is not built in: runs the `try-<name>` plugin from PATH
+/// (see [`crate::plugin::run_plugin`]) and exits with its status.
+pub(crate) fn run_external(args: &[OsString], base_path: &Path) -> Result<()> {
+    let Some((name, rest)) = args.split_first() else {
+        return Ok(());
+    };
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    let Some(exe) = find_plugin(name, &path_var) else {
+        let name = name.to_string_lossy();
+        let _ = tui::error(
+            &mut io::stderr(),
+            &format!("'{name}' is not a try command, and no {PLUGIN_PREFIX}{name} is on PATH"),
+        );
+        std::process::exit(1);
+    };
+    let status = run_plugin(&exe, rest, base_path)?;
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+/// Non-blank lines of `reader`, with trailing whitespace (including `\r`) removed.
+pub(crate) fn read_candidates(reader: impl io::BufRead) -> Result<Vec<String>> {
+    let mut out = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim_end();
+        if !line.is_empty() {
+            out.push(line.to_string());
+        }
+    }
+    Ok(out)
+}
+
+/// `try trash prune`: applies the trash retention policy now and lists what was purged.
+pub(crate) fn run_trash_prune(base_path: &Path, config: &Config) -> Result<()> {
+    let started = Instant::now();
+    let expired = trash_expired(base_path);
+    if !expired.is_empty() {
+        println!("Moved expired tries to trash: {}", expired.join(", "));
+    }
+    let purged = crate::trash::prune(base_path, &TrashPolicy::from_config(config))
+        .map_err(|e| Error::from_write(&base_path.join(crate::trash::TRASH_DIR), e))?;
+    notify_if_slow(started, &purge_summary(&purged));
+    let out = Printer::stdout();
+    if purged.is_empty() {
+        let kept = crate::trash::list_trash(base_path);
+        let bytes = kept.iter().map(|t| t.size).sum();
+        println!(
+            "Nothing to prune: {} in trash, {}",
+            items_label(kept.len()),
+            crate::util::format_human_size(bytes)
+        );
+        return Ok(());
+    }
+    let rows: Vec<Vec<String>> = purged
+        .iter()
+        .map(|t| {
+            vec![
+                t.name.clone(),
+                crate::util::format_human_size(t.size),
+                format!("trashed {}", tui::format_relative_time(Some(t.trashed))),
+            ]
+        })
+        .collect();
+    println!("{}", out.title(&purge_summary(&purged)));
+    println!("{}", out.table(&rows));
+    Ok(())
+}
+
+/// `try trash list`: every trash item, most recently trashed first, with its size, when and
+/// why it was trashed, and where it came from (`-` for items the trash index does not know).
+pub(crate) fn run_trash_list(base_path: &Path) -> Result<()> {
+    let mut items = crate::trash::list_trash(base_path);
+    if items.is_empty() {
+        println!("Trash is empty");
+        return Ok(());
+    }
+    items.sort_by(|a, b| b.trashed.cmp(&a.trashed).then_with(|| a.name.cmp(&b.name)));
+    let bytes = items.iter().map(|t| t.size).sum();
+    let out = Printer::stdout();
+    let rows: Vec<Vec<String>> = items
+        .iter()
+        .map(|t| {
+            vec![
+                t.name.clone(),
+                crate::util::format_human_size(t.size),
+                format!("trashed {}", tui::format_relative_time(Some(t.trashed))),
+                t.reason.clone().unwrap_or_else(|| "-".into()),
+                t.original.as_deref().map_or_else(|| "-".into(), tilde_path),
+            ]
+        })
+        .collect();
+    let title = format!(
+        "Trash: {}, {}",
+        items_label(items.len()),
+        crate::util::format_human_size(bytes)
+    );
+    output::page(&format!("{}\n{}\n", out.title(&title), out.table(&rows)))?;
+    Ok(())
+}
+
+/// `try trash empty`: after confirmation, removes every trash item on a worker thread while
+/// a progress line on stderr shows items per second and bytes freed. Esc, q, or Ctrl-C stops
+/// after the current item; whatever was not reached stays in the trash untouched.
+pub(crate) fn run_trash_empty(base_path: &Path, assume_yes: bool) -> Result<()> {
+    let mut err = io::stderr();
+    let items = crate::trash::list_trash(base_path);
+    if items.is_empty() {
+        println!("Trash is empty");
+        return Ok(());
+    }
+    let bytes: u64 = items.iter().map(|t| t.size).sum();
+    if !assume_yes {
+        if !io::stdin().is_tty() {
+            let _ = tui::error(
+                &mut err,
+                "try trash empty asks for confirmation; pass --yes to empty it without asking",
+            );
+            std::process::exit(1);
+        }
+        eprint!(
+            "Permanently remove {} ({}) from the trash? [y/N] ",
+            items_label(items.len()),
+            crate::util::format_human_size(bytes)
+        );
+        let mut line = String::new();
+        io::stdin().read_line(&mut line)?;
+        if !matches!(line.trim(), "y" | "Y" | "yes") {
+            println!("Trash left as it was");
+            return Ok(());
+        }
+    }
+
+    let started = Instant::now();
+    let cancel = AtomicBool::new(false);
+    let (done, freed) = (AtomicUsize::new(0), AtomicU64::new(0));
+    let watch = !output::quiet() && io::stderr().is_tty() && io::stdin().is_tty();
+    let removed = std::thread::scope(|s| {
+        let worker = s.spawn(|| {
+            crate::trash::empty(base_path, &items, &cancel, |item| {
+                done.fetch_add(1, Ordering::Relaxed);
+                freed.fetch_add(item.size, Ordering::Relaxed);
+            })
+        });
+        if watch {
+            let _ = watch_trash_empty(&worker, &cancel, items.len(), &done, &freed, started);
+        }
+        worker
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("trash worker panicked")))
+    })
+    .map_err(|e| Error::from_write(&base_path.join(crate::trash::TRASH_DIR), e))?;
+
+    let freed = removed.iter().map(|t| t.size).sum();
+    let summary = if removed.len() < items.len() {
+        format!(
+            "Cancelled: removed {} of {}, freed {}; the rest stays in the trash",
+            removed.len(),
+            items_label(items.len()),
+            crate::util::format_human_size(freed)
+        )
+    } else {
+        format!(
+            "Emptied trash: removed {}, freed {}",
+            items_label(removed.len()),
+            crate::util::format_human_size(freed)
+        )
+    };
+    notify_if_slow(started, &summary);
+    println!("{summary}");
+    Ok(())
+}
+
+/// Redraws the `try trash empty` progress line until `worker` finishes, in raw mode so a
+/// single Esc, q, or Ctrl-C sets `cancel`.
+fn watch_trash_empty<T>(
+    worker: &std::thread::ScopedJoinHandle<'_, T>,
+    cancel: &AtomicBool,
+    total: usize,
+    done: &AtomicUsize,
+    freed: &AtomicU64,
+    started: Instant,
+) -> Result<()> {
+    use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+    use crossterm::{cursor, execute};
+    const REDRAW_MS: u64 = 100;
+
+    let mut err = io::stderr();
+    terminal::enable_raw_mode()?;
+    let mut draw = || -> Result<()> {
+        execute!(err, cursor::Hide)?;
+        while !worker.is_finished() {
+            if event::poll(Duration::from_millis(REDRAW_MS)).unwrap_or(false)
+                && let Ok(Event::Key(KeyEvent {
+                    code, modifiers, ..
+                })) = event::read()
+                && (matches!(code, KeyCode::Esc | KeyCode::Char('q'))
+                    || code == KeyCode::Char('c') && modifiers.contains(KeyModifiers::CONTROL))
+            {
+                cancel.store(true, Ordering::Relaxed);
+            }
+            let n = done.load(Ordering::Relaxed);
+            let rate = n as f64 / started.elapsed().as_secs_f64().max(0.001);
+            let mut line = format!(
+                "Emptying trash: {n}/{total} items, {} freed, {rate:.1} items/s",
+                crate::util::format_human_size(freed.load(Ordering::Relaxed))
+            );
+            line.push_str(if cancel.load(Ordering::Relaxed) {
+                " — stopping after this item"
+            } else {
+                " — Esc to stop"
+            });
+            execute!(
+                err,
+                cursor::MoveToColumn(0),
+                terminal::Clear(ClearType::CurrentLine)
+            )?;
+            tui::dim(&mut err, &line)?;
+        }
+        Ok(())
+    };
+    let drawn = draw();
+    // Restore the terminal even when drawing failed
+    let _ = execute!(
+        io::stderr(),
+        cursor::MoveToColumn(0),
+        terminal::Clear(ClearType::CurrentLine),
+        cursor::Show
+    );
+    terminal::disable_raw_mode()?;
+    drawn
+}
+
+/// Prunes the trash at startup at most once a day, noting on stderr what was purged. Failures
+/// are left for `try trash prune` to report, as is trashing expired tries.
+fn auto_prune_trash(base_path: &Path, config: &Config) {
+    if !crate::trash::auto_prune_due(base_path) {
+        return;
+    }
+    let Ok(purged) = crate::trash::prune(base_path, &TrashPolicy::from_config(config)) else {
+        return;
+    };
+    if purged.is_empty() || crate::output::quiet() {
+        return;
+    }
+    let names: Vec<&str> = purged.iter().map(|t| t.name.as_str()).collect();
+    eprintln!("{}: {}", purge_summary(&purged), names.join(", "));
+}
+
+/// Moves ephemeral tries past their expiry to the trash and returns their names. Tries with a
+/// keep marker and the one the shell is in stay until a later run.
+fn trash_expired(base_path: &Path) -> Vec<String> {
+    let mut expiries = Expiries::load(base_path);
+    let expired = expiries.expired(SystemTime::now());
+    if expired.is_empty() {
+        return Vec::new();
+    }
+    let current = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| containing_try(base_path, &cwd));
+    let mut trashed = Vec::new();
+    for name in expired {
+        let path = base_path.join(&name);
+        let in_use = current
+            .as_deref()
+            .is_some_and(|c| c.file_name() == path.file_name());
+        if path.is_dir()
+            && !in_use
+            && !is_protected(&path)
+            && move_to_trash(base_path, &path, "expired").is_ok()
+        {
+            trashed.push(name);
+        }
+    }
+    let names = list_dir_names(base_path);
+    let _ = expiries.save(&names.iter().map(String::as_str).collect());
+    trashed
+}
+
+/// E.g. "Purged 2 items from trash, freed 1.5G".
+fn purge_summary(purged: &[TrashItem]) -> String {
+    let bytes = purged.iter().map(|t| t.size).sum();
+    format!(
+        "Purged {} from trash, freed {}",
+        items_label(purged.len()),
+        crate::util::format_human_size(bytes)
+    )
+}
+
+fn items_label(n: usize) -> String {
+    if n == 1 {
+        "1 item".into()
+    } else {
+        format!("{n} items")
+    }
+}
+
+/// What `try clone` does when its target name is already taken (`--existing`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum CloneConflict {
+    /// Change into the existing directory.
+    Cd,
+    /// Clone into the first free `<name>-2`, `<name>-3`, ... sibling instead.
+    Suffix,
+    /// Fast-forward the existing clone with `git pull --ff-only`, then change into it.
+    Pull,
+}
+
+/// `try clone`: prints the shell pipeline cloning `git_uri` into a dated directory (or
+/// `name`). When that name is taken, under this or another date, `existing` decides what
+/// happens to the taken directory; without it `names.collision` does, which by default asks
+/// the user on a terminal and elsewhere fails before anything runs.
+pub(crate) fn run_clone(
+    git_uri: &str,
+    name: Option<&str>,
+    existing: Option<CloneConflict>,
+    base_path: &Path,
+    config: &Config,
+) -> Result<()> {
+    let mut err = io::stderr();
+    let spec = CloneSpec::resolve(git_uri, config);
+    let Some(dir_name) = spec.dir_name(name) else {
+        let _ = tui::error(&mut err, &format!("Unable to parse git URI: {git_uri}"));
+        std::process::exit(1);
+    };
+    let mut target = base_path.join(&dir_name);
+    let mut conflict = None;
+    let tries = scan_tries(base_path);
+    let taken = if target.exists() {
+        Some((target.clone(), dir_name.clone()))
+    } else {
+        name_collision(&tries, &dir_name).map(|t| (t.path.clone(), t.basename.clone()))
+    };
+    if let Some((taken_path, taken_name)) = taken {
+        let choice = match (existing, collision_policy()) {
+            (Some(choice), _) => choice,
+            (None, CollisionPolicy::Reuse) => CloneConflict::Cd,
+            (None, CollisionPolicy::Suffix) => CloneConflict::Suffix,
+            (None, CollisionPolicy::Ask) if io::stdin().is_tty() => {
+                match ask_clone_conflict(&taken_name)? {
+                    Some(choice) => choice,
+                    None => std::process::exit(1),
+                }
+            }
+            (None, policy) => {
+                let why = if policy == CollisionPolicy::Fail {
+                    " (names.collision = fail)"
+                } else {
+                    ""
+                };
+                let msg = format!(
+                    "{taken_name} already exists{why}; pass --existing cd, suffix, or pull"
+                );
+                let _ = tui::error(&mut err, &msg);
+                std::process::exit(1);
+            }
+        };
+        if choice == CloneConflict::Pull && !taken_path.join(".git").exists() {
+            let _ = tui::error(
+                &mut err,
+                &format!("{taken_name} is not a git clone; use --existing cd or suffix"),
+            );
+            std::process::exit(1);
+        }
+        if choice == CloneConflict::Suffix {
+            target = free_sibling(&base_path.join(suffixed_name(&tries, &dir_name)));
+        } else {
+            target = taken_path;
+            conflict = Some(choice);
+        }
+    }
+    let mut pipeline = clone_commands(&spec, base_path, &target, conflict);
+    if conflict != Some(CloneConflict::Cd)
+        && let Some(step) = clone_notify_step(&target)
+    {
+        pipeline.run(&step);
+    }
+    println!("{}", pipeline.line());
+    Ok(())
+}
+
+/// `try clone --resume`: starts over the unfinished clone `name` (or the only unfinished
+/// clone containing it). Whatever the failed attempt left behind goes to the trash after
+/// confirmation (`assume_yes` skips it); a kept try is left alone unless `force`.
+pub(crate) fn run_clone_resume(
+    name: &str,
+    base_path: &Path,
+    assume_yes: bool,
+    force: bool,
+    config: &Config,
+) -> Result<()> {
+    let mut err = io::stderr();
+    let pending = pending_clones(base_path);
+    let matches: Vec<&String> = match pending.get_key_value(name) {
+        Some((exact, _)) => vec![exact],
+        None => pending.keys().filter(|k| k.contains(name)).collect(),
+    };
+    let [found] = matches[..] else {
+        let mut names: Vec<&str> = pending.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        let msg = match (matches.len(), names.is_empty()) {
+            (_, true) => "No unfinished clones".to_string(),
+            (0, _) => format!(
+                "No unfinished clone matches '{name}'; unfinished: {}",
+                names.join(", ")
+            ),
+            _ => format!("'{name}' matches several unfinished clones; give the full name"),
+        };
+        let _ = tui::error(&mut err, &msg);
+        std::process::exit(1);
+    };
+    let target = base_path.join(found);
+    if target.symlink_metadata().is_ok() {
+        if !force && is_protected(&target) {
+            let _ = tui::error(
+                &mut err,
+                &format!("{found} is protected by {KEEP_MARKER}; pass --force to clone it again"),
+            );
+            std::process::exit(1);
+        }
+        if !assume_yes {
+            if !io::stdin().is_tty() {
+                let _ = tui::error(
+                    &mut err,
+                    &format!(
+                        "{found} already exists; pass --yes to move it to the trash and clone again"
+                    ),
+                );
+                std::process::exit(1);
+            }
+            eprint!("Move the partial clone {found} to the trash and clone again? [y/N] ");
+            let mut line = String::new();
+            io::stdin().read_line(&mut line)?;
+            if !matches!(line.trim(), "y" | "Y" | "yes") {
+                eprintln!("Left {found} as it was");
+                return Ok(());
+            }
+        }
+        move_to_trash(base_path, &target, "unfinished clone")
+            .map_err(|e| Error::from_write(&target, e))?;
+    }
+    let spec = CloneSpec::resolve(&pending[found], config);
+    let mut pipeline = clone_commands(&spec, base_path, &target, None);
+    if let Some(step) = clone_notify_step(&target) {
+        pipeline.run(&step);
+    }
+    println!("{}", pipeline.line());
+    Ok(())
+}
+
+/// Final clone step announcing the finished clone when `notify.after_secs` is set.
+fn clone_notify_step(target: &Path) -> Option<Vec<Arg>> {
+    let name = target.file_name()?.to_string_lossy();
+    crate::notify::shell_step(&format!("Cloned {name}"))
+}
+
+/// What `try clone` fetches, resolved from the URI the user gave.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct CloneSpec {
+    /// The URI as given; recorded in the pending marker for `--resume`.
+    pub source: String,
+    /// URL handed to `git clone`, after `clone.rewrite` rules.
+    pub url: String,
+    /// Clone only the latest commit and fetch file contents on demand (`clone.shallow`).
+    pub shallow: bool,
+    /// Folder link (`.../tree/<ref>/<path>`), checked out sparsely.
+    pub tree: Option<TreeUrl>,
+}
+
+impl CloneSpec {
+    /// Dated try name for the clone: `name` when given, else `user-repo` from the URI, or the
+    /// last folder of a folder link.
+    pub(crate) fn dir_name(&self, name: Option<&str>) -> Option<String> {
+        match (&self.tree, name) {
+            (Some(tree), None) => Some(new_try_name(tree.subdir.rsplit('/').next().unwrap_or(""))),
+            _ => generate_clone_directory_name(&self.source, name),
+        }
+    }
+
+    pub(crate) fn resolve(uri: &str, config: &Config) -> Self {
+        let tree = parse_tree_url(uri);
+        let repo = tree.as_ref().map_or(uri, |t| t.repo.as_str());
+        CloneSpec {
+            source: uri.to_string(),
+            url: rewrite_url(repo, &config.clone_rewrites),
+            shallow: config.clone_shallow.unwrap_or(false),
+            tree,
+        }
+    }
+}
+
+/// Shell pipeline for cloning `spec` into `target`, or, for an existing target, changing into it
+/// (`Cd`) or pulling first (`Pull`). A clone records a pending marker first and removes it
+/// only after `git clone` succeeds, so an interrupted clone stays visible. Folder links are
+/// cloned without file contents, check out only that folder, and end up inside it.
+pub(crate) fn clone_commands(
+    spec: &CloneSpec,
+    base_path: &Path,
+    target: &Path,
+    existing: Option<CloneConflict>,
+) -> ShellPipeline {
+    let mut pipeline = ShellPipeline::new();
+    pipeline.assign_dir(target);
+    match existing {
+        None | Some(CloneConflict::Suffix) => {
+            let name = target.file_name().unwrap_or_default().to_string_lossy();
+            let marker = pending_clone_marker(base_path, &name);
+            let marker_dir = marker.parent().unwrap_or(base_path).to_path_buf();
+            pipeline
+                .mkdir(&[Arg::Dir, Arg::path(marker_dir)])
+                .write_line(&spec.source, Arg::path(&marker));
+            let mut clone = vec![Arg::bare("git"), Arg::bare("clone")];
+            if spec.shallow {
+                clone.extend([Arg::bare("--depth"), Arg::bare("1")]);
+            }
+            if spec.shallow || spec.tree.is_some() {
+                clone.push(Arg::bare("--filter=blob:none"));
+            }
+            if let Some(tree) = &spec.tree {
+                clone.extend([
+                    Arg::bare("--sparse"),
+                    Arg::bare("--branch"),
+                    Arg::lit(&tree.git_ref),
+                ]);
+            }
+            clone.extend([Arg::lit(&spec.url), Arg::Dir]);
+            pipeline.run(&clone);
+            if let Some(tree) = &spec.tree {
+                pipeline.run(&[
+                    Arg::bare("git"),
+                    Arg::bare("-C"),
+                    Arg::Dir,
+                    Arg::bare("sparse-checkout"),
+                    Arg::bare("set"),
+                    Arg::lit(&tree.subdir),
+                ]);
+            }
+            pipeline.remove_file(Arg::path(marker));
+        }
+        Some(CloneConflict::Pull) => {
+            pipeline.run(&[
+                Arg::bare("git"),
+                Arg::bare("-C"),
+                Arg::Dir,
+                Arg::bare("pull"),
+                Arg::bare("--ff-only"),
+            ]);
+        }
+        Some(CloneConflict::Cd) => {}
+    }
+    pipeline.touch(Arg::Dir);
+    remember_previous(&mut pipeline, base_path, target);
+    match &spec.tree {
+        Some(tree) => pipeline.cd(Arg::DirJoin(tree.subdir.clone())),
+        None => pipeline.cd(Arg::Dir),
+    };
+    pipeline
+}
+
+/// URIs listed in a `--from-file` list: one per line, blank lines and `#` comments skipped.
+pub(crate) fn read_clone_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.split_once('#').map_or(line, |(uri, _)| uri).trim())
+        .filter(|uri| !uri.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// GraphQL query listing an owner's repositories a page at a time, for `gh api --paginate`.
+const ORG_REPOS_QUERY: &str = "query($owner: String!, $endCursor: String) { \
+    repositoryOwner(login: $owner) { repositories(first: 100, after: $endCursor) { \
+    nodes { url } pageInfo { hasNextPage endCursor } } } }";
+
+/// Clone URLs of the repositories in the GitHub organization `org` (`myorg`,
+/// `github.com/myorg`, or its URL) whose names match `filter`, listed with `gh` page by page
+/// so that no repository is left out.
+pub(crate) fn org_repos(org: &str, filter: Option<&str>) -> Result<Vec<String>> {
+    let Some(org) = parse_org(org) else {
+        let _ = tui::error(
+            &mut io::stderr(),
+            &format!("--org expects a GitHub organization such as github.com/myorg, got '{org}'"),
+        );
+        std::process::exit(1);
+    };
+    let output = std::process::Command::new("gh")
+        .args(["api", "graphql", "--paginate", "-f"])
+        .arg(format!("owner={org}"))
+        .args(["-f", &format!("query={ORG_REPOS_QUERY}")])
+        .args(["--jq", ".data.repositoryOwner.repositories.nodes[]?.url"])
+        .stderr(std::process::Stdio::inherit())
+        .output();
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        Ok(_) => std::process::exit(1),
+        Err(_) => {
+            let _ = tui::error(
+                &mut io::stderr(),
+                "try clone --org lists repositories with the GitHub CLI; install `gh` and run `gh auth login`",
+            );
+            std::process::exit(1);
+        }
+    };
+    let listed = String::from_utf8_lossy(&output.stdout);
+    if listed.trim().is_empty() {
+        let _ = tui::error(
+            &mut io::stderr(),
+            &format!("No repositories found for '{org}' on GitHub"),
+        );
+        std::process::exit(1);
+    }
+    Ok(listed
+        .lines()
+        .map(str::trim)
+        .filter(|url| {
+            let name = url.rsplit('/').next().unwrap_or(url);
+            !url.is_empty() && filter.is_none_or(|glob| glob_match(glob, name))
+        })
+        .map(str::to_string)
+        .collect())
+}
+
+/// Organization name from `myorg`, `github.com/myorg`, or `https://github.com/myorg/`;
+/// `None` for other hosts or nested paths.
+pub(crate) fn parse_org(org: &str) -> Option<&str> {
+    let rest = org.trim().trim_end_matches('/');
+    let rest = rest
+        .strip_prefix("https://")
+        .or_else(|| rest.strip_prefix("http://"))
+        .unwrap_or(rest);
+    let rest = rest.strip_prefix("github.com/").unwrap_or(rest);
+    (!rest.is_empty() && !rest.contains(['/', ':', ' '])).then_some(rest)
+}
+
+/// `try clone --from-file` / `--org`: clones each URI into its own dated try, one after
+/// another, with git's progress and a summary on stderr. Existing targets are skipped and
+/// failures reported at the end without stopping the batch, which then ends in
+/// [`Error::ClonesFailed`]. Prints nothing for the shell to run, so the working directory
+/// stays put.
+pub(crate) fn run_clone_batch(uris: &[String], base_path: &Path, config: &Config) -> Result<()> {
+    let mut err = io::stderr();
+    let started = Instant::now();
+    let (mut cloned, mut skipped) = (0usize, 0usize);
+    let mut failed: Vec<String> = Vec::new();
+    for (n, uri) in uris.iter().enumerate() {
+        let spec = CloneSpec::resolve(uri, config);
+        let Some(dir_name) = spec.dir_name(None) else {
+            failed.push(format!("{uri}: not a git URI"));
+            continue;
+        };
+        let target = base_path.join(&dir_name);
+        if target.exists() {
+            skipped += 1;
+            let _ = tui::warn(&mut err, &format!("{dir_name} already exists; skipped"));
+            continue;
+        }
+        if !output::quiet() {
+            eprintln!("[{}/{}] {uri} → {dir_name}", n + 1, uris.len());
+        }
+        match clone_now(&spec, base_path, &target) {
+            Ok(()) => cloned += 1,
+            Err(e) => failed.push(format!("{uri}: {e}")),
+        }
+    }
+    notify_if_slow(started, &format!("Cloned {cloned} of {}", uris.len()));
+    let mut summary = format!("Cloned {cloned} of {}", uris.len());
+    if skipped > 0 {
+        summary.push_str(&format!(", {skipped} skipped"));
+    }
+    if !failed.is_empty() {
+        summary.push_str(&format!(", {} failed", failed.len()));
+    }
+    if !output::quiet() {
+        eprintln!("{summary}");
+    }
+    for failure in &failed {
+        let _ = tui::error(&mut err, failure);
+    }
+    if !failed.is_empty() {
+        return Err(Error::ClonesFailed {
+            failed: failed.len(),
+            total: uris.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Runs the clone steps of [`clone_commands`] for a new `target` directly instead of through
+/// the shell: pending marker, `git clone`, sparse checkout for folder links, marker removal.
+fn clone_now(spec: &CloneSpec, base_path: &Path, target: &Path) -> io::Result<()> {
+    use std::process::{Command, Stdio};
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    let marker = pending_clone_marker(base_path, &name);
+    if let Some(dir) = marker.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&marker, format!("{}\n", spec.source))?;
+    let mut args: Vec<&str> = vec!["clone"];
+    if spec.shallow {
+        args.extend(["--depth", "1"]);
+    }
+    if spec.shallow || spec.tree.is_some() {
+        args.push("--filter=blob:none");
+    }
+    if let Some(tree) = &spec.tree {
+        args.extend(["--sparse", "--branch", &tree.git_ref]);
+    }
+    let run = |cmd: &mut Command| -> io::Result<()> {
+        let status = cmd.stdout(Stdio::null()).status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("git failed ({status})")))
+        }
+    };
+    run(Command::new("git").args(&args).arg(&spec.url).arg(target))?;
+    if let Some(tree) = &spec.tree {
+        run(Command::new("git").arg("-C").arg(target).args([
+            "sparse-checkout",
+            "set",
+            &tree.subdir,
+        ]))?;
+    }
+    std::fs::remove_file(&marker)
+}
+
+enum ExactChoice {
+    Open(usize),
+    Selector,
+    Quit,
+}
+
+/// Lists `tries` (all named alike) on stderr and asks which to open; Enter opens the first.
+fn ask_exact(tries: &[TryDir]) -> Result<ExactChoice> {
+    let out = Printer::new(output::colors_enabled(true));
+    let rows: Vec<Vec<String>> = tries
+        .iter()
+        .enumerate()
+        .map(|(i, t)| {
+            vec![
+                format!("{})", i + 1),
+                t.basename.clone(),
+                format!("modified {}", tui::format_relative_time(t.mtime)),
+            ]
+        })
+        .collect();
+    eprintln!("{}", out.table(&rows));
+    let count = tries.len();
+    loop {
+        eprint!("Open which? [1-{count}, Enter = 1, s = selector, q = quit] ");
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            return Ok(ExactChoice::Quit);
+        }
+        match line.trim() {
+            "" => return Ok(ExactChoice::Open(0)),
+            "s" => return Ok(ExactChoice::Selector),
+            "q" => return Ok(ExactChoice::Quit),
+            n => {
+                if let Ok(n) = n.parse::<usize>()
+                    && (1..=count).contains(&n)
+                {
+                    return Ok(ExactChoice::Open(n - 1));
+                }
+            }
+        }
+    }
+}
+
+/// When a trashed try is named exactly `query` (date prefix ignored), asks on stderr whether
+/// to restore it instead of creating an empty one, and restores it on yes. `assume_yes`
+/// restores without asking; without a terminal to ask on, nothing is restored.
+fn offer_restore(base_path: &Path, query: &str, assume_yes: bool) -> Result<Option<PathBuf>> {
+    let Some(item) = find_trashed(base_path, &normalize_query_for_match(query)) else {
+        return Ok(None);
+    };
+    if !assume_yes {
+        if !io::stdin().is_tty() {
+            return Ok(None);
+        }
+        eprint!(
+            "{} is in the trash (trashed {}, {}). Restore it? [Y/n] ",
+            item.name,
+            tui::format_relative_time(Some(item.trashed)),
+            crate::util::format_human_size(item.size)
+        );
+        let mut line = String::new();
+        io::stdin().read_line(&mut line)?;
+        if !matches!(line.trim(), "" | "y" | "Y" | "yes") {
+            return Ok(None);
+        }
+    }
+    let restored = restore_from_trash(base_path, &item)?;
+    if !output::quiet() {
+        eprintln!("Restored {} from trash", item.name);
+    }
+    Ok(Some(restored))
+}
+
+/// Asks on stderr what to do about the existing clone target `name`; `None` cancels.
+fn ask_clone_conflict(name: &str) -> Result<Option<CloneConflict>> {
+    loop {
+        eprint!("{name} already exists. [c]d into it, [s]uffixed clone, [p]ull, [q]uit? [c] ");
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        match line.trim() {
+            "" | "c" => return Ok(Some(CloneConflict::Cd)),
+            "s" => return Ok(Some(CloneConflict::Suffix)),
+            "p" => return Ok(Some(CloneConflict::Pull)),
+            "q" => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// `try unshallow`: fetches the full history of the best-matching try when it is a shallow
+/// clone (see `clone.shallow`).
+pub(crate) fn run_unshallow(query: &str, base_path: &Path, config: &Config) -> Result<()> {
+    let mut err = io::stderr();
+    let Some(t) = resolve_try(base_path, query, config.tie_break()) else {
+        let _ = tui::error(&mut err, &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    };
+    if !t.path.join(".git").exists() {
+        let _ = tui::error(&mut err, &format!("{} is not a git repository", t.basename));
+        std::process::exit(1);
+    }
+    if !crate::git::is_shallow(&t.path) {
+        if !crate::output::quiet() {
+            eprintln!("{} already has its full history", t.basename);
+        }
+        return Ok(());
+    }
+    if !crate::git::unshallow(&t.path)?.success() {
+        let _ = tui::error(
+            &mut err,
+            &format!("git fetch --unshallow failed in {}", t.basename),
+        );
+        std::process::exit(1);
+    }
+    if !crate::output::quiet() {
+        eprintln!("Fetched the full history of {}", t.basename);
+    }
+    Ok(())
+}
+
+/// `try refresh`: asks the remotes of git-backed tries for new commits and caches the
+/// ahead/behind counts shown in the selector. Tries checked within `refresh.ttl_secs` are
+/// skipped unless `all`; with `background` a detached refresh is started instead.
+pub(crate) fn run_refresh(
+    base_path: &Path,
+    background: bool,
+    all: bool,
+    jobs: Option<usize>,
+    config: &Config,
+) -> Result<()> {
+    let mut err = io::stderr();
+    if background {
+        return Ok(crate::refresh::spawn_background(base_path)?);
+    }
+    let jobs = jobs
+        .or(config.refresh_jobs.map(|n| n as usize))
+        .unwrap_or(crate::refresh::DEFAULT_JOBS);
+    let ttl = if all {
+        Duration::ZERO
+    } else {
+        Duration::from_secs(
+            config
+                .refresh_ttl_secs
+                .unwrap_or(crate::refresh::DEFAULT_TTL_SECS),
+        )
+    };
+    let tries = scan_tries(base_path);
+    let summary = match crate::refresh::refresh(base_path, &tries, jobs, ttl) {
+        Ok(summary) => summary,
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            let _ = tui::warn(&mut err, "A refresh is already running");
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if !crate::output::quiet() {
+        let noun = if summary.checked == 1 {
+            "remote"
+        } else {
+            "remotes"
+        };
+        let mut line = format!("Checked {} {noun}", summary.checked);
+        if summary.fresh > 0 {
+            line.push_str(&format!(", {} still fresh", summary.fresh));
+        }
+        if summary.unknown > 0 {
+            line.push_str(&format!(
+                ", {} without upstream or unreachable",
+                summary.unknown
+            ));
+        }
+        eprintln!("{line}");
+    }
+    Ok(())
+}
+
+/// `try which`: prints the absolute path of the try best matching `query`, or with `all`
+/// every match as `score<TAB>path`, best first.
+pub(crate) fn run_which(query: &str, base_path: &Path, all: bool, config: &Config) -> Result<()> {
+    let ranked = rank_tries(base_path, query, config.tie_break());
+    if ranked.is_empty() {
+        let _ = tui::error(&mut io::stderr(), &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    }
+    let shown = if all { &ranked[..] } else { &ranked[..1] };
+    for t in shown {
+        let path = std::path::absolute(&t.path)?;
+        if all {
+            println!("{:.2}\t{}", t.score, path.display());
+        } else {
+            println!("{}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// `try show`: prints the first `limit` matches for `query` as the selector draws them,
+/// sizes and git status included. Fails when nothing matches.
+pub(crate) fn run_show(query: &str, base_path: &Path, limit: usize, config: &Config) -> Result<()> {
+    let mut selector = TrySelector::new(query, base_path.to_path_buf())?;
+    selector.tie_break = config.tie_break().to_vec();
+    let (width, _) = terminal::size().unwrap_or(LISTING_SIZE);
+    let mut rows = Vec::new();
+    if selector.render_top(&mut rows, TermCaps::listing(), limit, width)? == 0 {
+        let _ = tui::error(&mut io::stderr(), &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    }
+    output::page(&String::from_utf8_lossy(&rows))?;
+    Ok(())
+}
+
+/// `try man`: prints the manual page of the (sub)command named by `names` (empty for the top
+/// level), or with `install` writes every page to `<install>/share/man/man1`, printing each
+/// path.
+pub(crate) fn run_man(cmd: &clap::Command, names: &[String], install: Option<&Path>) -> Result<()> {
+    let pages = crate::man::pages(cmd);
+    if let Some(prefix) = install {
+        let dir = prefix.join("share").join("man").join("man1");
+        std::fs::create_dir_all(&dir).map_err(|e| Error::from_write(&dir, e))?;
+        for (file, page) in &pages {
+            let path = dir.join(file);
+            std::fs::write(&path, page).map_err(|e| Error::from_write(&path, e))?;
+            println!("{}", path.display());
+        }
+        return Ok(());
+    }
+    // Resolve aliases such as `ls` to the command's own name
+    let mut file = vec![cmd.get_name().to_string()];
+    let mut current = cmd;
+    for name in names {
+        let Some(sub) = current.find_subcommand(name) else {
+            let _ = tui::error(
+                &mut io::stderr(),
+                &format!("No command '{}'", names.join(" ")),
+            );
+            std::process::exit(1);
+        };
+        file.push(sub.get_name().to_string());
+        current = sub;
+    }
+    let file = format!("{}.1", file.join("-"));
+    if let Some((_, page)) = pages.iter().find(|(f, _)| *f == file) {
+        print!("{page}");
+    }
+    Ok(())
+}
+
+/// `try list`: prints every try matching `query` as an absolute path, best first. With
+/// `scores`, prints every try instead, matching or not, as `score<TAB>name<TAB>created<TAB>
+/// modified<TAB>visits<TAB>path`: the total and its parts from `score::try_score_parts`. An
+/// exact name match is listed first regardless of its score.
+pub(crate) fn run_list(
+    query: &str,
+    base_path: &Path,
+    scores: bool,
+    branch: bool,
+    project: Option<&Project>,
+    config: &Config,
+) -> Result<()> {
+    let only = project.map(|p| ProjectLinks::load(base_path).linked_to(p));
+    let listed = |t: &TryDir| only.as_ref().is_none_or(|only| only.contains(&t.basename));
+    // Branch column, read from .git/HEAD; `-` for plain folders
+    let head = |t: &TryDir| {
+        if !branch {
+            return String::new();
+        }
+        let label = crate::git::read_head(&t.path);
+        format!("{}\t", label.as_ref().map_or("-", |h| h.label()))
+    };
+    let mut out = String::new();
+    if !scores {
+        for t in rank_tries(base_path, query, config.tie_break())
+            .into_iter()
+            .filter(listed)
+        {
+            out.push_str(&format!(
+                "{}{}\n",
+                head(&t),
+                std::path::absolute(&t.path)?.display()
+            ));
+        }
+        output::page(&out)?;
+        return Ok(());
+    }
+    let text = crate::charset::sanitize_query(query.trim());
+    let (tries, _) = score_tries(base_path, query, config.tie_break());
+    for t in tries.into_iter().filter(listed) {
+        let parts = try_score_parts(&t, &text, &mut MatchState::default());
+        out.push_str(&format!(
+            "{:.2}\t{:.2}\t{:.2}\t{:.2}\t{:.2}\t{}{}\n",
+            parts.total(),
+            parts.name,
+            parts.created,
+            parts.modified,
+            parts.visits,
+            head(&t),
+            std::path::absolute(&t.path)?.display()
+        ));
+    }
+    output::page(&out)?;
+    Ok(())
+}
+
+/// `try push`: remembers the working directory on this shell's stack and enters the try best
+/// matching `query`.
+pub(crate) fn run_push(query: &str, base_path: &Path, config: &Config) -> Result<()> {
+    let Some(t) = resolve_try(base_path, query, config.tie_break()) else {
+        let _ = tui::error(&mut io::stderr(), &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    };
+    let mut stack = DirStack::load(base_path, &session_key());
+    stack.push(std::env::current_dir()?);
+    stack
+        .save()
+        .map_err(|e| Error::from_write(&base_path.join(META_DIR), e))?;
+    enter_try(base_path, &t.path, false);
+    Ok(())
+}
+
+/// `try pop`: returns to the directory on top of this shell's stack. An entry that no longer
+/// exists is dropped with an error, so the next `try pop` moves on.
+pub(crate) fn run_pop(base_path: &Path) -> Result<()> {
+    let mut err = io::stderr();
+    let mut stack = DirStack::load(base_path, &session_key());
+    let Some(dir) = stack.pop() else {
+        let _ = tui::error(&mut err, "Directory stack is empty");
+        std::process::exit(1);
+    };
+    stack
+        .save()
+        .map_err(|e| Error::from_write(&base_path.join(META_DIR), e))?;
+    if !dir.is_dir() {
+        let msg = format!("{} no longer exists; dropped it", tilde_path(&dir));
+        let _ = tui::error(&mut err, &msg);
+        std::process::exit(1);
+    }
+    let mut pipeline = ShellPipeline::new();
+    pipeline.assign_dir(&dir);
+    remember_previous(&mut pipeline, base_path, &dir);
+    println!("{}", pipeline.cd(Arg::Dir).line());
+    Ok(())
+}
+
+/// `try stack`: lists this shell's directory stack, the next `try pop` target first.
+pub(crate) fn run_stack(base_path: &Path) -> Result<()> {
+    let stack = DirStack::load(base_path, &session_key());
+    if stack.entries().is_empty() {
+        if !crate::output::quiet() {
+            eprintln!("Directory stack is empty");
+        }
+        return Ok(());
+    }
+    for (i, dir) in stack.entries().iter().rev().enumerate() {
+        println!("{i}  {}", tilde_path(dir));
+    }
+    Ok(())
+}
+
+/// The project named by `--project`; exits with an error when `spec` is neither a directory
+/// nor a git URL.
+pub(crate) fn resolve_project(spec: &str) -> Project {
+    Project::resolve(spec).unwrap_or_else(|| {
+        let msg = format!("'{spec}' is neither a directory nor a git URL");
+        let _ = tui::error(&mut io::stderr(), &msg);
+        std::process::exit(1);
+    })
+}
+
+/// `try link`: links the try best matching `query` to the project `spec`, or removes its link
+/// with `clear`.
+pub(crate) fn run_link(
+    query: &str,
+    base_path: &Path,
+    spec: &str,
+    clear: bool,
+    config: &Config,
+) -> Result<()> {
+    let Some(t) = resolve_try(base_path, query, config.tie_break()) else {
+        let _ = tui::error(&mut io::stderr(), &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    };
+    let mut links = ProjectLinks::load(base_path);
+    let message = if clear {
+        if !links.remove(&t.basename) {
+            eprintln!("{} is not linked to a project", t.basename);
+            return Ok(());
+        }
+        format!("Unlinked {}", t.basename)
+    } else {
+        let project = resolve_project(spec);
+        links.set(&t.basename, &project);
+        format!("Linked {} to {}", t.basename, project.key())
+    };
+    links
+        .save()
+        .map_err(|e| Error::from_write(&base_path.join(META_DIR), e))?;
+    if !crate::output::quiet() {
+        eprintln!("{message}");
+    }
+    Ok(())
+}
+
+/// `try tag`: adds the `add` tags to the best-matching try and removes the `remove` ones,
+/// then reports what it carries; with neither, prints its tags one per line. Tags are
+/// already normalized by [`crate::tags::normalize_tag`].
+pub(crate) fn run_tag(
+    query: &str,
+    base_path: &Path,
+    add: &[String],
+    remove: &[String],
+    config: &Config,
+) -> Result<()> {
+    let Some(t) = resolve_try(base_path, query, config.tie_break()) else {
+        let _ = tui::error(&mut io::stderr(), &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    };
+    let mut tags = Tags::load(base_path);
+    if add.is_empty() && remove.is_empty() {
+        for tag in tags.get(&t.basename) {
+            println!("{tag}");
+        }
+        return Ok(());
+    }
+    for tag in add {
+        tags.add(&t.basename, tag);
+    }
+    for tag in remove {
+        tags.remove(&t.basename, tag);
+    }
+    tags.save()
+        .map_err(|e| Error::from_write(&base_path.join(META_DIR), e))?;
+    if !crate::output::quiet() {
+        match tags.get(&t.basename).as_slice() {
+            [] => eprintln!("{} has no tags", t.basename),
+            now => eprintln!("Tagged {}: {}", t.basename, now.join(", ")),
+        }
+    }
+    Ok(())
+}
+
+/// `try new`: creates a try named `name` under today's date (with a `-2`, `-3`… suffix when
+/// taken) and enters it. With `ttl` the try is ephemeral: it is flagged once `ttl` has passed
+/// and moved to the trash by the next cleanup.
+pub(crate) fn run_new(name: &str, ttl: Option<Duration>, base_path: &Path) -> Result<()> {
+    if normalize_query_for_match(name.trim()).is_empty() {
+        let _ = tui::error(&mut io::stderr(), "Give the new try a name");
+        std::process::exit(1);
+    }
+    let target = free_sibling(&base_path.join(new_try_name(name)));
+    if let Some(ttl) = ttl {
+        let new_name = target.file_name().unwrap_or_default().to_string_lossy();
+        let mut expiries = Expiries::load(base_path);
+        let Some(expires) = SystemTime::now().checked_add(ttl) else {
+            let _ = tui::error(&mut io::stderr(), "--ttl is too long");
+            std::process::exit(1);
+        };
+        expiries.set(&new_name, expires);
+        let mut keep_names = list_dir_names(base_path);
+        keep_names.push(new_name.to_string());
+        expiries
+            .save(&keep_names.iter().map(String::as_str).collect())
+            .map_err(|e| Error::from_write(&base_path.join(META_DIR), e))?;
+    }
+    enter_try(base_path, &target, true);
+    Ok(())
+}
+
+/// `try branch`: enters the try named after the branch checked out in the working directory's
+/// repository, creating it under today's date unless one exists under any date.
+pub(crate) fn run_branch(base_path: &Path) -> Result<()> {
+    let mut err = io::stderr();
+    let cwd = std::env::current_dir()?;
+    let Some(root) = crate::git::repo_root(&cwd) else {
+        let _ = tui::error(&mut err, "Not inside a git repository");
+        std::process::exit(1);
+    };
+    let branch = match crate::git::read_head(root) {
+        Some(crate::git::Head::Branch(name)) => name,
+        Some(crate::git::Head::Detached(id)) => {
+            let msg = format!("HEAD is detached at {id}; check out a branch first");
+            let _ = tui::error(&mut err, &msg);
+            std::process::exit(1);
+        }
+        None => {
+            let msg = format!("Could not read HEAD of {}", tilde_path(root));
+            let _ = tui::error(&mut err, &msg);
+            std::process::exit(1);
+        }
+    };
+    let name = branch_try_name(&branch);
+    if name.is_empty() {
+        let msg = format!("Branch '{branch}' leaves nothing to name a try after");
+        let _ = tui::error(&mut err, &msg);
+        std::process::exit(1);
+    }
+    match crate::storage::exact_matches(base_path, &name).first() {
+        Some(existing) => enter_try(base_path, &existing.path, false),
+        None => enter_try(base_path, &base_path.join(new_try_name(&name)), true),
+    }
+    Ok(())
+}
+
+/// `try copy`: duplicates the try best matching `query` into a new dated try named `name`
+/// (default: the source's name plus `-copy`) and prints shell commands to cd into it.
+pub(crate) fn run_copy(
+    query: &str,
+    name: Option<&str>,
+    mode: CopyMode,
+    base_path: &Path,
+    config: &Config,
+) -> Result<()> {
+    let mut err = io::stderr();
+    let Some(t) = resolve_try(base_path, query, config.tie_break()) else {
+        let _ = tui::error(&mut err, &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    };
+    let stem = name.map_or_else(
+        || {
+            let plain = split_date_prefixed(&t.basename).map_or(t.basename.as_str(), |(_, n)| n);
+            format!("{plain}-copy")
+        },
+        str::to_string,
+    );
+    let dest = base_path.join(new_try_name(&stem));
+    if dest.exists() {
+        let _ = tui::error(&mut err, &format!("{} already exists", tilde_path(&dest)));
+        std::process::exit(1);
+    }
+    let stats = match copy_tree(&t.path, &dest, mode) {
+        Ok(stats) => stats,
+        Err(e) => {
+            if mode == CopyMode::Reflink && e.kind() == io::ErrorKind::Unsupported {
+                let _ = tui::error(
+                    &mut err,
+                    "This filesystem cannot clone files; use --copy-mode auto or copy",
+                );
+                std::process::exit(1);
+            }
+            return Err(Error::from_write(&dest, e));
+        }
+    };
+    if !crate::output::quiet() {
+        let counts = [
+            (stats.reflinked, "reflinked"),
+            (stats.hardlinked, "hard-linked"),
+            (stats.copied, "copied"),
+        ];
+        let summary: Vec<String> = counts
+            .iter()
+            .filter(|(n, _)| *n > 0)
+            .map(|(n, how)| format!("{n} {how}"))
+            .collect();
+        eprintln!(
+            "Copied {} → {} ({})",
+            t.basename,
+            tilde_path(&dest),
+            if summary.is_empty() {
+                "no files".into()
+            } else {
+                summary.join(", ")
+            }
+        );
+    }
+    let mut pipeline = ShellPipeline::new();
+    pipeline.assign_dir(&dest);
+    remember_previous(&mut pipeline, base_path, &dest);
+    println!("{}", pipeline.cd(Arg::Dir).line());
+    Ok(())
+}
+
+/// `try diff`: lists which files differ between the tries best matching `left` and `right`,
+/// or with `content` hands both directories to the configured differ.
+pub(crate) fn run_diff(
+    left: &str,
+    right: &str,
+    content: bool,
+    base_path: &Path,
+    config: &Config,
+) -> Result<()> {
+    let mut err = io::stderr();
+    let mut resolve = |query: &str| {
+        resolve_try(base_path, query, config.tie_break()).unwrap_or_else(|| {
+            let _ = tui::error(&mut err, &format!("No try matches '{query}'"));
+            std::process::exit(1);
+        })
+    };
+    let (a, b) = (resolve(left), resolve(right));
+    if a.path == b.path {
+        let _ = tui::error(
+            &mut err,
+            &format!("'{left}' and '{right}' both match {}", a.basename),
+        );
+        std::process::exit(1);
+    }
+    if content {
+        // The differ's own exit status only says whether there were differences
+        if let Err(e) = run_diff_tool(config.diff_tool.as_deref(), &a.path, &b.path) {
+            let _ = tui::error(&mut err, &format!("Could not start the differ: {e}"));
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    let diff = diff_trees(&a.path, &b.path)?;
+    let out = Printer::stdout();
+    println!("{}", out.title(&format!("{} ↔ {}", a.basename, b.basename)));
+    println!(
+        "{}",
+        out.fields(&[
+            ("identical", files_label(diff.identical)),
+            ("changed", files_label(diff.changed.len())),
+            ("only left", files_label(diff.only_left.len())),
+            ("only right", files_label(diff.only_right.len())),
+        ])
+    );
+    if diff.is_empty() {
+        println!("\n  No differences");
+        return Ok(());
+    }
+    print_file_groups(
+        &out,
+        [
+            ("Changed".to_string(), "M", &diff.changed),
+            (format!("Only in {}", a.basename), "-", &diff.only_left),
+            (format!("Only in {}", b.basename), "+", &diff.only_right),
+        ],
+    );
+    Ok(())
+}
+
+/// `try snapshot`: records the size and content hash of every file in the try best matching
+/// `query`, replacing any earlier snapshot of it.
+pub(crate) fn run_snapshot(query: &str, base_path: &Path, config: &Config) -> Result<()> {
+    let Some(t) = resolve_try(base_path, query, config.tie_break()) else {
+        let _ = tui::error(&mut io::stderr(), &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    };
+    let manifest = Manifest::capture(&t.path)?;
+    let path = snapshot::save(base_path, &t.basename, &manifest)
+        .map_err(|e| Error::from_write(base_path, e))?;
+    println!(
+        "Snapshot of {}: {}, {} → {}",
+        t.basename,
+        files_label(manifest.files.len()),
+        crate::util::format_human_size(manifest.total_bytes()),
+        tilde_path(&path)
+    );
+    Ok(())
+}
+
+/// `try verify`: compares the try best matching `query` with its snapshot and lists added,
+/// removed, and changed files. Exits with status 1 when anything changed.
+pub(crate) fn run_verify(query: &str, base_path: &Path, config: &Config) -> Result<()> {
+    let mut err = io::stderr();
+    let Some(t) = resolve_try(base_path, query, config.tie_break()) else {
+        let _ = tui::error(&mut err, &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    };
+    let Some(recorded) = snapshot::load(base_path, &t.basename) else {
+        let _ = tui::error(
+            &mut err,
+            &format!(
+                "No snapshot of {}; record one with `try snapshot {}`",
+                t.basename, t.basename
+            ),
+        );
+        std::process::exit(1);
+    };
+    let diff = recorded.compare(&Manifest::capture(&t.path)?);
+    let out = Printer::stdout();
+    println!("{}", out.title(&t.basename));
+    if diff.is_empty() {
+        println!(
+            "  Unchanged since the snapshot ({})",
+            files_label(diff.identical)
+        );
+        return Ok(());
+    }
+    print_file_groups(
+        &out,
+        [
+            ("Changed".to_string(), "M", &diff.changed),
+            ("Removed".to_string(), "-", &diff.only_left),
+            ("Added".to_string(), "+", &diff.only_right),
+        ],
+    );
+    std::process::exit(1);
+}
+
+/// Prints each non-empty group of paths as a section of `marker path` rows.
+fn print_file_groups(out: &Printer, groups: [(String, &str, &Vec<PathBuf>); 3]) {
+    for (title, marker, paths) in groups {
+        if paths.is_empty() {
+            continue;
+        }
+        let rows: Vec<Vec<String>> = paths
+            .iter()
+            .map(|p| vec![marker.to_string(), p.display().to_string()])
+            .collect();
+        println!("{}", out.section(&title));
+        println!("{}", out.table(&rows));
+    }
+}
+
+fn files_label(n: usize) -> String {
+    if n == 1 {
+        "1 file".into()
+    } else {
+        format!("{n} files")
+    }
+}
+
+/// `try dedupe`: groups tries whose contents are at least `threshold` percent alike, asks
+/// which one of each group to keep, and moves the others to the trash. With `assume_yes`
+/// the most recently modified member is kept without asking.
+pub(crate) fn run_dedupe(
+    base_path: &Path,
+    threshold: u8,
+    assume_yes: bool,
+    force: bool,
+) -> Result<()> {
+    let mut err = io::stderr();
+    if !assume_yes && !io::stdin().is_tty() {
+        let _ = tui::error(
+            &mut err,
+            "try dedupe asks which try to keep; pass --yes to keep the newest of each group",
+        );
+        std::process::exit(1);
+    }
+    let mut tries = scan_tries(base_path);
+    // Newest first, so the first member of each group is the default to keep
+    tries.sort_by(|a, b| {
+        b.mtime
+            .cmp(&a.mtime)
+            .then_with(|| a.basename.cmp(&b.basename))
+    });
+    let prints: Vec<Fingerprint> = tries.iter().map(|t| fingerprint(&t.path)).collect();
+    let groups = group_duplicates(&prints, f64::from(threshold) / 100.0);
+    let out = Printer::stdout();
+    if groups.is_empty() {
+        println!("No near-identical tries found");
+        return Ok(());
+    }
+    let (mut trashed, mut reclaimed) = (0usize, 0u64);
+    for (n, group) in groups.iter().enumerate() {
+        println!(
+            "{}",
+            out.title(&format!("Group {} of {}", n + 1, groups.len()))
+        );
+        let rows: Vec<Vec<String>> = group
+            .iter()
+            .enumerate()
+            .map(|(pos, &i)| {
+                vec![
+                    format!("{}", pos + 1),
+                    tries[i].basename.clone(),
+                    crate::util::format_human_size(prints[i].total),
+                    tui::format_relative_time(tries[i].mtime),
+                ]
+            })
+            .collect();
+        println!("{}", out.table(&rows));
+        let keep = if assume_yes {
+            0
+        } else {
+            match ask_keep(group.len())? {
+                KeepChoice::Keep(pos) => pos,
+                KeepChoice::Skip => continue,
+                KeepChoice::Quit => break,
+            }
+        };
+        for (pos, &i) in group.iter().enumerate() {
+            if pos == keep {
+                continue;
+            }
+            let t = &tries[i];
+            if !force && is_protected(&t.path) {
+                if !crate::output::quiet() {
+                    eprintln!("Kept {}: protected by {KEEP_MARKER}", t.basename);
+                }
+                continue;
+            }
+            let size = crate::util::calculate_dir_size(&t.path);
+            let reason = format!("duplicate of {}", tries[group[keep]].basename);
+            move_to_trash(base_path, &t.path, &reason)
+                .map_err(|e| Error::from_write(&t.path, e))?;
+            trashed += 1;
+            reclaimed += size;
+        }
+    }
+    println!(
+        "Moved {} to the trash, reclaiming {}",
+        if trashed == 1 {
+            "1 try".into()
+        } else {
+            format!("{trashed} tries")
+        },
+        crate::util::format_human_size(reclaimed)
+    );
+    Ok(())
+}
+
+enum KeepChoice {
+    Keep(usize),
+    Skip,
+    Quit,
+}
+
+/// Asks on stderr which of `count` listed tries to keep; Enter keeps the first.
+fn ask_keep(count: usize) -> Result<KeepChoice> {
+    loop {
+        eprint!("Keep which? [1-{count}, Enter = 1, s = skip, q = quit] ");
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            return Ok(KeepChoice::Quit);
+        }
+        match line.trim() {
+            "" => return Ok(KeepChoice::Keep(0)),
+            "s" => return Ok(KeepChoice::Skip),
+            "q" => return Ok(KeepChoice::Quit),
+            n => {
+                if let Ok(n) = n.parse::<usize>()
+                    && (1..=count).contains(&n)
+                {
+                    return Ok(KeepChoice::Keep(n - 1));
+                }
+            }
+        }
+    }
+}
+
+/// `try import-dir`: brings each of `paths` into the tries directory. Failures are reported
+/// per folder and the rest still imported; the exit status is 1 if any failed.
+pub(crate) fn run_import(paths: &[PathBuf], mode: ImportMode, base_path: &Path) -> Result<()> {
+    let mut err = io::stderr();
+    if let Some(blocked) = write_blocked(base_path) {
+        let _ = tui::error(&mut err, blocked.hint());
+        std::process::exit(1);
+    }
+    let mut failed = false;
+    for path in paths {
+        match import_dir(base_path, path, mode) {
+            Ok(dest) if !crate::output::quiet() => {
+                eprintln!("Imported {} → {}", tilde_path(path), tilde_path(&dest));
+            }
+            Ok(_) => {}
+            Err(e) => {
+                let _ = tui::error(&mut err, &format!("{}: {e}", tilde_path(path)));
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// `try stats`: prints how many tries were created and last visited per `by` period, or how
+/// many carry each tag or sit on each directory stack and how many of those were visited, as
+/// a table with bars or, with `csv`, as CSV.
+pub(crate) fn run_stats(base_path: &Path, by: GroupBy, csv: bool) -> Result<()> {
+    const BAR_WIDTH: usize = 30;
+    let tries = scan_tries(base_path);
+    let visits = Visits::load(base_path);
+    let visited = |t: &TryDir| visits.contains(&t.basename);
+    let buckets = match by {
+        GroupBy::Week => histogram(&tries, visits.last_visits(), Period::Week),
+        GroupBy::Month => histogram(&tries, visits.last_visits(), Period::Month),
+        GroupBy::Tag => {
+            let tags = Tags::load(base_path);
+            group_counts(&tries, visited, |t| {
+                tags.get(&t.basename)
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            })
+        }
+        GroupBy::Stack => {
+            // Stacks hold the directories left, which may lie deeper inside a try
+            let mut sessions: HashMap<String, Vec<String>> = HashMap::new();
+            for (session, stack) in all_stacks(base_path) {
+                let mut on_stack: Vec<String> = stack
+                    .entries()
+                    .iter()
+                    .filter_map(|dir| containing_try(base_path, dir))
+                    .filter_map(|t| Some(t.file_name()?.to_string_lossy().into_owned()))
+                    .collect();
+                on_stack.sort();
+                on_stack.dedup();
+                for name in on_stack {
+                    sessions.entry(name).or_default().push(session.clone());
+                }
+            }
+            group_counts(&tries, visited, |t| {
+                sessions.get(&t.basename).cloned().unwrap_or_default()
+            })
+        }
+    };
+    if csv {
+        print!("{}", to_csv(&buckets, by));
+        return Ok(());
+    }
+    if buckets.is_empty() {
+        println!("No tries yet");
+        return Ok(());
+    }
+    let out = Printer::stdout();
+    let (title, column) = match by {
+        GroupBy::Week => ("Tries by week", "week of"),
+        GroupBy::Month => ("Tries by month", "month"),
+        GroupBy::Tag => ("Tries by tag", "tag"),
+        GroupBy::Stack => ("Tries by stack", "stack"),
+    };
+    println!("{}", out.title(title));
+    let max = buckets.iter().map(|b| b.created).max().unwrap_or(0);
+    let mut rows = vec![vec![
+        column.to_string(),
+        "created".to_string(),
+        "visited".to_string(),
+    ]];
+    rows.extend(buckets.iter().map(|b| {
+        vec![
+            b.label.clone(),
+            b.created.to_string(),
+            b.visited.to_string(),
+            bar(b.created, max, BAR_WIDTH),
+        ]
+    }));
+    println!("{}", out.table(&rows));
+    Ok(())
+}
+
+/// `try root`: prints the tries directory in effect after `--path`, `TRY_PATH`, and defaults.
+pub(crate) fn run_root(base_path: &Path) -> Result<()> {
+    println!("{}", base_path.display());
+    Ok(())
+}
+
+/// `try env`: prints [`env_report`].
+pub(crate) fn run_env(base_path: &Path, from_flag: bool, config: &Config) -> Result<()> {
+    print!(
+        "{}",
+        env_report(base_path, from_flag, config, &Printer::stdout())
+    );
+    Ok(())
+}
+
+/// Everything a bug report needs about this installation: version and cargo features, the
+/// resolved paths (`from_flag`: the tries directory came from `--path`), the shell wrapper
+/// `try init` would print, what the terminal supports, and the state stored in the tries
+/// directory.
+pub(crate) fn env_report(
+    base_path: &Path,
+    from_flag: bool,
+    config: &Config,
+    out: &Printer,
+) -> String {
+    let features: Vec<&str> = [
+        ("background-tasks", cfg!(feature = "background-tasks")),
+        ("testing", cfg!(feature = "testing")),
+    ]
+    .into_iter()
+    .filter_map(|(name, on)| on.then_some(name))
+    .collect();
+    let features = if features.is_empty() {
+        "none".to_string()
+    } else {
+        features.join(", ")
+    };
+    let source = if from_flag {
+        "--path"
+    } else if std::env::var_os("TRY_PATH").is_some() {
+        "TRY_PATH"
+    } else {
+        "default"
+    };
+    let file_state = |path: &Path| {
+        let state = if path.exists() { "" } else { " (missing)" };
+        format!("{}{state}", path.display())
+    };
+    let paths = vec![
+        ("tries dir", format!("{} ({source})", base_path.display())),
+        (
+            "global config",
+            crate::config::global_config_path().map_or("unknown".into(), |p| file_state(&p)),
+        ),
+        (
+            "workspace config",
+            file_state(&base_path.join(crate::config::WORKSPACE_CONFIG)),
+        ),
+        ("data dir", file_state(&base_path.join(META_DIR))),
+        (
+            "archive dir",
+            archive_dir(base_path, config).display().to_string(),
+        ),
+        (
+            "scratch dir",
+            scratch_dir(base_path, config).display().to_string(),
+        ),
+        (
+            "trash dir",
+            base_path
+                .join(crate::trash::TRASH_DIR)
+                .display()
+                .to_string(),
+        ),
+    ];
+
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "unknown".into());
+    // The dialect `try init` writes the wrapper for and eval commands are printed in
+    let wrapper = match config.shell_dialect {
+        Some(dialect) => format!("{} (shell.dialect)", dialect.name()),
+        None => Shell::current().name().to_string(),
+    };
+    let stream = |tty: bool| if tty { "terminal" } else { "not a terminal" };
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    let terminal = vec![
+        ("stdin", stream(io::stdin().is_tty()).to_string()),
+        ("stdout", stream(io::stdout().is_tty()).to_string()),
+        ("stderr", stream(io::stderr().is_tty()).to_string()),
+        (
+            "size",
+            terminal::size().map_or("unknown".into(), |(w, h)| format!("{w}x{h}")),
+        ),
+        (
+            "colors (stdout)",
+            on_off(TermCaps::listing().color).to_string(),
+        ),
+        (
+            "colors (stderr)",
+            on_off(TermCaps::screen().color).to_string(),
+        ),
+    ];
+
+    let visits = crate::visits::Visits::load(base_path);
+    let state = vec![
+        (
+            "history",
+            format!(
+                "{} queries",
+                crate::history::QueryHistory::load(base_path).len()
+            ),
+        ),
+        ("visits", format!("{} tries", visits.len())),
+        (
+            "pending clones",
+            pending_clones(base_path).len().to_string(),
+        ),
+        (
+            "trash",
+            format!("{} items", crate::trash::list_trash(base_path).len()),
+        ),
+    ];
+
+    let blocks = [
+        out.title(&format!("try {}", env!("CARGO_PKG_VERSION"))),
+        out.fields(&[("features", features)]),
+        out.section("Paths"),
+        out.fields(&paths),
+        out.section("Shell"),
+        out.fields(&[("shell", shell), ("init wrapper", wrapper)]),
+        out.section("Terminal"),
+        out.fields(&terminal),
+        out.section("State"),
+        out.fields(&state),
+    ];
+    blocks.join("\n") + "\n"
+}
+
+/// `try is-try`: exits 0 when `path` (default: the working directory) lies inside a try under
+/// `base_path`, 1 otherwise. Prints nothing.
+pub(crate) fn run_is_try(path: Option<&Path>, base_path: &Path) -> Result<()> {
+    let path = match path {
+        Some(p) => std::fs::canonicalize(p).or_else(|_| std::path::absolute(p))?,
+        None => std::env::current_dir()?,
+    };
+    if containing_try(base_path, &path).is_none_or(|dir| !dir.is_dir()) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// `try prompt-info`: prints a one-line segment for shell prompts when the working directory
+/// is inside a try, and exits 1 silently otherwise. Only stats the current try, so it stays
+/// fast enough to run on every prompt.
+pub(crate) fn run_prompt_info(base_path: &Path) -> Result<()> {
+    let Some(dir) = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| containing_try(base_path, &cwd))
+    else {
+        std::process::exit(1);
+    };
+    let Ok(meta) = std::fs::metadata(&dir) else {
+        std::process::exit(1);
+    };
+    let name = dir.file_name().unwrap_or_default().to_string_lossy();
+    let age = tui::format_relative_time(Timestamps::from_metadata(&meta, &name).created);
+    println!(
+        "{}",
+        crate::info::prompt_segment(&name, &age, is_protected(&dir))
+    );
+    Ok(())
+}
+
+/// `try info`: prints details about the try best matching `query`, including open TODOs.
+pub(crate) fn run_info(query: &str, base_path: &Path, config: &Config) -> Result<()> {
+    const MAX_TODOS: usize = 5;
+    let Some(t) = resolve_try(base_path, query, config.tie_break()) else {
+        let _ = tui::error(&mut io::stderr(), &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    };
+    let out = Printer::stdout();
+    let size = crate::util::calculate_dir_size(&t.path);
+    let mut fields = vec![
+        ("path", t.path.display().to_string()),
+        ("created", tui::format_relative_time(t.ctime)),
+        ("modified", tui::format_relative_time(t.mtime)),
+        ("size", crate::util::format_human_size(size)),
+    ];
+    if let Some(origin) = imported_from(base_path, &t.basename) {
+        fields.push(("imported", origin));
+    }
+    if let Some(git) = crate::git::read_status(&t.path) {
+        let branch = match (&git.branch, crate::git::read_head(&t.path)) {
+            (Some(branch), _) => branch.clone(),
+            (None, Some(head)) => format!("detached at {}", head.label()),
+            (None, None) => "(detached)".to_string(),
+        };
+        let indicator = git.indicator();
+        if indicator.is_empty() {
+            fields.push(("git", format!("{branch}, clean")));
+        } else {
+            fields.push(("git", format!("{branch} {indicator}")));
+        }
+    }
+    if let Some(entry) = crate::refresh::RefreshCache::load(base_path).get(&t.basename) {
+        let checked = tui::format_relative_time(Some(
+            std::time::UNIX_EPOCH + Duration::from_secs(entry.checked),
+        ));
+        let state = match entry.counts {
+            Some((0, 0)) => "up to date".to_string(),
+            Some((ahead, behind)) => format!("↑{ahead} ↓{behind}"),
+            None => "unknown".to_string(),
+        };
+        fields.push(("remote", format!("{state}, checked {checked}")));
+    }
+    if let Some(project) = ProjectLinks::load(base_path).get(&t.basename) {
+        fields.push(("project", project.to_string()));
+    }
+    let tags = Tags::load(base_path);
+    if let tags @ [_, ..] = tags.get(&t.basename).as_slice() {
+        fields.push(("tags", tags.join(", ")));
+    }
+    let mut text = format!("{}\n{}\n", out.title(&t.basename), out.fields(&fields));
+    if let Some(activity) = activity_section(&out, &t.path) {
+        text.push_str(&activity);
+    }
+    let todos = crate::info::scan_todos(&t.path, MAX_TODOS);
+    if !todos.is_empty() {
+        let rows: Vec<Vec<String>> = todos
+            .into_iter()
+            .map(|todo| vec![format!("{}:{}", todo.path.display(), todo.line), todo.text])
+            .collect();
+        text.push_str(&format!("{}\n{}\n", out.section("TODOs"), out.table(&rows)));
+    }
+    output::page(&text)?;
+    Ok(())
+}
+
+/// Prints a du-style breakdown of the best-matching try: its top-level entries, largest
+/// first, with those its `.gitignore` covers marked.
+pub(crate) fn run_size(query: &str, base_path: &Path, config: &Config) -> Result<()> {
+    let Some(t) = resolve_try(base_path, query, config.tie_break()) else {
+        let _ = tui::error(&mut io::stderr(), &format!("No try matches '{query}'"));
+        std::process::exit(1);
+    };
+    let jobs = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let entries = crate::info::size_breakdown(&t.path, jobs);
+    let total: u64 = entries.iter().map(|e| e.bytes).sum();
+    let files: u64 = entries.iter().map(|e| e.files).sum();
+    let ignored: u64 = entries.iter().filter(|e| e.ignored).map(|e| e.bytes).sum();
+    let out = Printer::stdout();
+    let noun = if files == 1 { "file" } else { "files" };
+    let mut fields = vec![
+        ("path", t.path.display().to_string()),
+        (
+            "size",
+            format!(
+                "{} in {files} {noun}",
+                crate::util::format_human_size(total)
+            ),
+        ),
+    ];
+    if ignored > 0 {
+        fields.push((
+            "ignored",
+            format!(
+                "{} matched by .gitignore",
+                crate::util::format_human_size(ignored)
+            ),
+        ));
+    }
+    let mut text = format!("{}\n{}\n", out.title(&t.basename), out.fields(&fields));
+    if !entries.is_empty() {
+        let rows: Vec<Vec<String>> = entries
+            .iter()
+            .map(|e| {
+                let share = (e.bytes * 100).checked_div(total).unwrap_or(0);
+                let name = if e.is_dir {
+                    format!("{}/", e.name)
+                } else {
+                    e.name.clone()
+                };
+                let mut row = vec![
+                    crate::util::format_human_size(e.bytes),
+                    format!("{share:>3}%"),
+                    name,
+                ];
+                if e.ignored {
+                    row.push("ignored".to_string());
+                }
+                row
+            })
+            .collect();
+        text.push_str(&format!(
+            "{}\n{}\n",
+            out.section("Entries"),
+            out.table(&rows)
+        ));
+    }
+    output::page(&text)?;
+    Ok(())
+}
+
+/// Prints the activity timeline section of `try info`.
+fn activity_section(out: &Printer, dir: &Path) -> Option<String> {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let activity = crate::info::collect_activity(dir, now);
+    let last_change = activity.last_change?;
+    let at = |secs: u64| Some(UNIX_EPOCH + Duration::from_secs(secs));
+    let mut fields = Vec::new();
+    if let Some(last) = activity.last_commit {
+        fields.push((
+            "last commit",
+            format!(
+                "{} ({} commits)",
+                tui::format_relative_time(at(last)),
+                activity.commits
+            ),
+        ));
+    }
+    fields.push(("last change", tui::format_relative_time(at(last_change))));
+    if let Some((day, count)) = &activity.busiest_day {
+        fields.push(("busiest day", format!("{day} ({count} changes)")));
+    }
+    let weeks_label = format!("last {} weeks", crate::info::ACTIVITY_WEEKS);
+    fields.push((&weeks_label, crate::info::sparkline(&activity.weekly)));
+    Some(format!(
+        "{}\n{}\n",
+        out.section("Activity"),
+        out.fields(&fields)
+    ))
+}
+
 /// Build the fuzzy query for the `cd` command from remaining args, removing a
 /// redundant leading "cd" token if present.
 pub(crate) fn build_cd_query(rest: &[OsString]) -> String {
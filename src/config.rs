@@ -0,0 +1,395 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+use crate::charset::{parse_extra_chars, parse_separators};
+use crate::i18n::Locale;
+use crate::output::ColorChoice;
+use crate::query::parse_size;
+use crate::score::{DEFAULT_TIE_BREAK, TieBreak};
+use crate::selector::DEFAULT_SIZE_WARNING;
+use crate::shell::Shell;
+use crate::storage::CollisionPolicy;
+use crate::util::shellexpand_home;
+
+/// Per-workspace overrides, read from the tries directory itself.
+pub(crate) const WORKSPACE_CONFIG: &str = ".try.toml";
+
+/// Environment variables and the setting each one overrides.
+pub(crate) const ENV_SETTINGS: &[(&str, &str)] = &[
+    ("TRY_DEBOUNCE_MS", "selector.debounce_ms"),
+    ("TRY_POLL_MS", "selector.poll_ms"),
+    ("TRY_MAX_FPS", "selector.max_fps"),
+    ("TRY_SORT", "selector.tie_break"),
+    ("TRY_VISIT_HALF_LIFE_DAYS", "selector.visit_half_life_days"),
+    ("TRY_COLLAPSE_OLDER", "selector.collapse_older"),
+    ("TRY_COLOR", "ui.color"),
+    ("TRY_ACCESSIBLE", "ui.accessible"),
+    ("TRY_LOCALE", "ui.locale"),
+    ("TRY_TRASH_RETENTION_DAYS", "trash.retention_days"),
+    ("TRY_TRASH_MAX_SIZE", "trash.max_size"),
+    ("TRY_ARCHIVE_DIR", "archive.dir"),
+    ("TRY_SCRATCH_DIR", "scratch.dir"),
+    ("TRY_DIFF_TOOL", "diff.tool"),
+    ("TRY_CLONE_SHALLOW", "clone.shallow"),
+    ("TRY_NOTIFY_AFTER_SECS", "notify.after_secs"),
+    ("TRY_REFRESH_TTL_SECS", "refresh.ttl_secs"),
+    ("TRY_CASE_SENSITIVE_NAMES", "names.case_sensitive"),
+    ("TRY_NAME_COLLISION", "names.collision"),
+    ("TRY_NAME_SEPARATORS", "names.separators"),
+    ("TRY_NAME_EXTRA_CHARS", "names.extra_chars"),
+    ("TRY_SHELL_HOME_RELATIVE", "shell.home_relative"),
+    ("TRY_SHELL_DIALECT", "shell.dialect"),
+    ("TRY_SIZE_WARNING", "selector.size_warning"),
+];
+
+/// Settings gathered from every layer; `None` means "use the built-in default".
+///
+/// Layers, lowest precedence first: built-in defaults, the global
+/// `<config dir>/try/config.toml`, the workspace `.try.toml`, then environment variables.
+/// Command-line flags override all of them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Config {
+    /// `selector.debounce_ms` / `TRY_DEBOUNCE_MS`
+    pub debounce_ms: Option<u64>,
+    /// `selector.poll_ms` / `TRY_POLL_MS`: wake the idle selector this often to check the
+    /// terminal size; unset or 0 waits for key and resize events
+    pub poll_ms: Option<u64>,
+    /// `selector.max_fps` / `TRY_MAX_FPS`: most frames drawn per second; unset or 0 draws
+    /// every change at once
+    pub max_fps: Option<u64>,
+    /// `trash.retention_days` / `TRY_TRASH_RETENTION_DAYS`
+    pub trash_retention_days: Option<u64>,
+    /// `trash.max_size` / `TRY_TRASH_MAX_SIZE`, in bytes
+    pub trash_max_size: Option<u64>,
+    /// `selector.tie_break`, e.g. `"mtime,name"`
+    pub tie_break: Option<Vec<TieBreak>>,
+    /// `selector.collapse_older`
+    pub collapse_older: Option<bool>,
+    /// `selector.pick_latest`: when several tries share the typed name, open the newest
+    /// instead of asking; `try cd --latest` sets it for one call
+    pub pick_latest: Option<bool>,
+    /// `selector.stay_open_after_create`: create new tries from the selector on the spot and
+    /// keep it open with the new entry highlighted
+    pub stay_open_after_create: Option<bool>,
+    /// `selector.visit_half_life_days` / `TRY_VISIT_HALF_LIFE_DAYS`: days after which a visit
+    /// counts half as much in the ranking; 0 ignores visits
+    pub visit_half_life_days: Option<u64>,
+    /// `archive.dir` / `TRY_ARCHIVE_DIR`
+    pub archive_dir: Option<PathBuf>,
+    /// `scratch.dir` / `TRY_SCRATCH_DIR`
+    pub scratch_dir: Option<PathBuf>,
+    /// `diff.tool` / `TRY_DIFF_TOOL`, a command given the two directories
+    pub diff_tool: Option<String>,
+    /// `ui.color` / `TRY_COLOR`; `--color` overrides it
+    pub color: Option<ColorChoice>,
+    /// `ui.accessible` / `TRY_ACCESSIBLE`: draw the selector as plain labeled lines for
+    /// screen readers
+    pub accessible: Option<bool>,
+    /// `ui.no_clear`: redraw the selector over the previous frame, rewriting only changed
+    /// lines, instead of clearing the screen; a non-empty `NO_FLICKER` also turns it on
+    pub no_clear: Option<bool>,
+    /// `ui.locale` / `TRY_LOCALE`: language of the selector and its prompts; unset follows
+    /// `LC_ALL`, `LC_MESSAGES`, or `LANG`
+    pub locale: Option<Locale>,
+    /// `clone.shallow` / `TRY_CLONE_SHALLOW`: clone with `--depth 1 --filter=blob:none`
+    pub clone_shallow: Option<bool>,
+    /// `notify.after_secs` / `TRY_NOTIFY_AFTER_SECS`: send a desktop notification when an
+    /// archive, trash prune, or clone takes at least this long; unset disables it
+    pub notify_after_secs: Option<u64>,
+    /// `names.case_sensitive` / `TRY_CASE_SENSITIVE_NAMES`: tell names apart by case when
+    /// looking for an existing try with the typed name
+    pub case_sensitive_names: Option<bool>,
+    /// `names.collision` / `TRY_NAME_COLLISION`: what creating a try does when its name is
+    /// taken under another date: reuse, suffix, ask, or fail
+    pub name_collision: Option<CollisionPolicy>,
+    /// `names.separators` / `TRY_NAME_SEPARATORS`: punctuation that separates words, so that
+    /// names differing only in which of it they use are the same name; `-` always does
+    pub name_separators: Option<String>,
+    /// `names.extra_chars` / `TRY_NAME_EXTRA_CHARS`: punctuation kept in names besides
+    /// `-`, `_`, and `.`, from `+,=~%^`
+    pub name_extra_chars: Option<String>,
+    /// `shell.home_relative` / `TRY_SHELL_HOME_RELATIVE`: write paths under the home
+    /// directory as `"$HOME"/…` in the commands the shell wrapper evals
+    pub shell_home_relative: Option<bool>,
+    /// `shell.dialect` / `TRY_SHELL_DIALECT`: shell the eval commands are written for;
+    /// unset picks fish when `$SHELL` names it, else POSIX
+    pub shell_dialect: Option<Shell>,
+    /// `selector.size_warning` / `TRY_SIZE_WARNING`: total size of the tries above which the
+    /// selector suggests cleaning up; 0 turns the notice off
+    pub size_warning: Option<u64>,
+    /// `refresh.auto`: start a background `try refresh` whenever the selector opens
+    pub refresh_auto: Option<bool>,
+    /// `refresh.ttl_secs` / `TRY_REFRESH_TTL_SECS`: seconds before `try refresh` asks a
+    /// try's remote again
+    pub refresh_ttl_secs: Option<u64>,
+    /// `refresh.jobs`: remotes `try refresh` asks at the same time
+    pub refresh_jobs: Option<u64>,
+    /// `[clone.rewrite]` entries `"<prefix>" = "<replacement>"`, like git's `insteadOf`
+    pub clone_rewrites: Vec<(String, String)>,
+}
+
+impl Config {
+    /// Loads and merges all layers for `base_path`. Problems in a file are returned as
+    /// warnings (`file:line: message`) and the offending line is skipped.
+    pub(crate) fn load(base_path: &Path) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut config = Config::default();
+        let files = [global_config_path(), Some(base_path.join(WORKSPACE_CONFIG))];
+        for path in files.into_iter().flatten() {
+            if let Ok(text) = fs::read_to_string(&path) {
+                let origin = crate::util::tilde_path(&path);
+                config.merge(&Config::parse(&text, &origin, &mut warnings));
+            }
+        }
+        config.merge(&Config::from_env(
+            |key| std::env::var(key).ok(),
+            &mut warnings,
+        ));
+        (config, warnings)
+    }
+
+    /// Parses the supported TOML subset: `[section]` headers, `key = value` lines with bare or
+    /// double-quoted keys and integer, boolean, or double-quoted string values, and `#`
+    /// comments.
+    pub(crate) fn parse(text: &str, origin: &str, warnings: &mut Vec<String>) -> Self {
+        let mut config = Config::default();
+        let mut section = String::new();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = strip_comment(raw).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let at = format!("{origin}:{}", idx + 1);
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+            let Some((key, value)) = split_key_value(line) else {
+                warnings.push(format!("{at}: expected `key = value`"));
+                continue;
+            };
+            let key = match (section.as_str(), key.trim()) {
+                ("", key) => key.to_string(),
+                (section, key) => format!("{section}.{key}"),
+            };
+            if let Err(msg) = config.set(&key, unquote(value.trim())) {
+                warnings.push(format!("{at}: {msg}"));
+            }
+        }
+        config
+    }
+
+    /// Layer read from the `TRY_*` environment variables in `ENV_SETTINGS`, looked up with
+    /// `var`. Empty variables are skipped; unparsable ones are reported as `VAR: message`
+    /// warnings and ignored.
+    pub(crate) fn from_env(
+        var: impl Fn(&str) -> Option<String>,
+        warnings: &mut Vec<String>,
+    ) -> Self {
+        let mut config = Config::default();
+        for (name, key) in ENV_SETTINGS {
+            let Some(value) = var(name) else { continue };
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            if let Err(msg) = config.set(key, value) {
+                warnings.push(format!("{name}: {msg}"));
+            }
+        }
+        config
+    }
+
+    /// Overwrites every setting that `other` defines.
+    pub(crate) fn merge(&mut self, other: &Config) {
+        self.debounce_ms = other.debounce_ms.or(self.debounce_ms);
+        self.poll_ms = other.poll_ms.or(self.poll_ms);
+        self.max_fps = other.max_fps.or(self.max_fps);
+        self.trash_retention_days = other.trash_retention_days.or(self.trash_retention_days);
+        self.trash_max_size = other.trash_max_size.or(self.trash_max_size);
+        self.tie_break = other.tie_break.clone().or(self.tie_break.take());
+        self.collapse_older = other.collapse_older.or(self.collapse_older);
+        self.pick_latest = other.pick_latest.or(self.pick_latest);
+        self.stay_open_after_create = other.stay_open_after_create.or(self.stay_open_after_create);
+        self.visit_half_life_days = other.visit_half_life_days.or(self.visit_half_life_days);
+        self.archive_dir = other.archive_dir.clone().or(self.archive_dir.take());
+        self.scratch_dir = other.scratch_dir.clone().or(self.scratch_dir.take());
+        self.diff_tool = other.diff_tool.clone().or(self.diff_tool.take());
+        self.color = other.color.or(self.color);
+        self.accessible = other.accessible.or(self.accessible);
+        self.no_clear = other.no_clear.or(self.no_clear);
+        self.locale = other.locale.or(self.locale);
+        self.clone_shallow = other.clone_shallow.or(self.clone_shallow);
+        self.notify_after_secs = other.notify_after_secs.or(self.notify_after_secs);
+        self.case_sensitive_names = other.case_sensitive_names.or(self.case_sensitive_names);
+        self.name_collision = other.name_collision.or(self.name_collision);
+        self.name_separators = other
+            .name_separators
+            .clone()
+            .or(self.name_separators.take());
+        self.name_extra_chars = other
+            .name_extra_chars
+            .clone()
+            .or(self.name_extra_chars.take());
+        self.shell_home_relative = other.shell_home_relative.or(self.shell_home_relative);
+        self.shell_dialect = other.shell_dialect.or(self.shell_dialect);
+        self.size_warning = other.size_warning.or(self.size_warning);
+        self.refresh_auto = other.refresh_auto.or(self.refresh_auto);
+        self.refresh_ttl_secs = other.refresh_ttl_secs.or(self.refresh_ttl_secs);
+        self.refresh_jobs = other.refresh_jobs.or(self.refresh_jobs);
+        for (prefix, replacement) in &other.clone_rewrites {
+            self.clone_rewrites.retain(|(p, _)| p != prefix);
+            self.clone_rewrites
+                .push((prefix.clone(), replacement.clone()));
+        }
+    }
+
+    /// Tie-breakers for equal scores, defaulting to newest modification then name.
+    pub(crate) fn tie_break(&self) -> &[TieBreak] {
+        self.tie_break.as_deref().unwrap_or(DEFAULT_TIE_BREAK)
+    }
+
+    /// Idle wake-up interval of the selector; `None` waits for key and resize events.
+    pub(crate) fn poll_interval(&self) -> Option<Duration> {
+        self.poll_ms.filter(|&ms| ms > 0).map(Duration::from_millis)
+    }
+
+    /// Least time between two selector frames; zero when the redraw rate is not capped.
+    pub(crate) fn frame_interval(&self) -> Duration {
+        self.max_fps
+            .filter(|&fps| fps > 0)
+            .map_or(Duration::ZERO, |fps| {
+                Duration::from_secs(1) / fps.min(1_000) as u32
+            })
+    }
+
+    /// Total size that triggers the selector's clean-up notice, defaulting to 20 GiB.
+    pub(crate) fn size_warning(&self) -> u64 {
+        self.size_warning.unwrap_or(DEFAULT_SIZE_WARNING)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("`{key}` expects a whole number, got `{value}`"))
+        };
+        let flag = || {
+            value
+                .parse::<bool>()
+                .map_err(|_| format!("`{key}` expects true or false, got `{value}`"))
+        };
+        match key {
+            "selector.debounce_ms" => self.debounce_ms = Some(number()?),
+            "selector.poll_ms" => self.poll_ms = Some(number()?),
+            "selector.max_fps" => self.max_fps = Some(number()?),
+            "selector.collapse_older" => self.collapse_older = Some(flag()?),
+            "selector.pick_latest" => self.pick_latest = Some(flag()?),
+            "selector.stay_open_after_create" => self.stay_open_after_create = Some(flag()?),
+            "selector.tie_break" => self.tie_break = Some(TieBreak::parse_list(value)?),
+            "selector.visit_half_life_days" => self.visit_half_life_days = Some(number()?),
+            "clone.shallow" => self.clone_shallow = Some(flag()?),
+            "ui.accessible" => self.accessible = Some(flag()?),
+            "ui.no_clear" => self.no_clear = Some(flag()?),
+            "notify.after_secs" => self.notify_after_secs = Some(number()?),
+            "names.case_sensitive" => self.case_sensitive_names = Some(flag()?),
+            "shell.home_relative" => self.shell_home_relative = Some(flag()?),
+            "refresh.auto" => self.refresh_auto = Some(flag()?),
+            "refresh.ttl_secs" => self.refresh_ttl_secs = Some(number()?),
+            "refresh.jobs" => self.refresh_jobs = Some(number()?),
+            _ if key.starts_with("clone.rewrite.") => {
+                let prefix = unquote(&key["clone.rewrite.".len()..]);
+                if prefix.is_empty() {
+                    return Err("`clone.rewrite` entries need a URL prefix as key".into());
+                }
+                self.clone_rewrites
+                    .push((prefix.to_string(), value.to_string()));
+            }
+            "archive.dir" => self.archive_dir = Some(shellexpand_home(value)),
+            "diff.tool" => self.diff_tool = Some(value.to_string()),
+            "scratch.dir" => self.scratch_dir = Some(shellexpand_home(value)),
+            "shell.dialect" => {
+                let shell = Shell::from_str(value, true).map_err(|_| {
+                    format!("`{key}` expects posix, fish, nu, or pwsh, got `{value}`")
+                })?;
+                self.shell_dialect = Some(shell);
+            }
+            "names.separators" => {
+                let chars = parse_separators(value).map_err(|e| format!("`{key}` {e}"))?;
+                self.name_separators = Some(chars);
+            }
+            "names.extra_chars" => {
+                let chars = parse_extra_chars(value).map_err(|e| format!("`{key}` {e}"))?;
+                self.name_extra_chars = Some(chars);
+            }
+            "names.collision" => {
+                let policy = CollisionPolicy::from_str(value, true).map_err(|_| {
+                    format!("`{key}` expects reuse, suffix, ask, or fail, got `{value}`")
+                })?;
+                self.name_collision = Some(policy);
+            }
+            "ui.locale" => {
+                let locale = Locale::parse(value)
+                    .ok_or_else(|| format!("`{key}` expects en or de, got `{value}`"))?;
+                self.locale = Some(locale);
+            }
+            "ui.color" => {
+                let choice = ColorChoice::from_str(value, true).map_err(|_| {
+                    format!("`{key}` expects auto, always, or never, got `{value}`")
+                })?;
+                self.color = Some(choice);
+            }
+            "trash.retention_days" => self.trash_retention_days = Some(number()?),
+            "selector.size_warning" => {
+                let size = parse_size(value)
+                    .ok_or_else(|| format!("`{key}` expects a size like `20G`, got `{value}`"))?;
+                self.size_warning = Some(size);
+            }
+            "trash.max_size" => {
+                let size = parse_size(value)
+                    .ok_or_else(|| format!("`{key}` expects a size like `2G`, got `{value}`"))?;
+                self.trash_max_size = Some(size);
+            }
+            _ => return Err(format!("unknown setting `{key}`")),
+        }
+        Ok(())
+    }
+}
+
+/// `<config dir>/try/config.toml`, e.g. `~/.config/try/config.toml` on Linux.
+pub(crate) fn global_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("try").join("config.toml"))
+}
+
+/// Splits `key = value` at the first `=` outside a quoted key.
+fn split_key_value(line: &str) -> Option<(&str, &str)> {
+    let key_end = match line.strip_prefix('"') {
+        Some(rest) => rest.find('"')? + 2,
+        None => 0,
+    };
+    let eq = key_end + line[key_end..].find('=')?;
+    Some((&line[..eq], &line[eq + 1..]))
+}
+
+/// Drops a trailing `# comment` that is not inside a quoted string.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
@@ -0,0 +1,284 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use clap::ValueEnum;
+
+/// How `try copy` duplicates files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum CopyMode {
+    /// Reflink where the filesystem supports it; otherwise hard-link read-only files and
+    /// copy the rest.
+    #[default]
+    Auto,
+    /// Reflink every file; fails on filesystems without copy-on-write clones.
+    Reflink,
+    /// Hard-link every file, so edits in one tree show up in the other.
+    Hardlink,
+    /// Copy every byte.
+    Copy,
+}
+
+/// How many files each method handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CopyStats {
+    pub reflinked: usize,
+    pub hardlinked: usize,
+    pub copied: usize,
+}
+
+/// Duplicates the tree at `src` into `dest`, which must not exist yet. Symlinks are
+/// recreated as links rather than followed. When the copy fails, the partial tree this call
+/// created is removed; an existing `dest` fails the call untouched.
+pub(crate) fn copy_tree(src: &Path, dest: &Path, mode: CopyMode) -> io::Result<CopyStats> {
+    copy_tree_with_progress(src, dest, mode, &mut |_| {})
+}
+
+/// [`copy_tree`] calling `progress` with the bytes handled so far after each file.
+pub(crate) fn copy_tree_with_progress(
+    src: &Path,
+    dest: &Path,
+    mode: CopyMode,
+    progress: &mut dyn FnMut(u64),
+) -> io::Result<CopyStats> {
+    fs::create_dir(dest)?;
+    copy_contents(src, dest, mode, progress).inspect_err(|_| {
+        let _ = crate::mounts::remove_tree(dest);
+    })
+}
+
+/// Duplicates what the directory `src` holds into the empty directory `dest`.
+fn copy_contents(
+    src: &Path,
+    dest: &Path,
+    mode: CopyMode,
+    progress: &mut dyn FnMut(u64),
+) -> io::Result<CopyStats> {
+    let mut copier = Copier {
+        mode,
+        // Stop trying once a clone fails; the filesystem will not start supporting them
+        reflinks: matches!(mode, CopyMode::Auto | CopyMode::Reflink),
+        stats: CopyStats::default(),
+        bytes: 0,
+        progress,
+    };
+    copier.copy_dir(src, dest)?;
+    Ok(copier.stats)
+}
+
+/// Moves `src` to `dest`, which must not exist yet: a rename when both are on one
+/// filesystem, otherwise [`copy_then_remove`]. `progress` gets the bytes copied so far and
+/// the total while copying.
+pub(crate) fn move_tree(
+    src: &Path,
+    dest: &Path,
+    mut progress: impl FnMut(u64, u64),
+) -> io::Result<()> {
+    match fs::rename(src, dest) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_then_remove(src, dest, &mut progress)
+        }
+        result => result,
+    }
+}
+
+/// Moves `src` to `dest` across filesystems: copies every byte, checks that the copy holds
+/// as many files and bytes as the original, and only then removes the original. An existing
+/// `dest` is never overwritten or removed: the move fails before copying anything. When the
+/// copy or the check fails, the partial copy is removed and `src` stays as it was; when
+/// removing the original fails, the error says the copy at `dest` is complete.
+pub(crate) fn copy_then_remove(
+    src: &Path,
+    dest: &Path,
+    progress: &mut dyn FnMut(u64, u64),
+) -> io::Result<()> {
+    let meta = fs::symlink_metadata(src)?;
+    if !meta.is_dir() {
+        if meta.is_symlink() {
+            copy_symlink(src, dest)?;
+        } else {
+            let n = copy_new(src, dest)?;
+            progress(n, meta.len());
+            if let Err(e) = verify_copy(dest, (1, n), (1, meta.len())) {
+                let _ = fs::remove_file(dest);
+                return Err(e);
+            }
+        }
+        return fs::remove_file(src).map_err(|e| original_kept(src, dest, e));
+    }
+    let (files, bytes) = crate::util::dir_usage(src);
+    // Fails on an existing `dest`, before anything below may remove it
+    fs::create_dir(dest)?;
+    let copied = copy_contents(src, dest, CopyMode::Copy, &mut |done| progress(done, bytes))
+        .and_then(|_| verify_copy(dest, crate::util::dir_usage(dest), (files, bytes)));
+    if let Err(e) = copied {
+        // Leave no half-copied tree behind
+        let _ = crate::mounts::remove_tree(dest);
+        return Err(e);
+    }
+    crate::mounts::remove_tree(src).map_err(|e| original_kept(src, dest, e))
+}
+
+/// Fails unless the copy at `dest` holds the `(files, bytes)` of the original.
+fn verify_copy(dest: &Path, copied: (u64, u64), expected: (u64, u64)) -> io::Result<()> {
+    if copied == expected {
+        return Ok(());
+    }
+    Err(io::Error::other(format!(
+        "copy at {} is incomplete: {} of {} files, {} of {} bytes",
+        dest.display(),
+        copied.0,
+        expected.0,
+        copied.1,
+        expected.1
+    )))
+}
+
+/// Copies the file `from` into `to` along with its permissions, failing rather than
+/// overwriting when `to` exists. A partial `to` is removed when the copy fails.
+fn copy_new(from: &Path, to: &Path) -> io::Result<u64> {
+    let mut src = fs::File::open(from)?;
+    let permissions = src.metadata()?.permissions();
+    let mut dst = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(to)?;
+    let copied = io::copy(&mut src, &mut dst).and_then(|n| {
+        dst.set_permissions(permissions)?;
+        Ok(n)
+    });
+    if copied.is_err() {
+        drop(dst);
+        let _ = fs::remove_file(to);
+    }
+    copied
+}
+
+fn original_kept(src: &Path, dest: &Path, e: io::Error) -> io::Error {
+    io::Error::new(
+        e.kind(),
+        format!(
+            "copied to {}, but removing {} failed: {e}",
+            dest.display(),
+            src.display()
+        ),
+    )
+}
+
+struct Copier<'p> {
+    mode: CopyMode,
+    reflinks: bool,
+    stats: CopyStats,
+    /// Bytes of the files handled so far, reported through `progress`.
+    bytes: u64,
+    progress: &'p mut dyn FnMut(u64),
+}
+
+impl Copier<'_> {
+    fn copy_dir(&mut self, src: &Path, dest: &Path) -> io::Result<()> {
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            let (from, to) = (entry.path(), dest.join(entry.file_name()));
+            let kind = entry.file_type()?;
+            if kind.is_dir() {
+                fs::create_dir(&to)?;
+                self.copy_dir(&from, &to)?;
+            } else if kind.is_symlink() {
+                copy_symlink(&from, &to)?;
+            } else {
+                self.copy_file(&from, &to)?;
+                self.bytes += entry.metadata()?.len();
+                (self.progress)(self.bytes);
+            }
+        }
+        Ok(())
+    }
+
+    fn copy_file(&mut self, from: &Path, to: &Path) -> io::Result<()> {
+        if self.reflinks {
+            match reflink(from, to) {
+                Ok(()) => {
+                    self.stats.reflinked += 1;
+                    return Ok(());
+                }
+                Err(e) if self.mode == CopyMode::Reflink => return Err(e),
+                Err(_) => self.reflinks = false,
+            }
+        }
+        let read_only = fs::metadata(from)?.permissions().readonly();
+        let link = match self.mode {
+            CopyMode::Hardlink => true,
+            CopyMode::Auto => read_only,
+            CopyMode::Reflink | CopyMode::Copy => false,
+        };
+        if link {
+            match fs::hard_link(from, to) {
+                Ok(()) => {
+                    self.stats.hardlinked += 1;
+                    return Ok(());
+                }
+                Err(e) if self.mode == CopyMode::Hardlink => return Err(e),
+                // Across devices, for example; a copy still works
+                Err(_) => {}
+            }
+        }
+        copy_new(from, to)?;
+        self.stats.copied += 1;
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    copy_new(from, to).map(drop)
+}
+
+/// Clones `from` into the new file `to`, sharing data blocks until either is written.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn reflink(from: &Path, to: &Path) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+    let src = fs::File::open(from)?;
+    let dst = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(to)?;
+    // SAFETY: both descriptors are open for the duration of the call.
+    let rc = unsafe { libc::ioctl(dst.as_raw_fd(), libc::FICLONE, src.as_raw_fd()) };
+    if rc != 0 {
+        let err = io::Error::last_os_error();
+        drop(dst);
+        let _ = fs::remove_file(to);
+        return Err(err);
+    }
+    dst.set_permissions(src.metadata()?.permissions())
+}
+
+/// Clones `from` into the new file `to`, sharing data blocks until either is written.
+#[cfg(target_os = "macos")]
+fn reflink(from: &Path, to: &Path) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    let c = |p: &Path| {
+        CString::new(p.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    };
+    let (src, dst) = (c(from)?, c(to)?);
+    // SAFETY: both pointers are valid NUL-terminated strings for the duration of the call.
+    if unsafe { libc::clonefile(src.as_ptr(), dst.as_ptr(), 0) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+fn reflink(_from: &Path, _to: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "reflinks are not supported on this platform",
+    ))
+}
@@ -0,0 +1,88 @@
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::tui;
+
+const ISSUES_URL: &str = "https://github.com/dariuszparys/try-rs/issues";
+
+/// Replaces the default panic output so a panic mid-selector never leaves the terminal in
+/// raw mode on the alternate screen. A panic on the thread running the selector restores
+/// the terminal before anything is printed; every panic gets a short report on stderr and
+/// its backtrace saved to a file under [`crash_dir`].
+///
+/// A panic on another thread while the selector is up (a background worker, say) only saves
+/// its backtrace: the selector may recover, and printing would garble its screen.
+pub(crate) fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let owned = tui::terminal_owned();
+        if owned == Some(true) {
+            tui::restore_terminal();
+        }
+        let message = panic_message(info);
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_default();
+        let text = report(&message, &location, &Backtrace::force_capture().to_string());
+        let saved = save_report(&crash_dir(), &text);
+        if owned == Some(false) {
+            return;
+        }
+        let mut msg = format!("try crashed: {message}");
+        if !location.is_empty() {
+            msg.push_str(&format!(" (at {location})"));
+        }
+        msg.push_str(&match saved {
+            Ok(path) => format!(
+                "\nThe backtrace was saved to {}; please attach it to a report at {ISSUES_URL}",
+                path.display()
+            ),
+            Err(e) => {
+                format!("\nThe backtrace could not be saved ({e}); please report at {ISSUES_URL}")
+            }
+        });
+        let _ = tui::error(&mut io::stderr(), &msg);
+    }));
+}
+
+/// Where crash reports go: `try/crashes` in the user's cache directory, else the temp dir.
+pub(crate) fn crash_dir() -> PathBuf {
+    dirs::cache_dir()
+        .map(|d| d.join("try").join("crashes"))
+        .unwrap_or_else(|| std::env::temp_dir().join("try-crashes"))
+}
+
+/// The text of the panic, for the usual `&str` and `String` payloads.
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panic with a non-string payload".to_string())
+}
+
+/// The saved report: version, platform, the panic, and the backtrace.
+pub(crate) fn report(message: &str, location: &str, backtrace: &str) -> String {
+    format!(
+        "try {} ({} {})\npanic: {message}\nlocation: {location}\n\n{backtrace}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+    )
+}
+
+/// Writes `text` to a new `panic-<seconds>-<pid>.txt` in `dir`, creating `dir` as needed.
+pub(crate) fn save_report(dir: &Path, text: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let path = dir.join(format!("panic-{secs}-{}.txt", std::process::id()));
+    fs::write(&path, text)?;
+    Ok(path)
+}
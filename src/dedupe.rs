@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::diff::list_files;
+
+/// Bytes hashed from each end of a file; files up to twice this size are hashed whole.
+const SAMPLE_BYTES: u64 = 4 * 1024;
+
+/// Cheap content summary of a try: each file's size and a hash of its head and tail.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Fingerprint {
+    files: HashMap<PathBuf, (u64, u64)>,
+    /// Total bytes of the fingerprinted files.
+    pub total: u64,
+}
+
+/// Fingerprints the files under `root`, skipping the same folders as `try diff`. Unreadable
+/// files count by size only.
+pub(crate) fn fingerprint(root: &Path) -> Fingerprint {
+    let mut print = Fingerprint::default();
+    for (rel, size) in list_files(root) {
+        let sample = sample_hash(&root.join(&rel), size).unwrap_or(0);
+        print.total += size;
+        print.files.insert(rel, (size, sample));
+    }
+    print
+}
+
+/// Share of bytes that `a` and `b` have in common (same path, size, and sample), relative to
+/// the larger of the two; `0.0` when either is empty.
+pub(crate) fn similarity(a: &Fingerprint, b: &Fingerprint) -> f64 {
+    if a.total == 0 || b.total == 0 {
+        return 0.0;
+    }
+    let larger = a.total.max(b.total);
+    let shared: u64 = a
+        .files
+        .iter()
+        .filter(|(path, key)| b.files.get(*path) == Some(key))
+        .map(|(_, (size, _))| size)
+        .sum();
+    shared as f64 / larger as f64
+}
+
+/// Groups of indices into `prints` whose members are at least `threshold` similar to another
+/// member of the same group. Groups keep index order; singletons are left out.
+pub(crate) fn group_duplicates(prints: &[Fingerprint], threshold: f64) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..prints.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for i in 0..prints.len() {
+        for j in i + 1..prints.len() {
+            let (a, b) = (&prints[i], &prints[j]);
+            // Sizes alone can rule a pair out before comparing files
+            let (small, large) = (a.total.min(b.total), a.total.max(b.total));
+            if (small as f64) < large as f64 * threshold {
+                continue;
+            }
+            if similarity(a, b) >= threshold {
+                let (ri, rj) = (root(&mut parent, i), root(&mut parent, j));
+                parent[rj] = ri;
+            }
+        }
+    }
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..prints.len() {
+        let r = root(&mut parent, i);
+        groups.entry(r).or_default().push(i);
+    }
+    let mut out: Vec<Vec<usize>> = groups.into_values().filter(|g| g.len() > 1).collect();
+    out.sort();
+    out
+}
+
+fn sample_hash(path: &Path, size: u64) -> io::Result<u64> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = DefaultHasher::new();
+    let mut buf = Vec::new();
+    if size <= 2 * SAMPLE_BYTES {
+        file.read_to_end(&mut buf)?;
+    } else {
+        file.by_ref().take(SAMPLE_BYTES).read_to_end(&mut buf)?;
+        file.seek(SeekFrom::End(-(SAMPLE_BYTES as i64)))?;
+        file.take(SAMPLE_BYTES).read_to_end(&mut buf)?;
+    }
+    buf.hash(&mut hasher);
+    Ok(hasher.finish())
+}
@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+use crate::info::SKIPPED_DIRS;
+
+const DEFAULT_DIFF_TOOL: &str = "git diff --no-index";
+const COMPARE_CHUNK: usize = 64 * 1024;
+
+/// File-level differences between two trees, with paths relative to their roots.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TreeDiff {
+    pub only_left: Vec<PathBuf>,
+    pub only_right: Vec<PathBuf>,
+    /// Files present on both sides whose contents differ.
+    pub changed: Vec<PathBuf>,
+    pub identical: usize,
+}
+
+impl TreeDiff {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.only_left.is_empty() && self.only_right.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares the files under `left` and `right`, in path order. `.git` and dependency or build
+/// directories are skipped, as are entries other than regular files.
+pub(crate) fn diff_trees(left: &Path, right: &Path) -> io::Result<TreeDiff> {
+    let left_files = list_files(left);
+    let mut right_files = list_files(right);
+    let mut diff = TreeDiff::default();
+    for (rel, left_len) in left_files {
+        match right_files.remove(&rel) {
+            None => diff.only_left.push(rel),
+            Some(right_len) => {
+                let same =
+                    left_len == right_len && same_contents(&left.join(&rel), &right.join(&rel))?;
+                if same {
+                    diff.identical += 1;
+                } else {
+                    diff.changed.push(rel);
+                }
+            }
+        }
+    }
+    diff.only_right = right_files.into_keys().collect();
+    Ok(diff)
+}
+
+/// Runs the content differ (`diff.tool`, default `git diff --no-index`) on the two trees,
+/// letting it write to the terminal.
+pub(crate) fn run_diff_tool(
+    tool: Option<&str>,
+    left: &Path,
+    right: &Path,
+) -> io::Result<ExitStatus> {
+    let tool = tool.unwrap_or(DEFAULT_DIFF_TOOL);
+    let mut parts = tool.split_whitespace();
+    let program = parts.next().unwrap_or("diff");
+    Command::new(program)
+        .args(parts)
+        .arg(left)
+        .arg(right)
+        .status()
+}
+
+/// Regular files under `root` keyed by relative path, with their sizes. `.git` and
+/// dependency or build directories are skipped.
+pub(crate) fn list_files(root: &Path) -> BTreeMap<PathBuf, u64> {
+    let mut out = BTreeMap::new();
+    walk(root, Path::new(""), &mut out);
+    out
+}
+
+fn walk(root: &Path, rel: &Path, out: &mut BTreeMap<PathBuf, u64>) {
+    let Ok(rd) = fs::read_dir(root.join(rel)) else {
+        return;
+    };
+    for e in rd.flatten() {
+        let name = e.file_name();
+        let rel = rel.join(&name);
+        let Ok(meta) = e.file_type() else { continue };
+        if meta.is_dir() {
+            let name = name.to_string_lossy();
+            if name != ".git" && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                walk(root, &rel, out);
+            }
+        } else if meta.is_file()
+            && let Ok(md) = e.metadata()
+        {
+            out.insert(rel, md.len());
+        }
+    }
+}
+
+fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    let (mut fa, mut fb) = (fs::File::open(a)?, fs::File::open(b)?);
+    let (mut ba, mut bb) = (vec![0u8; COMPARE_CHUNK], vec![0u8; COMPARE_CHUNK]);
+    loop {
+        let n = read_full(&mut fa, &mut ba)?;
+        let m = read_full(&mut fb, &mut bb)?;
+        if ba[..n] != bb[..m] {
+            return Ok(false);
+        }
+        if n == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+/// Reads until `buf` is full or the file ends, so both sides advance in equal steps.
+fn read_full(f: &mut fs::File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match f.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
@@ -1,10 +1,70 @@
 use std::io;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+use crate::i18n::{Msg, tr, trf};
+
 #[derive(Debug, Error)]
 pub enum Error {
-    #[error("I/O error: {0}")]
+    #[error("{}", trf(Msg::IoError, &[("error", .0)]))]
     Io(#[from] io::Error),
+    #[error("{}", trf(Msg::CannotWrite, &[("path", &.path.display()), ("hint", &.blocked.hint())]))]
+    Unwritable {
+        path: PathBuf,
+        blocked: WriteBlocked,
+        source: io::Error,
+    },
+    #[error("{}", trf(Msg::ArchiveFailed, &[("error", .0)]))]
+    Archive(String),
+    #[error("{}", trf(Msg::ClonesFailed, &[("failed", .failed), ("total", .total)]))]
+    ClonesFailed { failed: usize, total: usize },
+}
+
+impl Error {
+    /// Wraps a failed write under `path`, keeping targeted guidance for common causes.
+    pub(crate) fn from_write(path: &Path, source: io::Error) -> Self {
+        match WriteBlocked::classify(&source) {
+            Some(blocked) => Error::Unwritable {
+                path: path.to_path_buf(),
+                blocked,
+                source,
+            },
+            None => Error::Io(source),
+        }
+    }
+}
+
+/// Filesystem conditions that prevent creating or deleting tries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriteBlocked {
+    /// `EROFS`
+    ReadOnly,
+    /// `ENOSPC` (or a quota)
+    NoSpace,
+    /// `EACCES` / `EPERM`
+    PermissionDenied,
+}
+
+impl WriteBlocked {
+    pub(crate) fn classify(e: &io::Error) -> Option<Self> {
+        match e.kind() {
+            io::ErrorKind::ReadOnlyFilesystem => Some(WriteBlocked::ReadOnly),
+            io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded => {
+                Some(WriteBlocked::NoSpace)
+            }
+            io::ErrorKind::PermissionDenied => Some(WriteBlocked::PermissionDenied),
+            _ => None,
+        }
+    }
+
+    /// What went wrong and what to do about it.
+    pub(crate) fn hint(self) -> &'static str {
+        tr(match self {
+            WriteBlocked::ReadOnly => Msg::ReadOnlyHint,
+            WriteBlocked::NoSpace => Msg::NoSpaceHint,
+            WriteBlocked::PermissionDenied => Msg::PermissionDeniedHint,
+        })
+    }
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,529 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+
+/// Screen size assumed when the terminal cannot be measured, and for scripts that do not
+/// resize.
+pub(crate) const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+static REPLAY: OnceLock<Script> = OnceLock::new();
+static RECORD: OnceLock<PathBuf> = OnceLock::new();
+
+/// Sets the process-wide `--replay` script; only the first call takes effect.
+pub(crate) fn set_replay(script: Script) {
+    let _ = REPLAY.set(script);
+}
+
+/// The `--replay` script the selector should run instead of reading the terminal.
+pub(crate) fn replay() -> Option<Script> {
+    REPLAY.get().cloned()
+}
+
+/// Sets the process-wide `--record` file; only the first call takes effect.
+pub(crate) fn set_record(path: PathBuf) {
+    let _ = RECORD.set(path);
+}
+
+/// Where the selector should write the events it reads, for a later `--replay`.
+pub(crate) fn record_path() -> Option<&'static Path> {
+    RECORD.get().map(PathBuf::as_path)
+}
+
+/// Input of the selector: the terminal, or a [`Script`] in tests and `--replay`.
+pub(crate) trait EventSource {
+    /// Whether an event is ready within `timeout`.
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool>;
+    /// The next event, waiting for one as long as it takes.
+    fn read(&mut self) -> io::Result<Event>;
+    /// Columns and rows of the screen the selector draws on.
+    fn size(&self) -> (u16, u16);
+}
+
+/// Keys and resizes from the terminal, which must be in raw mode.
+pub(crate) struct Terminal;
+
+impl EventSource for Terminal {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        event::poll(timeout)
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        event::read()
+    }
+
+    fn size(&self) -> (u16, u16) {
+        terminal::size().unwrap_or(DEFAULT_SIZE)
+    }
+}
+
+/// A fixed sequence of events, either hand-written or recorded by `--record`.
+///
+/// Without timings, each event is ready at once, except to a poll that does not wait, so
+/// the selector handles them one at a time and draws a frame after every one. Recorded
+/// sessions carry the time of every event and play back in real time, so debouncing and
+/// the frame rate cap behave as they did. A script that runs out sends Esc, which cancels
+/// the selector.
+///
+/// The text form has one event per line; blank lines and `#` comments are skipped:
+///
+/// ```text
+/// # one key per character
+/// type alpha
+/// down
+/// ctrl-space
+/// resize 100 30
+/// enter
+/// ```
+///
+/// Key names are `enter`, `esc`, `tab`, `backtab`, `backspace`, `delete`, `up`, `down`,
+/// `left`, `right`, `home`, `end`, `pageup`, `pagedown`, `space`, or a single character,
+/// optionally after `ctrl-`, `alt-`, and `shift-`.
+///
+/// A recorded session is JSON holding the screen size and the events as the same lines,
+/// each with its time in milliseconds since the session started:
+///
+/// ```text
+/// {"version": 1, "size": [80, 24], "events": [{"ms": 0, "event": "type a"}, ...]}
+/// ```
+#[derive(Clone, Debug)]
+pub(crate) struct Script {
+    /// Events with when they arrive, counted from the first poll or read; `None` for at once.
+    events: VecDeque<(Option<Duration>, Event)>,
+    size: (u16, u16),
+    started: Option<Instant>,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl Script {
+    /// A script of `events` on a screen of [`DEFAULT_SIZE`].
+    pub(crate) fn new(events: impl IntoIterator<Item = Event>) -> Self {
+        Self {
+            events: events.into_iter().map(|e| (None, e)).collect(),
+            size: DEFAULT_SIZE,
+            started: None,
+        }
+    }
+
+    /// Reads a recorded session, or else the text form described on [`Script`]. Errors
+    /// name the first bad line or entry.
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        if text.trim_start().starts_with('{') {
+            return parse_session(text);
+        }
+        let mut events = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let parsed = parse_line(line).ok_or_else(|| {
+                let line = line.trim();
+                format!("line {}: unknown event `{line}`", n + 1)
+            })?;
+            events.extend(parsed);
+        }
+        Ok(Self::new(events))
+    }
+
+    /// Whether the events carry recorded times.
+    pub(crate) fn is_timed(&self) -> bool {
+        self.events.iter().any(|(at, _)| at.is_some())
+    }
+
+    /// How long until the next event is due; zero when it is ready or there is none.
+    fn wait(&mut self) -> Duration {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        match self.events.front() {
+            Some(&(Some(at), _)) => at.saturating_sub(started.elapsed()),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+impl EventSource for Script {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        if !matches!(self.events.front(), Some((Some(_), _))) {
+            return Ok(!timeout.is_zero());
+        }
+        let wait = self.wait();
+        thread::sleep(wait.min(timeout));
+        Ok(wait <= timeout)
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        thread::sleep(self.wait());
+        let event = self.events.pop_front().map_or_else(
+            || Event::Key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)),
+            |(_, e)| e,
+        );
+        if let Event::Resize(w, h) = event {
+            self.size = (w, h);
+        }
+        Ok(event)
+    }
+
+    fn size(&self) -> (u16, u16) {
+        self.size
+    }
+}
+
+/// Passes events through from `inner` and saves them, with their times and the screen
+/// size, as a session file for `--replay` once dropped. Writing is best effort beyond
+/// creating the file: a full disk never interrupts the selector.
+pub(crate) struct Recorder<S> {
+    inner: S,
+    file: File,
+    size: (u16, u16),
+    started: Instant,
+    events: Vec<(Duration, String)>,
+}
+
+impl<S: EventSource> Recorder<S> {
+    /// Records the events of `inner` into a new file at `path`.
+    pub(crate) fn create(inner: S, path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            file: File::create(path)?,
+            size: inner.size(),
+            inner,
+            started: Instant::now(),
+            events: Vec::new(),
+        })
+    }
+}
+
+impl<S: EventSource> EventSource for Recorder<S> {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        self.inner.poll(timeout)
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        let event = self.inner.read()?;
+        if let Some(line) = event_line(&event) {
+            self.events.push((self.started.elapsed(), line));
+        }
+        Ok(event)
+    }
+
+    fn size(&self) -> (u16, u16) {
+        self.inner.size()
+    }
+}
+
+impl<S> Drop for Recorder<S> {
+    fn drop(&mut self) {
+        let text = session_json(self.size, &self.events);
+        let _ = self.file.write_all(text.as_bytes());
+    }
+}
+
+/// Events of one line of the text form; `None` when it is not understood.
+fn parse_line(line: &str) -> Option<Vec<Event>> {
+    if let Some(typed) = line.strip_prefix("type ") {
+        return Some(typed.chars().map(|c| Event::Key(typed_key(c))).collect());
+    }
+    let line = line.split('#').next().unwrap_or_default().trim();
+    let event = match line.split_whitespace().collect::<Vec<_>>()[..] {
+        [] => return Some(Vec::new()),
+        ["resize", w, h] => Event::Resize(w.parse().ok()?, h.parse().ok()?),
+        [key] => Event::Key(parse_key(key)?),
+        _ => return None,
+    };
+    Some(vec![event])
+}
+
+/// `event` as a line of the text form; `None` for events the selector ignores, such as key
+/// releases.
+fn event_line(event: &Event) -> Option<String> {
+    match event {
+        Event::Key(key) if key.kind != KeyEventKind::Press => None,
+        Event::Key(key) => match key.code {
+            KeyCode::Char(c) if typed_key(c) == *key => Some(format!("type {c}")),
+            _ => key_name(key),
+        },
+        Event::Resize(w, h) => Some(format!("resize {w} {h}")),
+        _ => None,
+    }
+}
+
+const SESSION_VERSION: u64 = 1;
+
+fn session_json(size: (u16, u16), events: &[(Duration, String)]) -> String {
+    let mut out = format!(
+        "{{\n  \"version\": {SESSION_VERSION},\n  \"size\": [{}, {}],\n  \"events\": [",
+        size.0, size.1
+    );
+    for (i, (at, line)) in events.iter().enumerate() {
+        let sep = if i == 0 { "" } else { "," };
+        out.push_str(&format!(
+            "{sep}\n    {{\"ms\": {}, \"event\": {}}}",
+            at.as_millis(),
+            crate::util::json_string(line)
+        ));
+    }
+    out.push_str("\n  ]\n}\n");
+    out
+}
+
+fn parse_session(text: &str) -> Result<Script, String> {
+    let root = Json::parse(text)?;
+    let version = root.get("version").and_then(Json::as_u64);
+    if version != Some(SESSION_VERSION) {
+        return Err(format!(
+            "unsupported session version (expected {SESSION_VERSION})"
+        ));
+    }
+    let size = match root.get("size") {
+        Some(Json::Array(wh)) => match wh.as_slice() {
+            [w, h] => w
+                .as_u64()
+                .zip(h.as_u64())
+                .and_then(|(w, h)| Some((u16::try_from(w).ok()?, u16::try_from(h).ok()?))),
+            _ => None,
+        },
+        _ => None,
+    }
+    .ok_or("session has no valid \"size\"")?;
+    let Some(Json::Array(entries)) = root.get("events") else {
+        return Err("session has no \"events\" list".into());
+    };
+    let mut events = VecDeque::new();
+    for (n, entry) in entries.iter().enumerate() {
+        let at = entry
+            .get("ms")
+            .and_then(Json::as_u64)
+            .map(Duration::from_millis);
+        let parsed = match (at, entry.get("event")) {
+            (Some(at), Some(Json::String(line))) => parse_line(line).map(|es| (at, es)),
+            _ => None,
+        };
+        let (at, parsed) = parsed.ok_or_else(|| format!("event {}: not understood", n + 1))?;
+        events.extend(parsed.into_iter().map(|e| (Some(at), e)));
+    }
+    Ok(Script {
+        events,
+        size,
+        started: None,
+    })
+}
+
+/// Just enough JSON to read session files back: numbers are kept as unsigned integers.
+#[derive(Debug, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn parse(text: &str) -> Result<Json, String> {
+        let mut chars = text.chars().peekable();
+        let value = parse_value(&mut chars)?;
+        skip_ws(&mut chars);
+        match chars.next() {
+            None => Ok(value),
+            Some(c) => Err(format!("unexpected `{c}` after the session")),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+fn skip_ws(chars: &mut Chars<'_>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn expect(chars: &mut Chars<'_>, want: char) -> Result<(), String> {
+    skip_ws(chars);
+    match chars.next() {
+        Some(c) if c == want => Ok(()),
+        Some(c) => Err(format!("expected `{want}`, found `{c}`")),
+        None => Err(format!("expected `{want}`, found the end")),
+    }
+}
+
+fn parse_value(chars: &mut Chars<'_>) -> Result<Json, String> {
+    skip_ws(chars);
+    match chars.peek().copied() {
+        Some('{') => {
+            chars.next();
+            let mut fields = Vec::new();
+            skip_ws(chars);
+            if chars.next_if_eq(&'}').is_some() {
+                return Ok(Json::Object(fields));
+            }
+            loop {
+                skip_ws(chars);
+                let Json::String(key) = parse_value(chars)? else {
+                    return Err("object keys must be strings".into());
+                };
+                expect(chars, ':')?;
+                fields.push((key, parse_value(chars)?));
+                skip_ws(chars);
+                match chars.next() {
+                    Some(',') => {}
+                    Some('}') => return Ok(Json::Object(fields)),
+                    _ => return Err("expected `,` or `}` in an object".into()),
+                }
+            }
+        }
+        Some('[') => {
+            chars.next();
+            let mut items = Vec::new();
+            skip_ws(chars);
+            if chars.next_if_eq(&']').is_some() {
+                return Ok(Json::Array(items));
+            }
+            loop {
+                items.push(parse_value(chars)?);
+                skip_ws(chars);
+                match chars.next() {
+                    Some(',') => {}
+                    Some(']') => return Ok(Json::Array(items)),
+                    _ => return Err("expected `,` or `]` in a list".into()),
+                }
+            }
+        }
+        Some('"') => {
+            chars.next();
+            let mut s = String::new();
+            loop {
+                match chars.next().ok_or("unterminated string")? {
+                    '"' => return Ok(Json::String(s)),
+                    '\\' => match chars.next().ok_or("unterminated string")? {
+                        'n' => s.push('\n'),
+                        't' => s.push('\t'),
+                        'r' => s.push('\r'),
+                        'b' => s.push('\u{8}'),
+                        'f' => s.push('\u{c}'),
+                        'u' => {
+                            let hex: String = chars.by_ref().take(4).collect();
+                            let c = u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| format!("bad escape `\\u{hex}`"))?;
+                            s.push(c);
+                        }
+                        c => s.push(c),
+                    },
+                    c => s.push(c),
+                }
+            }
+        }
+        Some(c) if c.is_ascii_digit() => {
+            let mut digits = String::new();
+            while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                digits.push(d);
+            }
+            digits.parse().map(Json::Number).map_err(|e| e.to_string())
+        }
+        Some(c) if c.is_ascii_alphabetic() => {
+            let mut word = String::new();
+            while let Some(l) = chars.next_if(char::is_ascii_alphabetic) {
+                word.push(l);
+            }
+            match word.as_str() {
+                "true" => Ok(Json::Bool(true)),
+                "false" => Ok(Json::Bool(false)),
+                "null" => Ok(Json::Null),
+                _ => Err(format!("unexpected `{word}`")),
+            }
+        }
+        Some(c) => Err(format!("unexpected `{c}`")),
+        None => Err("unexpected end of the session".into()),
+    }
+}
+
+/// The key event a terminal sends for typing `c`.
+fn typed_key(c: char) -> KeyEvent {
+    let modifiers = if c.is_uppercase() {
+        KeyModifiers::SHIFT
+    } else {
+        KeyModifiers::NONE
+    };
+    KeyEvent::new(KeyCode::Char(c), modifiers)
+}
+
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("space", KeyCode::Char(' ')),
+];
+
+const MODIFIER_NAMES: &[(&str, KeyModifiers)] = &[
+    ("ctrl-", KeyModifiers::CONTROL),
+    ("alt-", KeyModifiers::ALT),
+    ("shift-", KeyModifiers::SHIFT),
+];
+
+fn parse_key(spec: &str) -> Option<KeyEvent> {
+    let mut rest = spec;
+    let mut modifiers = KeyModifiers::NONE;
+    while let Some(&(prefix, m)) = MODIFIER_NAMES.iter().find(|(p, _)| rest.starts_with(p)) {
+        modifiers |= m;
+        rest = &rest[prefix.len()..];
+    }
+    let code = match KEY_NAMES.iter().find(|(name, _)| *name == rest) {
+        Some(&(_, code)) => code,
+        None => {
+            let mut chars = rest.chars();
+            let c = chars.next()?;
+            chars.next().is_none().then_some(KeyCode::Char(c))?
+        }
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
+
+/// Name of `key` in the script text form, the inverse of [`parse_key`].
+fn key_name(key: &KeyEvent) -> Option<String> {
+    let base = match KEY_NAMES.iter().find(|(_, code)| *code == key.code) {
+        Some((name, _)) => name.to_string(),
+        None => match key.code {
+            KeyCode::Char(c) => c.to_string(),
+            _ => return None,
+        },
+    };
+    let mut name = String::new();
+    for (prefix, m) in MODIFIER_NAMES {
+        if key.modifiers.contains(*m) {
+            name.push_str(prefix);
+        }
+    }
+    Some(name + &base)
+}
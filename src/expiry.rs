@@ -0,0 +1,74 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::storage::META_DIR;
+
+/// Expiry times of ephemeral tries under `.try_meta`: `name<TAB>expires (Unix seconds)` per
+/// line.
+const EXPIRY_FILE: &str = "expiry.tsv";
+
+/// When each ephemeral try (`try new --ttl`) expires.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Expiries {
+    path: PathBuf,
+    entries: HashMap<String, u64>,
+}
+
+impl Expiries {
+    /// Loads the expiry times stored under `base_path`; a missing file or malformed lines
+    /// are treated as no ephemeral tries.
+    pub(crate) fn load(base_path: &Path) -> Self {
+        let path = base_path.join(META_DIR).join(EXPIRY_FILE);
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let entries = text
+            .lines()
+            .filter_map(|line| {
+                let (name, expires) = line.split_once('\t')?;
+                Some((name.to_string(), expires.parse().ok()?))
+            })
+            .collect();
+        Self { path, entries }
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<SystemTime> {
+        let secs = *self.entries.get(name)?;
+        Some(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    pub(crate) fn set(&mut self, name: &str, expires: SystemTime) {
+        let secs = expires
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        self.entries.insert(name.to_string(), secs);
+    }
+
+    /// Names of the tries whose expiry is at or before `now`.
+    pub(crate) fn expired(&self, now: SystemTime) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|name| self.get(name).is_some_and(|at| at <= now))
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Writes the expiry times, keeping only the tries named in `keep`.
+    pub(crate) fn save(&mut self, keep: &HashSet<&str>) -> io::Result<()> {
+        self.entries.retain(|name, _| keep.contains(name.as_str()));
+        let mut lines: Vec<String> = self
+            .entries
+            .iter()
+            .map(|(name, secs)| format!("{name}\t{secs}"))
+            .collect();
+        lines.sort();
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, lines.join("\n") + "\n")
+    }
+}
@@ -0,0 +1,480 @@
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use crate::tasks::CancelToken;
+
+/// Working-tree state of a try that is a git repository.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GitStatus {
+    /// Current branch, or `None` when HEAD is detached.
+    pub branch: Option<String>,
+    /// Changed, staged, or untracked paths.
+    pub changes: usize,
+    /// Commits not yet pushed to the upstream branch.
+    pub ahead: usize,
+}
+
+impl GitStatus {
+    /// Whether there is work left to finish: uncommitted changes or unpushed commits.
+    pub(crate) fn has_pending_work(&self) -> bool {
+        self.changes > 0 || self.ahead > 0
+    }
+
+    /// Compact row indicator such as `*3 ↑2`; empty when the tree is clean and pushed.
+    pub(crate) fn indicator(&self) -> String {
+        let mut parts = Vec::new();
+        if self.changes > 0 {
+            parts.push(format!("*{}", self.changes));
+        }
+        if self.ahead > 0 {
+            parts.push(format!("↑{}", self.ahead));
+        }
+        parts.join(" ")
+    }
+}
+
+/// What HEAD of a repository points to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Head {
+    Branch(String),
+    /// Detached HEAD, with the abbreviated commit id.
+    Detached(String),
+}
+
+impl Head {
+    /// Branch name, or the short commit id when detached.
+    pub(crate) fn label(&self) -> &str {
+        match self {
+            Head::Branch(name) | Head::Detached(name) => name,
+        }
+    }
+}
+
+/// The working tree `dir` lies in: the nearest of `dir` and its ancestors holding `.git`.
+pub(crate) fn repo_root(dir: &Path) -> Option<&Path> {
+    dir.ancestors().find(|d| d.join(".git").exists())
+}
+
+/// HEAD of the repository at `dir`, read from `.git/HEAD` without spawning git, so it is
+/// cheap enough for every row of a listing. Follows the `gitdir:` file of worktrees and
+/// submodules. `None` when `dir` is not a repository.
+pub(crate) fn read_head(dir: &Path) -> Option<Head> {
+    parse_head(&std::fs::read_to_string(git_dir(dir)?.join("HEAD")).ok()?)
+}
+
+/// The git directory of the repository at `dir`: `dir/.git`, or where its `gitdir:` file
+/// points for worktrees and submodules.
+fn git_dir(dir: &Path) -> Option<std::path::PathBuf> {
+    let dot_git = dir.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let link = std::fs::read_to_string(&dot_git).ok()?;
+    Some(dir.join(link.strip_prefix("gitdir:")?.trim()))
+}
+
+/// Parses the contents of a `HEAD` file: `ref: refs/heads/<branch>` or a commit id.
+pub(crate) fn parse_head(text: &str) -> Option<Head> {
+    const SHORT_ID: usize = 7;
+    let text = text.trim();
+    if let Some(target) = text.strip_prefix("ref:") {
+        let target = target.trim();
+        let name = target.strip_prefix("refs/heads/").unwrap_or(target);
+        return Some(Head::Branch(name.to_string()));
+    }
+    let is_id = text.len() >= SHORT_ID && text.chars().all(|c| c.is_ascii_hexdigit());
+    is_id.then(|| Head::Detached(text[..SHORT_ID].to_string()))
+}
+
+/// Reads the git status of `dir`, returning `None` when it is not a repository or git is
+/// unavailable. Only spawns git when `dir/.git` exists, so plain folders stay cheap.
+pub(crate) fn read_status(dir: &Path) -> Option<GitStatus> {
+    if !dir.join(".git").exists() {
+        return None;
+    }
+    let output = status_command(dir).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_porcelain_v2(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// [`read_status`] that kills `git` and returns `None` once `cancel` is set.
+pub(crate) fn read_status_until(dir: &Path, cancel: &CancelToken) -> Option<GitStatus> {
+    if !dir.join(".git").exists() {
+        return None;
+    }
+    let mut child = status_command(dir).stdout(Stdio::piped()).spawn().ok()?;
+    let mut stdout = child.stdout.take()?;
+    let child = Arc::new(Mutex::new(child));
+    let killer = Arc::clone(&child);
+    // Reading to EOF waits for git without polling; killing it on cancel closes the pipe
+    let out = cancel.interrupt_with(
+        move || {
+            if let Ok(mut child) = killer.lock() {
+                let _ = child.kill();
+            }
+        },
+        || {
+            let mut out = String::new();
+            stdout.read_to_string(&mut out).map(|_| out)
+        },
+    );
+    let status = child.lock().ok()?.wait().ok()?;
+    let out = out.ok().filter(|_| !cancel.is_canceled())?;
+    status.success().then(|| parse_porcelain_v2(&out))
+}
+
+fn status_command(dir: &Path) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(dir)
+        .args(["status", "--porcelain=v2", "--branch"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null());
+    command
+}
+
+/// Parses `git status --porcelain=v2 --branch` output.
+pub(crate) fn parse_porcelain_v2(out: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    for line in out.lines() {
+        if let Some(head) = line.strip_prefix("# branch.head ") {
+            if head != "(detached)" {
+                status.branch = Some(head.to_string());
+            }
+        } else if let Some(ab) = line.strip_prefix("# branch.ab ") {
+            // Format: "+<ahead> -<behind>"
+            if let Some(ahead) = ab
+                .split_whitespace()
+                .next()
+                .and_then(|a| a.strip_prefix('+'))
+            {
+                status.ahead = ahead.parse().unwrap_or(0);
+            }
+        } else if !line.starts_with('#') && !line.is_empty() {
+            status.changes += 1;
+        }
+    }
+    status
+}
+
+/// Whether `dir` is a shallow clone, i.e. it lacks part of its history.
+pub(crate) fn is_shallow(dir: &Path) -> bool {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "--is-shallow-repository"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+    matches!(output, Ok(o) if o.status.success() && o.stdout.trim_ascii() == b"true")
+}
+
+/// Fetches the history a shallow clone is missing, showing git's progress on the terminal.
+pub(crate) fn unshallow(dir: &Path) -> std::io::Result<std::process::ExitStatus> {
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["fetch", "--unshallow"])
+        .stdin(Stdio::null())
+        .status()
+}
+
+/// Local branch with commits that exist nowhere else.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct UnpushedBranch {
+    pub name: String,
+    /// Commits ahead of the upstream, or `None` when the branch has never been pushed.
+    pub ahead: Option<usize>,
+}
+
+/// Local branches of `dir` that are ahead of their upstream or have none. Branches whose
+/// upstream was deleted (`[gone]`) are left out, as they are usually merged.
+pub(crate) fn unpushed_branches(dir: &Path) -> Vec<UnpushedBranch> {
+    if !dir.join(".git").exists() {
+        return Vec::new();
+    }
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "for-each-ref",
+            "--format=%(refname:short)%09%(upstream)%09%(upstream:track)",
+            "refs/heads",
+        ])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+    match output {
+        Ok(o) if o.status.success() => parse_branch_tracking(&String::from_utf8_lossy(&o.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+/// Parses `name<TAB>upstream<TAB>track` lines from `git for-each-ref`.
+pub(crate) fn parse_branch_tracking(out: &str) -> Vec<UnpushedBranch> {
+    let mut branches = Vec::new();
+    for line in out.lines() {
+        let mut fields = line.split('\t');
+        let (Some(name), Some(upstream)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let track = fields.next().unwrap_or("");
+        let ahead = if upstream.is_empty() {
+            None
+        } else if let Some(rest) = track.split("ahead ").nth(1) {
+            let n: String = rest.chars().take_while(char::is_ascii_digit).collect();
+            Some(n.parse().unwrap_or(0))
+        } else {
+            continue;
+        };
+        branches.push(UnpushedBranch {
+            name: name.to_string(),
+            ahead,
+        });
+    }
+    branches
+}
+
+/// Warnings for the delete confirmation of `dir`: uncommitted changes and branches with
+/// commits that would be lost. Empty for clean, pushed repositories and plain folders.
+pub(crate) fn deletion_warnings(dir: &Path) -> Vec<String> {
+    let changes = read_status(dir).map_or(0, |s| s.changes);
+    describe_risks(changes, &unpushed_branches(dir))
+}
+
+/// Warning lines for `changes` uncommitted paths and the given unpushed branches.
+pub(crate) fn describe_risks(changes: usize, unpushed: &[UnpushedBranch]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if changes > 0 {
+        let noun = if changes == 1 { "path" } else { "paths" };
+        warnings.push(format!("! Uncommitted changes: {changes} {noun}"));
+    }
+    if !unpushed.is_empty() {
+        let list: Vec<String> = unpushed
+            .iter()
+            .map(|b| match b.ahead {
+                Some(n) => format!("{} (↑{n})", b.name),
+                None => format!("{} (never pushed)", b.name),
+            })
+            .collect();
+        warnings.push(format!("! Unpushed branches: {}", list.join(", ")));
+    }
+    warnings
+}
+
+/// URL of the `origin` remote of `dir`, if it is a repository with one configured. Asks git,
+/// so `url.<base>.insteadOf` rules apply; without git on PATH it is read from the
+/// repository's config file by [`config_origin_url`] instead.
+pub(crate) fn origin_url(dir: &Path) -> Option<String> {
+    if !dir.join(".git").exists() {
+        return None;
+    }
+    let output = match Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["remote", "get-url", "origin"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return config_origin_url(dir),
+        Err(_) => return None,
+    };
+    if !output.status.success() {
+        return None;
+    }
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!url.is_empty()).then_some(url)
+}
+
+/// URL of the `origin` remote of `dir` as written in the repository's config file, following
+/// the `commondir` of linked worktrees. `url.<base>.insteadOf` rules are not applied.
+pub(crate) fn config_origin_url(dir: &Path) -> Option<String> {
+    let git_dir = git_dir(dir)?;
+    // Linked worktrees keep the shared config in the main repository
+    let common = match std::fs::read_to_string(git_dir.join("commondir")) {
+        Ok(rel) => git_dir.join(rel.trim()),
+        Err(_) => git_dir,
+    };
+    let config = std::fs::read_to_string(common.join("config")).ok()?;
+    parse_remote_url(&config, "origin")
+}
+
+/// First `url` of `[remote "<remote>"]` in the text of a git config file.
+pub(crate) fn parse_remote_url(config: &str, remote: &str) -> Option<String> {
+    let wanted = format!("remote \"{remote}\"");
+    let mut in_section = false;
+    for line in config.lines() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.split(']').next().unwrap_or("").trim();
+            in_section = header == wanted;
+            continue;
+        }
+        if !in_section || line.starts_with(['#', ';']) {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if key.trim().eq_ignore_ascii_case("url") {
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            return (!value.is_empty()).then(|| value.to_string());
+        }
+    }
+    None
+}
+
+/// Web page of the `origin` remote of `dir`, or a user-facing reason why there is none.
+pub(crate) fn browse_url(dir: &Path) -> Result<String, String> {
+    let name = dir.file_name().map_or_else(
+        || dir.display().to_string(),
+        |n| n.to_string_lossy().to_string(),
+    );
+    if !dir.join(".git").exists() {
+        return Err(format!("{name} is not a git repository"));
+    }
+    let remote = origin_url(dir).ok_or_else(|| format!("{name} has no 'origin' remote"))?;
+    remote_web_url(&remote).ok_or_else(|| format!("Unrecognized remote URL: {remote}"))
+}
+
+/// Translates a git remote into the repository's web page, e.g.
+/// `git@github.com:user/repo.git` → `https://github.com/user/repo`.
+/// Handles scp-style and `ssh://` remotes, strips credentials and the `.git` suffix.
+pub(crate) fn remote_web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim();
+    let (host, path) = if let Some(rest) = remote
+        .strip_prefix("https://")
+        .or_else(|| remote.strip_prefix("http://"))
+        .or_else(|| remote.strip_prefix("ssh://"))
+        .or_else(|| remote.strip_prefix("git://"))
+    {
+        rest.split_once('/')?
+    } else if let Some((host, path)) = remote.split_once(':') {
+        // scp-like syntax: [user@]host:path
+        if host.contains('/') {
+            return None;
+        }
+        (host, path)
+    } else {
+        return None;
+    };
+    // Drop "user@" / "user:token@" and any ":port" from the host
+    let host = host.rsplit_once('@').map_or(host, |(_, h)| h);
+    let host = host.split_once(':').map_or(host, |(h, _)| h);
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    Some(format!("https://{host}/{path}"))
+}
+
+/// Commit timestamps (Unix seconds, newest first) of the current branch, at most `limit`.
+/// Empty when `dir` is not a repository or has no commits.
+pub(crate) fn commit_times(dir: &Path, limit: usize) -> Vec<u64> {
+    if !dir.join(".git").exists() {
+        return Vec::new();
+    }
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["log", "--format=%ct"])
+        .arg(format!("-n{limit}"))
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+    match output {
+        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout)
+            .lines()
+            .filter_map(|l| l.trim().parse().ok())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Commits `(ahead, behind)` of the current branch of `dir` against its upstream as the
+/// remote has it now. `git fetch --dry-run` downloads the new commits without moving any ref,
+/// so the counts come from what it reports. `None` without an upstream, or when the remote
+/// cannot be reached without prompting for credentials.
+pub(crate) fn remote_counts(dir: &Path) -> Option<(usize, usize)> {
+    if !dir.join(".git").exists() {
+        return None;
+    }
+    let upstream = git_stdout(
+        dir,
+        &["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"],
+    )?;
+    let mut fetch = Command::new("git");
+    fetch
+        .arg("-C")
+        .arg(dir)
+        .args(["fetch", "--dry-run"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null());
+    if std::env::var_os("GIT_SSH_COMMAND").is_none() {
+        fetch.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+    }
+    let fetch = fetch.output().ok()?;
+    if !fetch.status.success() {
+        return None;
+    }
+    let updates = parse_fetch_dry_run(&String::from_utf8_lossy(&fetch.stderr));
+    let target = updates
+        .into_iter()
+        .find(|(remote_ref, _)| *remote_ref == upstream)
+        .map_or(upstream, |(_, new)| new);
+    let counts = git_stdout(
+        dir,
+        &[
+            "rev-list",
+            "--left-right",
+            "--count",
+            &format!("HEAD...{target}"),
+        ],
+    )?;
+    let (ahead, behind) = counts.split_once('\t')?;
+    Some((ahead.parse().ok()?, behind.parse().ok()?))
+}
+
+/// `(remote-tracking ref, new commit)` for each ref `git fetch --dry-run` would move, from
+/// lines like `   1a2b3c4..5d6e7f8  main  -> origin/main`. New refs carry no commit range
+/// and are left out.
+pub(crate) fn parse_fetch_dry_run(out: &str) -> Vec<(String, String)> {
+    out.lines()
+        .filter_map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let arrow = tokens.iter().position(|t| *t == "->")?;
+            let range = tokens[..arrow].iter().find(|t| t.contains(".."))?;
+            let new = range.rsplit("..").next()?.trim_start_matches('.');
+            let remote_ref = tokens.get(arrow + 1)?;
+            Some((remote_ref.to_string(), new.to_string()))
+        })
+        .collect()
+}
+
+fn git_stdout(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,96 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File under the tries directory holding past search queries, one per line, oldest first.
+/// Lines of the form `query<TAB>name` record which try was picked for a query.
+pub(crate) const HISTORY_FILE: &str = ".try_history";
+// Oldest entries are dropped beyond this many queries, and likewise for picks
+const MAX_ENTRIES: usize = 100;
+const MAX_PICKS: usize = 100;
+
+/// Recent search queries for one workspace, persisted next to the tries themselves.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct QueryHistory {
+    path: PathBuf,
+    entries: Vec<String>,
+    /// `(query, try name)` pairs, oldest first; one per query (compared case-insensitively).
+    picks: Vec<(String, String)>,
+}
+
+impl QueryHistory {
+    /// Loads the history stored under `base_path`; a missing or unreadable file yields an
+    /// empty history.
+    pub(crate) fn load(base_path: &Path) -> Self {
+        let path = base_path.join(HISTORY_FILE);
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let mut history = Self {
+            path,
+            ..Self::default()
+        };
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            match line.split_once('\t') {
+                Some((query, name)) => history.picks.push((query.into(), name.into())),
+                None => history.entries.push(line.to_string()),
+            }
+        }
+        history
+    }
+
+    /// Records `query` as the most recent entry, moving an existing duplicate to the end.
+    pub(crate) fn push(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        self.entries.retain(|e| e != query);
+        self.entries.push(query.to_string());
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// Remembers that `name` was chosen for `query`, replacing an earlier choice.
+    pub(crate) fn record_pick(&mut self, query: &str, name: &str) {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() || query.contains('\t') {
+            return;
+        }
+        self.picks.retain(|(q, _)| *q != query);
+        self.picks.push((query, name.to_string()));
+        if self.picks.len() > MAX_PICKS {
+            let excess = self.picks.len() - MAX_PICKS;
+            self.picks.drain(..excess);
+        }
+    }
+
+    /// Name last chosen for `query`, if any.
+    pub(crate) fn picked(&self, query: &str) -> Option<&str> {
+        let query = query.trim().to_lowercase();
+        self.picks
+            .iter()
+            .rev()
+            .find(|(q, _)| *q == query)
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// Entry `steps_back` positions from the newest (0 = most recent), if any.
+    pub(crate) fn recall(&self, steps_back: usize) -> Option<&str> {
+        let idx = self.entries.len().checked_sub(steps_back + 1)?;
+        self.entries.get(idx).map(String::as_str)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn save(&self) -> io::Result<()> {
+        let mut out = self.entries.join("\n");
+        out.push('\n');
+        for (query, name) in &self.picks {
+            out.push_str(&format!("{query}\t{name}\n"));
+        }
+        fs::write(&self.path, out)
+    }
+}
@@ -0,0 +1,381 @@
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// A language the user-facing strings are translated into.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    #[cfg(test)]
+    pub(crate) const ALL: [Locale; 2] = [Locale::En, Locale::De];
+
+    /// The locale for a tag such as `de`, `de-AT`, or `de_DE.UTF-8`, matched by language;
+    /// `C` and `POSIX` are English. `None` when the language has no translation.
+    pub(crate) fn parse(tag: &str) -> Option<Self> {
+        let tag = tag.trim();
+        let lang = tag
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    /// The locale the environment asks for, looked up with `var`: the first non-empty of
+    /// `LC_ALL`, `LC_MESSAGES`, and `LANG`, as in POSIX. English when that names a language
+    /// without a translation, or when none is set.
+    pub(crate) fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(var)
+            .find(|value| !value.trim().is_empty())
+            .and_then(|value| Locale::parse(&value))
+            .unwrap_or_default()
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Sets the process-wide locale (`ui.locale`, else the environment); only the first call
+/// takes effect.
+pub(crate) fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+/// The locale user-facing strings are shown in; English until [`set_locale`] is called.
+pub(crate) fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
+/// `msg` in the current locale.
+pub(crate) fn tr(msg: Msg) -> &'static str {
+    msg.text(locale())
+}
+
+/// `msg` in the current locale with each `{name}` placeholder replaced by its value in `args`.
+pub(crate) fn trf(msg: Msg, args: &[(&str, &dyn Display)]) -> String {
+    fill(tr(msg), args)
+}
+
+/// `template` with each `{name}` placeholder replaced by its value in `args`.
+pub(crate) fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+/// A user-facing string of the selector, its prompts, and error messages. Every message has a text in every
+/// [`Locale`]; placeholders in braces are filled by [`trf`] and must be the same in each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Msg {
+    TitlePick,
+    TitleShort,
+    Title,
+    SpokenTitlePick,
+    SpokenTitle,
+    Search,
+    Marked,
+    NoMatches,
+    NoMatchesCreates,
+    SpokenNoMatchesCreates,
+    DidYouMean,
+    CreateNew,
+    CreateNewNamed,
+    Exists,
+    Today,
+    ThisWeek,
+    Older,
+    OlderHidden,
+    OlderShown,
+    HintsPick,
+    HintsPickShort,
+    Hints,
+    HintsShort,
+    SpokenKeysPick,
+    SpokenKeys,
+    HintsTags,
+    SpokenKeysTags,
+    NoTags,
+    DeletePending,
+    SpokenDeletePending,
+    StatusLabel,
+    WarningLabel,
+    ErrorLabel,
+    Selected,
+    ConfirmToken,
+    NeedsTerminal,
+    NewNamePrompt,
+    Created,
+    Archiving,
+    Archived,
+    ArchiveCancelled,
+    ArchiveTitle,
+    ArchiveAsk,
+    Deleted,
+    DeleteCancelled,
+    DeleteTitle,
+    DeleteManyTitle,
+    DeleteAsk,
+    DeleteManyAsk,
+    DeleteFiles,
+    DeleteSize,
+    DeleteMore,
+    DeleteSkipping,
+    DeletedMany,
+    DeletedSome,
+    NameTaken,
+    NameTakenTitle,
+    NameTakenAsk,
+    NameTakenChoice,
+    IoError,
+    CannotWrite,
+    ArchiveFailed,
+    ClonesFailed,
+    ReadOnlyHint,
+    NoSpaceHint,
+    PermissionDeniedHint,
+}
+
+impl Msg {
+    #[cfg(test)]
+    pub(crate) const ALL: [Msg; 66] = [
+        Msg::TitlePick,
+        Msg::TitleShort,
+        Msg::Title,
+        Msg::SpokenTitlePick,
+        Msg::SpokenTitle,
+        Msg::Search,
+        Msg::Marked,
+        Msg::NoMatches,
+        Msg::NoMatchesCreates,
+        Msg::SpokenNoMatchesCreates,
+        Msg::DidYouMean,
+        Msg::CreateNew,
+        Msg::CreateNewNamed,
+        Msg::Exists,
+        Msg::Today,
+        Msg::ThisWeek,
+        Msg::Older,
+        Msg::OlderHidden,
+        Msg::OlderShown,
+        Msg::HintsPick,
+        Msg::HintsPickShort,
+        Msg::Hints,
+        Msg::HintsShort,
+        Msg::SpokenKeysPick,
+        Msg::SpokenKeys,
+        Msg::HintsTags,
+        Msg::SpokenKeysTags,
+        Msg::NoTags,
+        Msg::DeletePending,
+        Msg::SpokenDeletePending,
+        Msg::StatusLabel,
+        Msg::WarningLabel,
+        Msg::ErrorLabel,
+        Msg::Selected,
+        Msg::ConfirmToken,
+        Msg::NeedsTerminal,
+        Msg::NewNamePrompt,
+        Msg::Created,
+        Msg::Archiving,
+        Msg::Archived,
+        Msg::ArchiveCancelled,
+        Msg::ArchiveTitle,
+        Msg::ArchiveAsk,
+        Msg::Deleted,
+        Msg::DeleteCancelled,
+        Msg::DeleteTitle,
+        Msg::DeleteManyTitle,
+        Msg::DeleteAsk,
+        Msg::DeleteManyAsk,
+        Msg::DeleteFiles,
+        Msg::DeleteSize,
+        Msg::DeleteMore,
+        Msg::DeleteSkipping,
+        Msg::DeletedMany,
+        Msg::DeletedSome,
+        Msg::NameTaken,
+        Msg::NameTakenTitle,
+        Msg::NameTakenAsk,
+        Msg::NameTakenChoice,
+        Msg::IoError,
+        Msg::CannotWrite,
+        Msg::ArchiveFailed,
+        Msg::ClonesFailed,
+        Msg::ReadOnlyHint,
+        Msg::NoSpaceHint,
+        Msg::PermissionDeniedHint,
+    ];
+
+    /// The text of `self` in `locale`.
+    pub(crate) fn text(self, locale: Locale) -> &'static str {
+        let [en, de] = match self {
+            Msg::TitlePick => ["🔎 Pick", "🔎 Auswahl"],
+            Msg::TitleShort => ["📁 Try", "📁 Try"],
+            Msg::Title => ["📁 Try Directory Selection", "📁 Try-Verzeichnisauswahl"],
+            Msg::SpokenTitlePick => ["Pick", "Auswahl"],
+            Msg::SpokenTitle => ["Try directory selection", "Try-Verzeichnisauswahl"],
+            Msg::Search => ["Search: {query}", "Suche: {query}"],
+            Msg::Marked => ["{count} marked", "{count} markiert"],
+            Msg::NoMatches => ["No matches", "Keine Treffer"],
+            Msg::NoMatchesCreates => [
+                "No matches — Enter creates '{name}'",
+                "Keine Treffer — Enter legt '{name}' an",
+            ],
+            Msg::SpokenNoMatchesCreates => [
+                "No matches. Enter creates {name}",
+                "Keine Treffer. Enter legt {name} an",
+            ],
+            Msg::DidYouMean => ["Did you mean: {names}", "Meinten Sie: {names}"],
+            Msg::CreateNew => ["Create new", "Neu anlegen"],
+            Msg::CreateNewNamed => ["Create new: {name}", "Neu anlegen: {name}"],
+            Msg::Exists => ["(exists: {name})", "(existiert: {name})"],
+            Msg::Today => ["Today", "Heute"],
+            Msg::ThisWeek => ["This week", "Diese Woche"],
+            Msg::Older => ["Older", "Älter"],
+            Msg::OlderHidden => [
+                "Older · {count} hidden — Ctrl-E to show",
+                "Älter · {count} ausgeblendet — Strg-E zeigt sie",
+            ],
+            Msg::OlderShown => ["Older — Ctrl-E to hide", "Älter — Strg-E blendet aus"],
+            Msg::HintsPick => [
+                "↑↓: Navigate  Tab: Complete  Enter: Select  ESC: Cancel",
+                "↑↓: Bewegen  Tab: Ergänzen  Enter: Auswählen  ESC: Abbrechen",
+            ],
+            Msg::HintsPickShort => ["↑↓ Tab Enter Esc", "↑↓ Tab Enter Esc"],
+            Msg::Hints => [
+                "↑↓: Navigate  Tab: Complete  Enter: Select  Alt-N: New  Ctrl-Space: Mark  Ctrl-A: Archive  Ctrl-D: Delete  ESC: Cancel",
+                "↑↓: Bewegen  Tab: Ergänzen  Enter: Auswählen  Alt-N: Neu  Strg-Leertaste: Markieren  Strg-A: Archivieren  Strg-D: Löschen  ESC: Abbrechen",
+            ],
+            Msg::HintsShort => [
+                "↑↓ Tab Enter ^N new ^A archive ^D delete Esc",
+                "↑↓ Tab Enter ^N neu ^A archivieren ^D löschen Esc",
+            ],
+            Msg::SpokenKeysPick => [
+                "Keys: Up and Down move, Tab completes, Enter selects, Escape cancels",
+                "Tasten: Auf und Ab bewegen, Tab ergänzt, Enter wählt aus, Escape bricht ab",
+            ],
+            Msg::SpokenKeys => [
+                "Keys: Up and Down move, Tab completes, Enter selects, Control N creates, Control Space marks, Control A archives, Control D deletes, Escape cancels",
+                "Tasten: Auf und Ab bewegen, Tab ergänzt, Enter wählt aus, Steuerung N legt an, Steuerung Leertaste markiert, Steuerung A archiviert, Steuerung D löscht, Escape bricht ab",
+            ],
+            Msg::HintsTags => [
+                "↑↓: Move  Space: Toggle tag  Enter/Esc/Alt-T: Close",
+                "↑↓: Bewegen  Leertaste: Tag umschalten  Enter/Esc/Alt-T: Schließen",
+            ],
+            Msg::SpokenKeysTags => [
+                "Keys: Up and Down move, Space toggles the tag, Enter or Escape closes the tag list",
+                "Tasten: Auf und Ab bewegen, Leertaste schaltet den Tag um, Enter oder Escape schließt die Tag-Liste",
+            ],
+            Msg::NoTags => [
+                "No tags yet; add some with try tag",
+                "Noch keine Tags; vergeben mit try tag",
+            ],
+            Msg::DeletePending => [
+                "delete pending: press d to confirm; Esc to cancel",
+                "Löschen ausstehend: d bestätigt; Esc bricht ab",
+            ],
+            Msg::SpokenDeletePending => [
+                "Delete pending: press d to confirm, Escape to cancel",
+                "Löschen ausstehend: d bestätigt, Escape bricht ab",
+            ],
+            Msg::StatusLabel => ["Status", "Status"],
+            Msg::WarningLabel => ["Warning", "Warnung"],
+            Msg::ErrorLabel => ["Error", "Fehler"],
+            Msg::Selected => ["Selected: {row}", "Ausgewählt: {row}"],
+            Msg::ConfirmToken => [
+                "Type {token} to confirm: ",
+                "Zum Bestätigen {token} eingeben: ",
+            ],
+            Msg::NeedsTerminal => [
+                "try requires an interactive terminal",
+                "try benötigt ein interaktives Terminal",
+            ],
+            Msg::NewNamePrompt => ["Enter new try name", "Name des neuen Try eingeben"],
+            Msg::Created => ["Created: {name}", "Angelegt: {name}"],
+            Msg::Archiving => ["Archiving {name}", "Archiviere {name}"],
+            Msg::Archived => ["Archived: {name} → {dest}", "Archiviert: {name} → {dest}"],
+            Msg::ArchiveCancelled => ["Archive cancelled", "Archivieren abgebrochen"],
+            Msg::ArchiveTitle => ["Archive Directory", "Verzeichnis archivieren"],
+            Msg::ArchiveAsk => [
+                "Are you sure you want to archive and remove: {name}",
+                "Wirklich archivieren und entfernen: {name}",
+            ],
+            Msg::Deleted => ["Deleted: {name}", "Gelöscht: {name}"],
+            Msg::DeleteCancelled => ["Delete cancelled", "Löschen abgebrochen"],
+            Msg::DeleteTitle => ["Delete Directory", "Verzeichnis löschen"],
+            Msg::DeleteManyTitle => ["Delete Directories", "Verzeichnisse löschen"],
+            Msg::DeleteAsk => [
+                "Are you sure you want to delete: {name}",
+                "Wirklich löschen: {name}",
+            ],
+            Msg::DeleteManyAsk => [
+                "Are you sure you want to delete {count} tries:",
+                "Wirklich {count} Tries löschen:",
+            ],
+            Msg::DeleteFiles => ["  files: {count} files", "  Dateien: {count}"],
+            Msg::DeleteSize => ["  size: {size}", "  Größe: {size}"],
+            Msg::DeleteMore => ["  … and {count} more", "  … und {count} weitere"],
+            Msg::DeleteSkipping => [
+                "  skipping {count} protected",
+                "  {count} geschützte werden übersprungen",
+            ],
+            Msg::DeletedMany => ["Deleted {count} tries", "{count} Tries gelöscht"],
+            Msg::DeletedSome => [
+                "Deleted {deleted} of {total} tries; {failed} not deleted",
+                "{deleted} von {total} Tries gelöscht; {failed} nicht gelöscht",
+            ],
+            Msg::NameTaken => [
+                "Not created: {name} is taken by {existing}",
+                "Nicht angelegt: {name} ist durch {existing} belegt",
+            ],
+            Msg::NameTakenTitle => ["Name Taken", "Name belegt"],
+            Msg::NameTakenAsk => [
+                "{name} has the same name as {existing}",
+                "{name} heißt wie {existing}",
+            ],
+            Msg::NameTakenChoice => [
+                "[o]pen it, [n]ew anyway, or [s] {suffixed}? [o] ",
+                "[o] öffnen, [n] trotzdem neu oder [s] {suffixed}? [o] ",
+            ],
+            Msg::IoError => ["I/O error: {error}", "E/A-Fehler: {error}"],
+            Msg::CannotWrite => [
+                "Cannot write to {path}: {hint}",
+                "Schreiben nach {path} nicht möglich: {hint}",
+            ],
+            Msg::ArchiveFailed => [
+                "Archive failed: {error}",
+                "Archivieren fehlgeschlagen: {error}",
+            ],
+            Msg::ClonesFailed => [
+                "{failed} of {total} clones failed",
+                "{failed} von {total} Klonvorgängen fehlgeschlagen",
+            ],
+            Msg::ReadOnlyHint => [
+                "the filesystem is read-only; point TRY_PATH or --path at a writable directory",
+                "das Dateisystem ist schreibgeschützt; TRY_PATH oder --path auf ein beschreibbares Verzeichnis setzen",
+            ],
+            Msg::NoSpaceHint => [
+                "no space left on the device; free space or delete old tries",
+                "kein Platz mehr auf dem Gerät; Platz schaffen oder alte Tries löschen",
+            ],
+            Msg::PermissionDeniedHint => [
+                "permission denied; check the directory's owner and mode, or use --path",
+                "Zugriff verweigert; Besitzer und Rechte des Verzeichnisses prüfen oder --path verwenden",
+            ],
+        };
+        match locale {
+            Locale::En => en,
+            Locale::De => de,
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::copy::{CopyMode, copy_tree, move_tree};
+use crate::storage::{META_DIR, normalize_query_for_match};
+use crate::timestamps::Timestamps;
+use crate::tui::ProgressLine;
+use crate::util::{date_prefix, split_date_prefixed};
+
+const IMPORT_LOG: &str = "imports.tsv";
+
+/// How `try import-dir` brings a folder into the tries directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ImportMode {
+    Copy,
+    Move,
+    Link,
+}
+
+impl ImportMode {
+    fn label(self) -> &'static str {
+        match self {
+            ImportMode::Copy => "copy",
+            ImportMode::Move => "move",
+            ImportMode::Link => "link",
+        }
+    }
+}
+
+/// Try name for the folder at `src`: kept when it already has a date prefix, otherwise
+/// prefixed with the date the folder was created (its modification date where the platform
+/// does not record creation).
+pub(crate) fn import_name(src: &Path) -> io::Result<String> {
+    let name = src
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no folder name"))?;
+    if split_date_prefixed(&name).is_some() {
+        return Ok(name);
+    }
+    let meta = fs::metadata(src)?;
+    let born = Timestamps::from_metadata(&meta, &name)
+        .created
+        .ok_or_else(|| io::Error::other("the folder has no creation or modification time"))?;
+    Ok(format!(
+        "{}-{}",
+        date_prefix(born),
+        normalize_query_for_match(&name)
+    ))
+}
+
+/// Brings the directory `src` into `base_path` by copying, moving, or symlinking it, and
+/// records where it came from. Returns the new try's path. Fails without touching anything
+/// when the name is taken or `src` already lives in `base_path`.
+pub(crate) fn import_dir(base_path: &Path, src: &Path, mode: ImportMode) -> io::Result<PathBuf> {
+    let src = fs::canonicalize(src)?;
+    if !src.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "not a directory",
+        ));
+    }
+    if fs::canonicalize(base_path).is_ok_and(|base| src.starts_with(base)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "already inside the tries directory",
+        ));
+    }
+    let name = import_name(&src)?;
+    let dest = base_path.join(&name);
+    if fs::symlink_metadata(&dest).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{name} already exists"),
+        ));
+    }
+    fs::create_dir_all(base_path)?;
+    match mode {
+        ImportMode::Copy => drop(copy_tree(&src, &dest, CopyMode::Auto)?),
+        ImportMode::Move => {
+            let mut line = ProgressLine::new(format!("Moving {}", src.display()));
+            move_tree(&src, &dest, |done, total| line.update(done, total))?;
+        }
+        ImportMode::Link => symlink_dir(&src, &dest)?,
+    }
+    record_import(base_path, &name, mode, &src)?;
+    Ok(dest)
+}
+
+/// Where the try `name` was imported from, per the import log.
+pub(crate) fn imported_from(base_path: &Path, name: &str) -> Option<String> {
+    let log = fs::read_to_string(base_path.join(META_DIR).join(IMPORT_LOG)).ok()?;
+    log.lines().rev().find_map(|line| {
+        let mut fields = line.splitn(3, '\t');
+        (fields.next()? == name).then_some(())?;
+        let mode = fields.next()?;
+        Some(format!("{} ({mode})", fields.next()?))
+    })
+}
+
+fn record_import(base_path: &Path, name: &str, mode: ImportMode, src: &Path) -> io::Result<()> {
+    let dir = base_path.join(META_DIR);
+    fs::create_dir_all(&dir)?;
+    let mut log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(IMPORT_LOG))?;
+    writeln!(log, "{name}\t{}\t{}", mode.label(), src.display())
+}
+
+#[cfg(unix)]
+fn symlink_dir(src: &Path, dest: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(src, dest)
+}
+
+#[cfg(windows)]
+fn symlink_dir(src: &Path, dest: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_dir(src, dest)
+}
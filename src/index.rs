@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+/// Inverted index from the lowercase characters of each name to the entries containing them.
+/// A fuzzy match needs every query character somewhere in the name, so intersecting the
+/// postings of the query's characters yields every entry that can score, and matching skips
+/// the rest.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CharIndex {
+    postings: HashMap<char, Vec<usize>>,
+    len: usize,
+}
+
+impl CharIndex {
+    /// Indexes `names`; entry numbers are their positions.
+    pub(crate) fn build<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut index = CharIndex::default();
+        for (i, name) in names.into_iter().enumerate() {
+            let mut seen: Vec<char> = name.to_lowercase().chars().collect();
+            seen.sort_unstable();
+            seen.dedup();
+            for c in seen {
+                index.postings.entry(c).or_default().push(i);
+            }
+            index.len = i + 1;
+        }
+        index
+    }
+
+    /// Entries containing every character of `query` (case-insensitive), in ascending order;
+    /// every entry for an empty query.
+    pub(crate) fn candidates(&self, query: &str) -> Vec<usize> {
+        let mut chars: Vec<char> = query.to_lowercase().chars().collect();
+        chars.sort_unstable();
+        chars.dedup();
+        let mut lists = Vec::with_capacity(chars.len());
+        for c in &chars {
+            match self.postings.get(c) {
+                Some(list) => lists.push(list),
+                None => return Vec::new(),
+            }
+        }
+        // Start from the rarest character so the intersection shrinks fastest
+        lists.sort_by_key(|list| list.len());
+        let Some((first, rest)) = lists.split_first() else {
+            return (0..self.len).collect();
+        };
+        first
+            .iter()
+            .copied()
+            .filter(|i| rest.iter().all(|list| list.binary_search(i).is_ok()))
+            .collect()
+    }
+}
@@ -0,0 +1,267 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+// Bounds that keep a scan of a large try fast
+const MAX_FILES_SCANNED: usize = 2_000;
+const MAX_FILE_BYTES: u64 = 256 * 1_024;
+const MAX_TODO_TEXT: usize = 100;
+// Directories that hold dependencies or build output rather than the user's own notes
+pub(crate) const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "dist", "build"];
+const TODO_MARKERS: &[&str] = &["TODO", "FIXME"];
+// Activity timeline
+const MAX_COMMITS_READ: usize = 1_000;
+pub(crate) const ACTIVITY_WEEKS: usize = 8;
+const SECONDS_PER_DAY: u64 = 86_400;
+const DAYS_PER_WEEK: u64 = 7;
+const SPARK_CHARS: &[char] = &['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Compact prompt segment such as `parser-test · 3d ago · keep`: the try name without its
+/// date prefix, its age, and `keep` when it is protected.
+pub(crate) fn prompt_segment(name: &str, age: &str, protected: bool) -> String {
+    let plain = crate::util::split_date_prefixed(name).map_or(name, |(_, n)| n);
+    let mut parts = vec![plain, age];
+    if protected {
+        parts.push("keep");
+    }
+    parts.join(" · ")
+}
+
+/// A TODO/FIXME line found in a try.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TodoItem {
+    /// Path relative to the try root.
+    pub path: PathBuf,
+    /// 1-based line number.
+    pub line: usize,
+    /// Trimmed line text starting at the marker, shortened for display.
+    pub text: String,
+}
+
+/// Collects up to `limit` TODO/FIXME lines from text files under `root`, visiting files in
+/// name order. Hidden and dependency directories, large files, and binaries are skipped, and
+/// at most `MAX_FILES_SCANNED` files are read.
+pub(crate) fn scan_todos(root: &Path, limit: usize) -> Vec<TodoItem> {
+    let mut out = Vec::new();
+    let mut budget = MAX_FILES_SCANNED;
+    walk_todos(root, root, limit, &mut budget, &mut out);
+    out
+}
+
+/// Activity summary of a try from git commits and file modification times.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Activity {
+    /// Number of commits read (capped).
+    pub commits: usize,
+    /// Unix seconds of the newest commit.
+    pub last_commit: Option<u64>,
+    /// Unix seconds of the newest commit or file modification.
+    pub last_change: Option<u64>,
+    /// Day (`YYYY-MM-DD`, UTC) with the most commits and file changes, and that count.
+    pub busiest_day: Option<(String, usize)>,
+    /// Events per week, oldest first, ending with the current week.
+    pub weekly: Vec<usize>,
+}
+
+/// Gathers commit times and file mtimes under `root` (bounded like the TODO scan) and
+/// summarizes them relative to `now` (Unix seconds).
+pub(crate) fn collect_activity(root: &Path, now: u64) -> Activity {
+    let commits = crate::git::commit_times(root, MAX_COMMITS_READ);
+    let mut files = Vec::new();
+    let mut budget = MAX_FILES_SCANNED;
+    walk_mtimes(root, &mut budget, &mut files);
+    summarize_activity(&commits, &files, now)
+}
+
+/// Builds an [`Activity`] from commit and file timestamps (Unix seconds).
+pub(crate) fn summarize_activity(commits: &[u64], files: &[u64], now: u64) -> Activity {
+    let all = || commits.iter().chain(files.iter()).copied();
+    let mut per_day: std::collections::BTreeMap<u64, usize> = Default::default();
+    for t in all() {
+        *per_day.entry(t / SECONDS_PER_DAY).or_default() += 1;
+    }
+    // Earliest day wins ties so the result is stable
+    let busiest_day = per_day
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+        .map(|(&day, &count)| {
+            let (y, m, d) = crate::util::civil_from_days(day as i64);
+            (format!("{y:04}-{m:02}-{d:02}"), count)
+        });
+    let mut weekly = vec![0; ACTIVITY_WEEKS];
+    for t in all().filter(|&t| t <= now) {
+        let weeks_ago = ((now - t) / (SECONDS_PER_DAY * DAYS_PER_WEEK)) as usize;
+        if weeks_ago < ACTIVITY_WEEKS {
+            weekly[ACTIVITY_WEEKS - 1 - weeks_ago] += 1;
+        }
+    }
+    Activity {
+        commits: commits.len(),
+        last_commit: commits.iter().copied().max(),
+        last_change: all().max(),
+        busiest_day,
+        weekly,
+    }
+}
+
+/// Renders counts as a one-line bar chart scaled to the largest value.
+pub(crate) fn sparkline(counts: &[usize]) -> String {
+    let max = counts.iter().copied().max().unwrap_or(0);
+    counts
+        .iter()
+        .map(|&c| {
+            let level = (c * (SPARK_CHARS.len() - 1)).checked_div(max).unwrap_or(0);
+            SPARK_CHARS[level]
+        })
+        .collect()
+}
+
+/// Disk usage of one top-level entry of a try.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct EntrySize {
+    pub name: String,
+    pub is_dir: bool,
+    pub files: u64,
+    pub bytes: u64,
+    /// Matched by the try's `.gitignore`, i.e. build output or dependencies that can be
+    /// regenerated.
+    pub ignored: bool,
+}
+
+/// Sizes of the top-level entries of `root`, largest first, measured on `jobs` threads.
+pub(crate) fn size_breakdown(root: &Path, jobs: usize) -> Vec<EntrySize> {
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let Ok(rd) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let paths: Vec<PathBuf> = rd.flatten().map(|e| e.path()).collect();
+    let patterns = ignore_patterns(root);
+    let next = AtomicUsize::new(0);
+    let sizes = Mutex::new(Vec::with_capacity(paths.len()));
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, paths.len().max(1)) {
+            scope.spawn(|| {
+                while let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let name = path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    let is_dir = fs::symlink_metadata(path).is_ok_and(|m| m.is_dir());
+                    let (files, bytes) = crate::util::dir_usage(path);
+                    let ignored = is_ignored(&patterns, &name, is_dir);
+                    if let Ok(mut sizes) = sizes.lock() {
+                        sizes.push(EntrySize {
+                            name,
+                            is_dir,
+                            files,
+                            bytes,
+                            ignored,
+                        });
+                    }
+                }
+            });
+        }
+    });
+    let mut sizes = sizes.into_inner().unwrap_or_default();
+    sizes.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+    sizes
+}
+
+/// Patterns of the try's top-level `.gitignore` that can match a top-level entry: comments,
+/// negations, and patterns naming nested paths are skipped.
+fn ignore_patterns(root: &Path) -> Vec<String> {
+    let text = fs::read_to_string(root.join(".gitignore")).unwrap_or_default();
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with('!'))
+        .map(|l| l.trim_start_matches("**/").trim_start_matches('/'))
+        .filter(|l| !l.trim_end_matches('/').contains('/'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether `name` matches one of `patterns`; a pattern ending in `/` only matches
+/// directories.
+fn is_ignored(patterns: &[String], name: &str, is_dir: bool) -> bool {
+    patterns.iter().any(|p| match p.strip_suffix('/') {
+        Some(dir_only) => is_dir && crate::util::glob_match(dir_only, name),
+        None => crate::util::glob_match(p, name),
+    })
+}
+
+fn walk_mtimes(dir: &Path, budget: &mut usize, out: &mut Vec<u64>) {
+    let Ok(rd) = fs::read_dir(dir) else { return };
+    for e in rd.flatten() {
+        if *budget == 0 {
+            return;
+        }
+        let name = e.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let Ok(ft) = e.file_type() else { continue };
+        if ft.is_dir() {
+            if !SKIPPED_DIRS.contains(&name.as_str()) {
+                walk_mtimes(&e.path(), budget, out);
+            }
+        } else if ft.is_file() {
+            *budget -= 1;
+            let mtime = e.metadata().and_then(|m| m.modified());
+            if let Ok(secs) = mtime.map(|t| t.duration_since(UNIX_EPOCH)) {
+                out.extend(secs.ok().map(|d| d.as_secs()));
+            }
+        }
+    }
+}
+
+fn walk_todos(root: &Path, dir: &Path, limit: usize, budget: &mut usize, out: &mut Vec<TodoItem>) {
+    let Ok(rd) = fs::read_dir(dir) else { return };
+    let mut entries: Vec<_> = rd.flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+    for e in entries {
+        if out.len() >= limit || *budget == 0 {
+            return;
+        }
+        let name = e.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let Ok(ft) = e.file_type() else { continue };
+        let path = e.path();
+        if ft.is_dir() {
+            if !SKIPPED_DIRS.contains(&name.as_str()) {
+                walk_todos(root, &path, limit, budget, out);
+            }
+        } else if ft.is_file() {
+            *budget -= 1;
+            scan_file(root, &path, limit, out);
+        }
+    }
+}
+
+fn scan_file(root: &Path, path: &Path, limit: usize, out: &mut Vec<TodoItem>) {
+    let Ok(file) = fs::File::open(path) else {
+        return;
+    };
+    let mut buf = Vec::new();
+    if file.take(MAX_FILE_BYTES).read_to_end(&mut buf).is_err() || buf.contains(&0) {
+        return; // unreadable or binary
+    }
+    let text = String::from_utf8_lossy(&buf);
+    for (idx, line) in text.lines().enumerate() {
+        if out.len() >= limit {
+            return;
+        }
+        let Some(pos) = TODO_MARKERS.iter().filter_map(|m| line.find(m)).min() else {
+            continue;
+        };
+        let found: String = line[pos..].trim().chars().take(MAX_TODO_TEXT).collect();
+        out.push(TodoItem {
+            path: path.strip_prefix(root).unwrap_or(path).to_path_buf(),
+            line: idx + 1,
+            text: found,
+        });
+    }
+}
@@ -0,0 +1,167 @@
+use clap::{Arg, Command};
+
+/// Manual pages (section 1) for `cmd` and every visible subcommand, nested ones included,
+/// as `(file name, roff source)`: `try.1`, `try-list.1`, `try-trash-prune.1`, ...
+pub(crate) fn pages(cmd: &Command) -> Vec<(String, String)> {
+    let mut cmd = cmd.clone();
+    cmd.build();
+    let mut out = Vec::new();
+    collect(&cmd, &[], &mut out);
+    out
+}
+
+fn collect(cmd: &Command, parents: &[&str], out: &mut Vec<(String, String)>) {
+    let mut path = parents.to_vec();
+    path.push(cmd.get_name());
+    out.push((format!("{}.1", path.join("-")), page(cmd, &path)));
+    for sub in cmd.get_subcommands().filter(|s| !s.is_hide_set()) {
+        collect(sub, &path, out);
+    }
+}
+
+/// The page for `cmd`, reached from the top-level binary through `path`.
+fn page(cmd: &Command, path: &[&str]) -> String {
+    let root = path[0];
+    let title = path.join("-");
+    let version = cmd
+        .get_version()
+        .map_or_else(|| env!("CARGO_PKG_VERSION").to_string(), str::to_string);
+    let about = cmd.get_about().map(|s| s.to_string()).unwrap_or_default();
+    let mut out = format!(
+        ".TH {} 1 \"\" \"{root} {version}\" \"User Commands\"\n",
+        escape(&title.to_uppercase())
+    );
+
+    out.push_str(".SH NAME\n");
+    out.push_str(&format!("{} \\- {}\n", escape(&title), escape(&about)));
+
+    out.push_str(".SH SYNOPSIS\n");
+    let usage = cmd.clone().render_usage().to_string();
+    let usage = usage.strip_prefix("Usage: ").unwrap_or(&usage);
+    let command = path.join(" ");
+    let rest = usage.strip_prefix(command.as_str()).unwrap_or(usage);
+    out.push_str(&format!("\\fB{}\\fR{}\n", escape(&command), escape(rest)));
+
+    out.push_str(".SH DESCRIPTION\n");
+    let description = cmd.get_long_about().map_or(about, |s| s.to_string());
+    out.push_str(&paragraphs(&description));
+    let aliases: Vec<&str> = cmd.get_visible_aliases().collect();
+    if !aliases.is_empty() {
+        out.push_str(&format!(".PP\nAliases: {}\n", escape(&aliases.join(", "))));
+    }
+
+    let args: Vec<&Arg> = cmd
+        .get_arguments()
+        .filter(|a| !a.is_hide_set() && !a.is_positional())
+        .collect();
+    if !args.is_empty() {
+        out.push_str(".SH OPTIONS\n");
+        for arg in args {
+            out.push_str(&option(arg));
+        }
+    }
+
+    let subs: Vec<&Command> = cmd.get_subcommands().filter(|s| !s.is_hide_set()).collect();
+    if !subs.is_empty() {
+        out.push_str(".SH COMMANDS\n");
+        for sub in &subs {
+            let about = sub.get_about().map(|s| s.to_string()).unwrap_or_default();
+            out.push_str(&format!(
+                ".TP\n\\fB{}\\-{}\\fR(1)\n{}\n",
+                escape(&title),
+                escape(sub.get_name()),
+                escape(&about)
+            ));
+        }
+    }
+
+    out.push_str(".SH SEE ALSO\n");
+    if path.len() > 1 {
+        out.push_str(&format!(
+            "\\fB{}\\fR(1)\n",
+            escape(&path[..path.len() - 1].join("-"))
+        ));
+    } else {
+        out.push_str(&format!("\\fB{root} man\\fR writes these pages.\n"));
+    }
+    out
+}
+
+/// `.TP` entry: the flag forms on the tag line, then the long help and possible values.
+fn option(arg: &Arg) -> String {
+    let mut forms = Vec::new();
+    if let Some(s) = arg.get_short() {
+        forms.push(format!("\\fB\\-{}\\fR", escape(&s.to_string())));
+    }
+    if let Some(l) = arg.get_long() {
+        forms.push(format!("\\fB\\-\\-{}\\fR", escape(l)));
+    }
+    for s in arg.get_visible_short_aliases().unwrap_or_default() {
+        forms.push(format!("\\fB\\-{}\\fR", escape(&s.to_string())));
+    }
+    for l in arg.get_visible_aliases().unwrap_or_default() {
+        forms.push(format!("\\fB\\-\\-{}\\fR", escape(l)));
+    }
+    let mut tag = forms.join(", ");
+    if arg.get_action().takes_values()
+        && let Some(names) = arg.get_value_names()
+    {
+        for name in names {
+            tag.push_str(&format!(" \\fI{}\\fR", escape(name)));
+        }
+    }
+    let help = arg
+        .get_long_help()
+        .or(arg.get_help())
+        .map(|s| s.to_string())
+        .unwrap_or_default();
+    let mut body = paragraphs(&help);
+    if !arg.get_action().takes_values() {
+        return format!(".TP\n{tag}\n{body}");
+    }
+    let values: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|v| !v.is_hide_set())
+        .map(|v| v.get_name().to_string())
+        .collect();
+    if !values.is_empty() {
+        body.push_str(&format!(
+            "Possible values: {}.\n",
+            escape(&values.join(", "))
+        ));
+    }
+    if let Some(default) = arg.get_default_values().first() {
+        body.push_str(&format!(
+            "Default: {}.\n",
+            escape(&default.to_string_lossy())
+        ));
+    }
+    format!(".TP\n{tag}\n{body}")
+}
+
+/// Help text as roff paragraphs: blank lines separate `.PP` blocks.
+fn paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| format!("{}\n", escape(p)))
+        .collect::<Vec<_>>()
+        .join(".PP\n")
+}
+
+/// Escapes text for roff: backslashes and hyphens, and a leading `.` or `'` that would
+/// otherwise start a request.
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            if line.starts_with(['.', '\'']) {
+                format!("\\&{line}")
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
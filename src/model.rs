@@ -1,6 +1,9 @@
+use std::ops::Index;
 use std::path::PathBuf;
 use std::time::SystemTime;
 
+use crate::git::GitStatus;
+
 #[derive(Clone, Debug)]
 pub struct TryDir {
     pub basename: String,
@@ -9,4 +12,44 @@ pub struct TryDir {
     pub mtime: Option<SystemTime>,
     pub score: f64,
     pub size: Option<u64>,
+    /// Git status when the try is a repository; collected lazily like `size`.
+    pub git: Option<GitStatus>,
+    /// Left behind by a `try clone` that did not finish.
+    pub pending_clone: bool,
+    /// Decayed number of visits from `try cd`; see `visits::Visits`.
+    pub visits: f64,
+    /// Upstream commits not yet pulled, as of the last `try refresh`; see
+    /// `refresh::RefreshCache`.
+    pub behind: Option<usize>,
+    /// When the try expires, for ephemeral tries from `try new --ttl`; see
+    /// `expiry::Expiries`.
+    pub expires: Option<SystemTime>,
+    /// Tags set with `try tag`, sorted; see `tags::Tags`.
+    pub tags: Vec<String>,
+}
+
+/// Entries matching the current query in display order, borrowed as indices into the full
+/// list so filtering never clones `TryDir`s.
+#[derive(Clone, Copy, Debug)]
+pub struct MatchList<'a> {
+    pub all: &'a [TryDir],
+    pub order: &'a [usize],
+}
+
+impl<'a> MatchList<'a> {
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+impl Index<usize> for MatchList<'_> {
+    type Output = TryDir;
+
+    fn index(&self, pos: usize) -> &TryDir {
+        &self.all[self.order[pos]]
+    }
 }
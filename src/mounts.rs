@@ -0,0 +1,150 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static ONE_FILE_SYSTEM: OnceLock<bool> = OnceLock::new();
+
+/// Sets the process-wide `--one-file-system` flag; only the first call takes effect.
+pub(crate) fn set_one_file_system(enabled: bool) {
+    let _ = ONE_FILE_SYSTEM.set(enabled);
+}
+
+/// Whether size walks and deletions stay on the filesystem of the try they start in.
+pub(crate) fn one_file_system() -> bool {
+    ONE_FILE_SYSTEM.get().copied().unwrap_or(false)
+}
+
+/// Which directories below a root a walk may enter. Directories on another device are left
+/// out when they hold a virtual filesystem (`/proc`, `sysfs`, ...) or, with
+/// `--one-file-system`, always.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Boundary {
+    root_dev: Option<u64>,
+    one_file_system: bool,
+}
+
+impl Boundary {
+    /// Boundary for a walk starting at `root`, following `--one-file-system`.
+    pub(crate) fn at(root: &Path) -> Self {
+        Self::with(root, one_file_system())
+    }
+
+    pub(crate) fn with(root: &Path, one_file_system: bool) -> Self {
+        Self {
+            root_dev: fs::symlink_metadata(root).ok().as_ref().and_then(device),
+            one_file_system,
+        }
+    }
+
+    /// Whether the walk may descend into `dir`, whose metadata is `md`.
+    pub(crate) fn allows(&self, dir: &Path, md: &fs::Metadata) -> bool {
+        let crosses = match (self.root_dev, device(md)) {
+            (Some(root), Some(dev)) => root != dev,
+            _ => false,
+        };
+        !crosses || (!self.one_file_system && !is_virtual_fs(dir))
+    }
+}
+
+/// Deletes `path` and everything below it, refusing up front (before anything is removed)
+/// when a directory inside lies beyond the boundary, so mounted content is never deleted.
+/// Symlinks, including ones to directories, are removed as links and never followed.
+pub(crate) fn remove_tree(path: &Path) -> io::Result<()> {
+    let is_link = fs::symlink_metadata(path)?.file_type().is_symlink();
+    if !is_link && let Some(mount) = blocked_dir(path, &Boundary::at(path)) {
+        return Err(io::Error::other(format!(
+            "{} is a mount point on another filesystem; unmount it first",
+            mount.display()
+        )));
+    }
+    remove_entry(path)
+}
+
+fn remove_entry(path: &Path) -> io::Result<()> {
+    let file_type = fs::symlink_metadata(path)?.file_type();
+    if file_type.is_symlink() {
+        remove_link(path)
+    } else if file_type.is_dir() {
+        for e in fs::read_dir(path)? {
+            remove_entry(&e?.path())?;
+        }
+        fs::remove_dir(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Windows directory symlinks and junctions are removed like empty directories.
+fn remove_link(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if cfg!(windows) => fs::remove_dir(path).map_err(|_| e),
+        result => result,
+    }
+}
+
+fn blocked_dir(dir: &Path, boundary: &Boundary) -> Option<PathBuf> {
+    for e in fs::read_dir(dir).ok()?.flatten() {
+        if !e.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        let path = e.path();
+        let Ok(md) = e.metadata() else { continue };
+        if !boundary.allows(&path, &md) {
+            return Some(path);
+        }
+        if let Some(found) = blocked_dir(&path, boundary) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+#[cfg(unix)]
+fn device(md: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(md.dev())
+}
+
+#[cfg(not(unix))]
+fn device(_md: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Whether `dir` is on a kernel-provided filesystem such as procfs or sysfs, whose sizes are
+/// meaningless and whose files must never be deleted.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn is_virtual_fs(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    // Magic numbers from linux/magic.h
+    const VIRTUAL_MAGIC: &[u32] = &[
+        0x9fa0,      // proc
+        0x6265_6572, // sysfs
+        0x1cd1,      // devpts
+        0x0027_e0eb, // cgroup
+        0x6367_7270, // cgroup2
+        0x6462_6720, // debugfs
+        0x7472_6163, // tracefs
+        0x7363_6673, // securityfs
+        0x6165_676c, // pstore
+        0xcafe_4a11, // bpf
+        0x6573_5543, // fusectl
+        0x6265_6570, // configfs
+    ];
+    let Ok(path) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat = std::mem::MaybeUninit::<libc::statfs>::uninit();
+    // SAFETY: `path` is NUL-terminated and `stat` is large enough for the kernel to fill.
+    if unsafe { libc::statfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return false;
+    }
+    // SAFETY: statfs succeeded, so the struct is initialized.
+    let kind = unsafe { stat.assume_init() }.f_type as u32;
+    VIRTUAL_MAGIC.contains(&kind)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn is_virtual_fs(_dir: &Path) -> bool {
+    false
+}
@@ -0,0 +1,88 @@
+use std::io;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::shell::Arg;
+
+static AFTER_SECS: OnceLock<Option<u64>> = OnceLock::new();
+
+/// Sets the process-wide `notify.after_secs`; only the first call takes effect.
+pub(crate) fn set_after_secs(secs: Option<u64>) {
+    let _ = AFTER_SECS.set(secs);
+}
+
+/// Seconds an operation must take before its completion is announced; `None` (the default)
+/// disables notifications.
+pub(crate) fn after_secs() -> Option<u64> {
+    AFTER_SECS.get().copied().flatten()
+}
+
+/// Whether an operation that took `elapsed` deserves a notification under `after_secs`.
+pub(crate) fn is_slow(after_secs: Option<u64>, elapsed: Duration) -> bool {
+    after_secs.is_some_and(|secs| elapsed >= Duration::from_secs(secs))
+}
+
+/// Announces `message` on the desktop when the operation begun at `started` ran longer than
+/// `notify.after_secs`. A missing notifier or notification daemon is ignored.
+pub(crate) fn notify_if_slow(started: Instant, message: &str) {
+    if is_slow(after_secs(), started.elapsed()) {
+        let _ = send(message);
+    }
+}
+
+/// Shell step announcing `message` once the preceding steps finish, for work the shell runs
+/// after try exits (clones). It calls back into `try notify` with the start time; `None` when
+/// notifications are off.
+pub(crate) fn shell_step(message: &str) -> Option<Vec<Arg>> {
+    let after = after_secs()?;
+    let exe = std::env::current_exe().ok()?;
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    Some(vec![
+        Arg::path(exe),
+        Arg::bare("notify"),
+        Arg::bare("--started"),
+        Arg::bare(started.to_string()),
+        Arg::bare("--after"),
+        Arg::bare(after.to_string()),
+        Arg::lit(message),
+    ])
+}
+
+/// `try notify`: announces `message` when at least `after` seconds passed since `started`
+/// (Unix seconds).
+pub(crate) fn run_notify(started: u64, after: u64, message: &str) {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH + Duration::from_secs(started))
+        .unwrap_or_default();
+    if is_slow(Some(after), elapsed) {
+        let _ = send(message);
+    }
+}
+
+fn send(message: &str) -> io::Result<()> {
+    notification_command(message)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|_| ())
+}
+
+/// The platform notifier: `osascript` on macOS, `notify-send` elsewhere.
+pub(crate) fn notification_command(message: &str) -> Command {
+    if cfg!(target_os = "macos") {
+        let quoted = message.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut cmd = Command::new("osascript");
+        cmd.arg("-e").arg(format!(
+            "display notification \"{quoted}\" with title \"try\""
+        ));
+        cmd
+    } else {
+        let mut cmd = Command::new("notify-send");
+        cmd.args(["--app-name=try", "try", message]);
+        cmd
+    }
+}
@@ -0,0 +1,231 @@
+use std::env;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use crossterm::style::{Attribute, Color, Stylize};
+use crossterm::tty::IsTty;
+
+use crate::tui::display_width;
+
+/// When to emit ANSI styling, chosen with the global `--color` flag.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ColorChoice {
+    /// Color when writing to a terminal, unless `NO_COLOR` or `CLICOLOR=0` is set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+static COLOR_CHOICE: OnceLock<ColorChoice> = OnceLock::new();
+static QUIET: OnceLock<bool> = OnceLock::new();
+static NO_PAGER: OnceLock<bool> = OnceLock::new();
+static ACCESSIBLE: OnceLock<bool> = OnceLock::new();
+static NO_CLEAR: OnceLock<bool> = OnceLock::new();
+
+/// Sets the process-wide color choice; only the first call takes effect.
+pub(crate) fn set_color_choice(choice: ColorChoice) {
+    let _ = COLOR_CHOICE.set(choice);
+}
+
+/// Sets the process-wide `--quiet` flag; only the first call takes effect.
+pub(crate) fn set_quiet(quiet: bool) {
+    let _ = QUIET.set(quiet);
+}
+
+/// Sets the process-wide `--no-pager` flag; only the first call takes effect.
+pub(crate) fn set_no_pager(no_pager: bool) {
+    let _ = NO_PAGER.set(no_pager);
+}
+
+/// Sets the process-wide screen-reader mode (`ui.accessible`); only the first call takes
+/// effect.
+pub(crate) fn set_accessible(accessible: bool) {
+    let _ = ACCESSIBLE.set(accessible);
+}
+
+/// Whether the selector should draw for screen readers; see [`crate::tui::TermCaps`].
+pub(crate) fn accessible() -> bool {
+    ACCESSIBLE.get().copied().unwrap_or(false)
+}
+
+/// Sets the process-wide no-clear rendering mode (`ui.no_clear`, or `NO_FLICKER` in the
+/// environment); only the first call takes effect.
+pub(crate) fn set_no_clear(no_clear: bool) {
+    let _ = NO_CLEAR.set(no_clear);
+}
+
+/// Whether the selector should draw over its previous frame instead of clearing the screen.
+pub(crate) fn no_clear() -> bool {
+    NO_CLEAR.get().copied().unwrap_or(false)
+}
+
+/// Whether informational and warning messages on stderr should be suppressed. Errors,
+/// explicitly requested reports, and stdout are unaffected.
+pub(crate) fn quiet() -> bool {
+    QUIET.get().copied().unwrap_or(false)
+}
+
+/// Whether output to a stream that is (`is_tty`) or is not a terminal should be styled.
+pub(crate) fn colors_enabled(is_tty: bool) -> bool {
+    let choice = COLOR_CHOICE.get().copied().unwrap_or_default();
+    should_color(choice, is_tty, |key| std::env::var(key).ok())
+}
+
+/// Color decision from the choice, the stream, and the `NO_COLOR` / `CLICOLOR` /
+/// `CLICOLOR_FORCE` conventions (looked up through `env`).
+pub(crate) fn should_color(
+    choice: ColorChoice,
+    is_tty: bool,
+    env: impl Fn(&str) -> Option<String>,
+) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            if !is_tty || env("NO_COLOR").is_some() {
+                return false;
+            }
+            if env("CLICOLOR_FORCE").is_some_and(|v| v != "0") {
+                return true;
+            }
+            env("CLICOLOR").is_none_or(|v| v != "0")
+        }
+    }
+}
+
+/// The pager from `$PAGER` split into program and arguments, `less` when unset; `None` when
+/// it is empty or `cat`, which ask for no paging.
+pub(crate) fn pager_command(env: impl Fn(&str) -> Option<String>) -> Option<Vec<String>> {
+    let Some(pager) = env("PAGER") else {
+        return Some(vec!["less".to_string()]);
+    };
+    let parts: Vec<String> = pager.split_whitespace().map(str::to_string).collect();
+    match parts.first().map(String::as_str) {
+        None | Some("cat") => None,
+        Some(_) => Some(parts),
+    }
+}
+
+/// Writes `text` to stdout, through the pager when stdout is a terminal that `text` does not
+/// fit on, like git. `--no-pager` always writes directly, and so does a pager that fails to
+/// start. `LESS` defaults to `FRX`: quit if one screen, keep colors, leave the text on exit.
+pub(crate) fn page(text: &str) -> io::Result<()> {
+    let stdout = io::stdout();
+    let rows = crossterm::terminal::size().map_or(usize::MAX, |(_, h)| usize::from(h));
+    let long = stdout.is_tty() && text.lines().count() >= rows;
+    let pager = long && !NO_PAGER.get().copied().unwrap_or(false);
+    if let Some(cmd) = pager
+        .then(|| pager_command(|key| env::var(key).ok()))
+        .flatten()
+    {
+        let mut command = Command::new(&cmd[0]);
+        command.args(&cmd[1..]).stdin(Stdio::piped());
+        if env::var_os("LESS").is_none() {
+            command.env("LESS", "FRX");
+        }
+        if let Ok(mut child) = command.spawn() {
+            if let Some(mut stdin) = child.stdin.take() {
+                // Quitting the pager early closes the pipe; that is not an error
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            child.wait()?;
+            return Ok(());
+        }
+    }
+    stdout.lock().write_all(text.as_bytes())
+}
+
+/// Shared formatter for the summaries and tables printed by non-interactive commands, so
+/// they look alike and honor `--color`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Printer {
+    color: bool,
+}
+
+impl Printer {
+    /// Printer for stdout, styled according to `--color` and whether stdout is a terminal.
+    pub(crate) fn stdout() -> Self {
+        Self::new(colors_enabled(std::io::stdout().is_tty()))
+    }
+
+    pub(crate) fn new(color: bool) -> Self {
+        Self { color }
+    }
+
+    /// Top line naming the subject, e.g. the try being described.
+    pub(crate) fn title(&self, text: &str) -> String {
+        self.paint(text, Attribute::Bold, Some(Color::Yellow))
+    }
+
+    /// Heading of a section, preceded by a blank line.
+    pub(crate) fn section(&self, text: &str) -> String {
+        format!(
+            "\n{}",
+            self.paint(&format!("{text}:"), Attribute::Bold, None)
+        )
+    }
+
+    /// Indented `label: value` lines with the values aligned.
+    pub(crate) fn fields(&self, rows: &[(&str, String)]) -> String {
+        let width = rows
+            .iter()
+            .map(|(l, _)| display_width(l))
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let mut out = Vec::with_capacity(rows.len());
+        for (label, value) in rows {
+            let label = format!("{label}:");
+            let pad = " ".repeat(width.saturating_sub(display_width(&label)));
+            let label = self.paint(&label, Attribute::Dim, None);
+            out.push(format!("  {label}{pad}{value}"));
+        }
+        out.join("\n")
+    }
+
+    /// Indented rows with columns aligned by display width; the first column is dimmed.
+    pub(crate) fn table(&self, rows: &[Vec<String>]) -> String {
+        let cols = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..cols)
+            .map(|c| {
+                rows.iter()
+                    .filter_map(|r| r.get(c))
+                    .map(|s| display_width(s))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let mut out = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut line = String::from(" ");
+            for (c, cell) in row.iter().enumerate() {
+                line.push(' ');
+                let text = if c == 0 {
+                    self.paint(cell, Attribute::Dim, None)
+                } else {
+                    cell.clone()
+                };
+                line.push_str(&text);
+                if c + 1 < row.len() {
+                    line.push_str(&" ".repeat(widths[c] - display_width(cell) + 1));
+                }
+            }
+            out.push(line);
+        }
+        out.join("\n")
+    }
+
+    fn paint(&self, text: &str, attr: Attribute, fg: Option<Color>) -> String {
+        if !self.color {
+            return text.to_string();
+        }
+        let styled = text.attribute(attr);
+        match fg {
+            Some(c) => styled.with(c).to_string(),
+            None => styled.to_string(),
+        }
+    }
+}
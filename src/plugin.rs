@@ -0,0 +1,54 @@
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+/// Prefix of external subcommands: `try foo` runs the first `try-foo` on PATH.
+pub(crate) const PLUGIN_PREFIX: &str = "try-";
+
+/// The executable for `try <name>` among the directories of `path_var` (a `PATH` value).
+/// Names containing a path separator never resolve, so `try ../x` cannot escape PATH.
+pub(crate) fn find_plugin(name: &OsStr, path_var: &OsStr) -> Option<PathBuf> {
+    let text = name.to_string_lossy();
+    if text.is_empty() || text.contains(['/', '\\']) {
+        return None;
+    }
+    let mut file = OsString::from(PLUGIN_PREFIX);
+    file.push(name);
+    std::env::split_paths(path_var)
+        .map(|dir| dir.join(&file))
+        .find(|p| is_executable(p))
+}
+
+/// Runs the plugin at `exe` with `args` and waits for it. The plugin's environment gets
+/// `TRY_BASE_PATH` (the tries directory in effect, also exported as `TRY_PATH` so nested
+/// `try` calls agree) and `TRY_BIN` (this executable, e.g. for `"$TRY_BIN" select`).
+/// `TRY_SELECTION`, set by the shell wrapper, passes through: a plugin writes a directory
+/// to that file to have the shell cd there afterwards.
+pub(crate) fn run_plugin(
+    exe: &Path,
+    args: &[OsString],
+    base_path: &Path,
+) -> io::Result<ExitStatus> {
+    let mut command = Command::new(exe);
+    command
+        .args(args)
+        .env("TRY_BASE_PATH", base_path)
+        .env("TRY_PATH", base_path);
+    if let Ok(bin) = std::env::current_exe() {
+        command.env("TRY_BIN", bin);
+    }
+    command.status()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
@@ -0,0 +1,45 @@
+use std::time::{Duration, Instant};
+
+/// Wall-clock timings of startup phases, reported by `--profile-startup`.
+#[derive(Clone, Debug)]
+pub(crate) struct StartupProfile {
+    start: Instant,
+    last: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl StartupProfile {
+    pub(crate) fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            last: now,
+            phases: Vec::new(),
+        }
+    }
+
+    /// Records `phase` as the time elapsed since the previous mark (or since start).
+    pub(crate) fn mark(&mut self, phase: &'static str) {
+        let now = Instant::now();
+        self.phases.push((phase, now - self.last));
+        self.last = now;
+    }
+
+    /// Recorded phases in order, paired with their durations.
+    pub(crate) fn phases(&self) -> &[(&'static str, Duration)] {
+        &self.phases
+    }
+
+    /// Multi-line report with one row per phase and a total.
+    pub(crate) fn report(&self) -> String {
+        let row = |phase: &str, d: Duration| {
+            format!("  {phase:<16}{:>9.2}ms\n", d.as_secs_f64() * 1_000.0)
+        };
+        let mut out = String::from("startup profile:\n");
+        for (phase, d) in &self.phases {
+            out.push_str(&row(phase, *d));
+        }
+        out.push_str(&row("total", self.last - self.start));
+        out
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::storage::META_DIR;
+use crate::util::{is_git_uri, shellexpand_home};
+
+/// Project links under `.try_meta`: `name<TAB>project` per line, where the project is a
+/// normalized repository URL or an absolute path.
+const PROJECTS_FILE: &str = "projects.tsv";
+
+/// What a try can be linked to: a local project directory, the repository it is a clone of,
+/// or both.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Project {
+    /// Root of the working tree (or the directory itself outside git).
+    pub root: Option<PathBuf>,
+    /// `origin` URL of that working tree, or the URL given, normalized by [`normalize_url`].
+    pub url: Option<String>,
+}
+
+impl Project {
+    /// Resolves `spec`: an existing directory stands for the repository it lies in (or itself
+    /// outside git), anything else that looks like a git URL for that repository. `None`
+    /// when it is neither.
+    pub(crate) fn resolve(spec: &str) -> Option<Self> {
+        let path = shellexpand_home(spec);
+        if path.is_dir() {
+            let dir = fs::canonicalize(&path).ok()?;
+            let root = crate::git::repo_root(&dir).unwrap_or(&dir).to_path_buf();
+            let url = crate::git::origin_url(&root).map(|u| normalize_url(&u));
+            return Some(Self {
+                root: Some(root),
+                url,
+            });
+        }
+        is_git_uri(spec).then(|| Self {
+            root: None,
+            url: Some(normalize_url(spec)),
+        })
+    }
+
+    /// Value recorded for a try linked to this project: the URL when there is one, so clones
+    /// elsewhere find the same tries, else the directory.
+    pub(crate) fn key(&self) -> String {
+        match (&self.url, &self.root) {
+            (Some(url), _) => url.clone(),
+            (None, Some(root)) => root.to_string_lossy().into_owned(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// Whether a try linked to `linked` belongs to this project.
+    pub(crate) fn matches(&self, linked: &str) -> bool {
+        self.url.as_deref() == Some(linked)
+            || self
+                .root
+                .as_deref()
+                .is_some_and(|root| root == Path::new(linked))
+    }
+}
+
+/// `host/path` of a git URL without scheme, user, or `.git`, so the HTTPS and SSH forms of
+/// one repository compare equal.
+pub(crate) fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest.to_string(),
+        // scp-like `git@host:owner/repo`
+        None => url.replacen(':', "/", 1),
+    };
+    let rest = rest.rsplit_once('@').map_or(rest.as_str(), |(_, r)| r);
+    let rest = rest.trim_end_matches('/');
+    rest.strip_suffix(".git").unwrap_or(rest).to_string()
+}
+
+/// Which project each try is linked to.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ProjectLinks {
+    path: PathBuf,
+    entries: HashMap<String, String>,
+}
+
+impl ProjectLinks {
+    /// Loads the links stored under `base_path`; a missing file or malformed lines are
+    /// treated as no links.
+    pub(crate) fn load(base_path: &Path) -> Self {
+        let path = base_path.join(META_DIR).join(PROJECTS_FILE);
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let entries = text
+            .lines()
+            .filter_map(|line| {
+                let (name, project) = line.split_once('\t')?;
+                Some((name.to_string(), project.to_string()))
+            })
+            .collect();
+        Self { path, entries }
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&str> {
+        self.entries.get(name).map(String::as_str)
+    }
+
+    pub(crate) fn set(&mut self, name: &str, project: &Project) {
+        self.entries.insert(name.to_string(), project.key());
+    }
+
+    /// Removes the link of `name`; whether there was one.
+    pub(crate) fn remove(&mut self, name: &str) -> bool {
+        self.entries.remove(name).is_some()
+    }
+
+    /// Names of the tries linked to `project`.
+    pub(crate) fn linked_to(&self, project: &Project) -> HashSet<String> {
+        self.entries
+            .iter()
+            .filter(|(_, linked)| project.matches(linked))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    pub(crate) fn save(&self) -> io::Result<()> {
+        let mut lines: Vec<String> = self
+            .entries
+            .iter()
+            .map(|(name, project)| format!("{name}\t{project}"))
+            .collect();
+        lines.sort();
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, lines.join("\n") + "\n")
+    }
+}
@@ -0,0 +1,237 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::i18n::{Msg, tr};
+use crate::model::TryDir;
+use crate::tags::normalize_tag;
+
+// Time constants
+const SECONDS_PER_DAY: u64 = 86_400;
+const DAYS_PER_WEEK: u64 = 7;
+const DAYS_PER_MONTH: u64 = 30;
+
+/// Token restricting the list to tries with uncommitted changes or unpushed commits.
+pub(crate) const DIRTY_TOKEN: &str = "@dirty";
+
+/// Time-range chips in the order Ctrl-T cycles through them.
+pub(crate) const TIME_CHIPS: &[&str] = &["@today", "@week", "@month"];
+
+/// A restriction parsed from a query token, applied before fuzzy scoring.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Filter {
+    /// Created at or after the given instant (`@today`, `@week`, `@month`).
+    CreatedSince(SystemTime),
+    /// Total size strictly above the given bytes (`>1G`).
+    LargerThan(u64),
+    /// Total size strictly below the given bytes (`<10M`).
+    SmallerThan(u64),
+    /// Git repository with uncommitted changes or unpushed commits (`@dirty`).
+    PendingWork,
+    /// Carries the given tag (`#infra`); see `tags::Tags`.
+    Tagged(String),
+}
+
+impl Filter {
+    /// Whether the filter needs directory sizes, which are otherwise computed lazily.
+    pub(crate) fn needs_size(&self) -> bool {
+        matches!(self, Filter::LargerThan(_) | Filter::SmallerThan(_))
+    }
+
+    /// Whether the filter needs git status, which is otherwise collected lazily.
+    pub(crate) fn needs_git(&self) -> bool {
+        matches!(self, Filter::PendingWork)
+    }
+
+    /// Whether `t` passes this filter. Entries without the required metadata are excluded.
+    pub(crate) fn matches(&self, t: &TryDir) -> bool {
+        match self {
+            Filter::CreatedSince(since) => t.ctime.or(t.mtime).is_some_and(|c| c >= *since),
+            Filter::LargerThan(bytes) => t.size.is_some_and(|s| s > *bytes),
+            Filter::SmallerThan(bytes) => t.size.is_some_and(|s| s < *bytes),
+            Filter::PendingWork => t.git.as_ref().is_some_and(|g| g.has_pending_work()),
+            Filter::Tagged(tag) => t.tags.contains(tag),
+        }
+    }
+}
+
+/// A search query split into its fuzzy text and filter tokens.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ParsedQuery {
+    /// Remaining free text used for fuzzy matching and new-entry names.
+    pub text: String,
+    pub filters: Vec<Filter>,
+    /// Raw filter tokens, kept so the query can be rebuilt around new text.
+    pub tokens: Vec<String>,
+}
+
+impl ParsedQuery {
+    /// Rebuilds a query string with `text` in place of the current free text.
+    pub(crate) fn with_text(&self, text: &str) -> String {
+        let mut parts: Vec<&str> = self.tokens.iter().map(String::as_str).collect();
+        if !text.is_empty() {
+            parts.push(text);
+        }
+        parts.join(" ")
+    }
+}
+
+/// Splits `input` into filter tokens and free text. Unrecognized tokens stay in the text.
+pub(crate) fn parse_query(input: &str) -> ParsedQuery {
+    parse_query_at(input, SystemTime::now())
+}
+
+pub(crate) fn parse_query_at(input: &str, now: SystemTime) -> ParsedQuery {
+    let mut out = ParsedQuery::default();
+    let mut text: Vec<&str> = Vec::new();
+    for token in input.split_whitespace() {
+        match parse_token(token, now) {
+            Some(filter) => {
+                out.filters.push(filter);
+                out.tokens.push(token.to_string());
+            }
+            None => text.push(token),
+        }
+    }
+    // Keep the user's inner spacing for the text part when there are no tokens.
+    out.text = if out.tokens.is_empty() {
+        input.to_string()
+    } else {
+        text.join(" ")
+    };
+    out
+}
+
+fn parse_token(token: &str, now: SystemTime) -> Option<Filter> {
+    if token == DIRTY_TOKEN {
+        return Some(Filter::PendingWork);
+    }
+    if let Some(range) = token.strip_prefix('@') {
+        let since = match range {
+            "today" => start_of_day(now)?,
+            "week" => week_ago(now),
+            "month" => now - Duration::from_secs(DAYS_PER_MONTH * SECONDS_PER_DAY),
+            _ => return None,
+        };
+        return Some(Filter::CreatedSince(since));
+    }
+    if let Some(size) = token.strip_prefix('>') {
+        return parse_size(size).map(Filter::LargerThan);
+    }
+    if let Some(size) = token.strip_prefix('<') {
+        return parse_size(size).map(Filter::SmallerThan);
+    }
+    if token.starts_with('#') {
+        return normalize_tag(token).map(Filter::Tagged);
+    }
+    None
+}
+
+/// Midnight (UTC) starting the day of `now`, the boundary used by `@today`.
+fn start_of_day(now: SystemTime) -> Option<SystemTime> {
+    let secs = now.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some(UNIX_EPOCH + Duration::from_secs(secs - secs % SECONDS_PER_DAY))
+}
+
+fn week_ago(now: SystemTime) -> SystemTime {
+    now - Duration::from_secs(DAYS_PER_WEEK * SECONDS_PER_DAY)
+}
+
+/// Section of the empty-query list an entry falls in, by its last modification. Uses the
+/// same boundaries as the `@today` and `@week` chips.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Recency {
+    Today,
+    ThisWeek,
+    Older,
+}
+
+impl Recency {
+    pub(crate) fn of(t: &TryDir, now: SystemTime) -> Self {
+        let Some(mtime) = t.mtime.or(t.ctime) else {
+            return Recency::Older;
+        };
+        if start_of_day(now).is_some_and(|midnight| mtime >= midnight) {
+            Recency::Today
+        } else if mtime >= week_ago(now) {
+            Recency::ThisWeek
+        } else {
+            Recency::Older
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        tr(match self {
+            Recency::Today => Msg::Today,
+            Recency::ThisWeek => Msg::ThisWeek,
+            Recency::Older => Msg::Older,
+        })
+    }
+}
+
+/// Parses sizes like `512`, `10K`, `1.5M`, or `2G` (binary units, case-insensitive).
+pub(crate) fn parse_size(s: &str) -> Option<u64> {
+    const BYTES_PER_KIB: f64 = 1_024.0;
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let value: f64 = num.parse().ok()?;
+    let exp = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" => 1,
+        "M" | "MB" => 2,
+        "G" | "GB" => 3,
+        "T" | "TB" => 4,
+        _ => return None,
+    };
+    Some((value * BYTES_PER_KIB.powi(exp)) as u64)
+}
+
+/// Parses durations like `30m`, `12h`, `7d`, or `2w` (case-insensitive); a bare number is
+/// days.
+pub(crate) fn parse_duration(s: &str) -> Option<Duration> {
+    const SECS_PER_MINUTE: u64 = 60;
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let value: u64 = num.parse().ok()?;
+    let minutes = match unit.to_ascii_lowercase().as_str() {
+        "m" | "min" => 1,
+        "h" => 60,
+        "" | "d" => 60 * 24,
+        "w" => 60 * 24 * 7,
+        _ => return None,
+    };
+    Some(Duration::from_secs(
+        value.checked_mul(minutes * SECS_PER_MINUTE)?,
+    ))
+}
+
+/// Cycles the time chip in `input`: none → @today → @week → @month → none.
+pub(crate) fn cycle_time_chip(input: &str) -> String {
+    let mut tokens: Vec<&str> = input.split_whitespace().collect();
+    let current = tokens.iter().position(|t| TIME_CHIPS.contains(t));
+    let next = match current {
+        None => Some(TIME_CHIPS[0]),
+        Some(i) => {
+            let idx = TIME_CHIPS.iter().position(|c| *c == tokens[i]).unwrap_or(0);
+            tokens.remove(i);
+            TIME_CHIPS.get(idx + 1).copied()
+        }
+    };
+    if let Some(chip) = next {
+        tokens.insert(0, chip);
+    }
+    tokens.join(" ")
+}
+
+/// Adds `token` to the front of `input`, or removes it if already present.
+pub(crate) fn toggle_token(input: &str, token: &str) -> String {
+    let mut tokens: Vec<&str> = input.split_whitespace().collect();
+    if let Some(i) = tokens.iter().position(|t| *t == token) {
+        tokens.remove(i);
+    } else {
+        tokens.insert(0, token);
+    }
+    tokens.join(" ")
+}
@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::model::TryDir;
+use crate::storage::META_DIR;
+
+/// Remote freshness under `.try_meta`: `name<TAB>checked (Unix seconds)<TAB>ahead<TAB>behind`
+/// per line, with `-` counts when the try has no upstream or its remote was unreachable.
+const REFRESH_FILE: &str = "git-refresh.tsv";
+/// Exists while a refresh runs, so background refreshes never overlap.
+const LOCK_FILE: &str = "git-refresh.lock";
+/// Default `refresh.ttl_secs`.
+pub(crate) const DEFAULT_TTL_SECS: u64 = 900;
+/// Default `refresh.jobs`.
+pub(crate) const DEFAULT_JOBS: usize = 2;
+// A lock this old was left behind by a refresh that died
+const STALE_LOCK: Duration = Duration::from_secs(3600);
+
+/// Last remote check of one try.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Entry {
+    /// Unix seconds of the check.
+    pub checked: u64,
+    /// Commits `(ahead, behind)` the upstream, or `None` when they could not be determined.
+    pub counts: Option<(usize, usize)>,
+}
+
+/// Cached ahead/behind counts from `try refresh`, read by the selector without touching git.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RefreshCache {
+    path: PathBuf,
+    entries: HashMap<String, Entry>,
+}
+
+impl RefreshCache {
+    /// Loads the cache stored under `base_path`; a missing file or malformed lines are
+    /// treated as never checked.
+    pub(crate) fn load(base_path: &Path) -> Self {
+        let path = base_path.join(META_DIR).join(REFRESH_FILE);
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let entries = text
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let (name, checked) = (fields.next()?, fields.next()?.parse().ok()?);
+                let counts = match (fields.next()?, fields.next()?) {
+                    ("-", _) | (_, "-") => None,
+                    (ahead, behind) => Some((ahead.parse().ok()?, behind.parse().ok()?)),
+                };
+                Some((name.to_string(), Entry { checked, counts }))
+            })
+            .collect();
+        Self { path, entries }
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<Entry> {
+        self.entries.get(name).copied()
+    }
+
+    /// Commits on the upstream of `name` that were not pulled at its last check.
+    pub(crate) fn behind(&self, name: &str) -> Option<usize> {
+        self.get(name)?.counts.map(|(_, behind)| behind)
+    }
+
+    /// Whether `name` was checked less than `ttl` before `now`.
+    pub(crate) fn is_fresh(&self, name: &str, now: SystemTime, ttl: Duration) -> bool {
+        self.get(name)
+            .is_some_and(|e| unix_secs(now).saturating_sub(e.checked) < ttl.as_secs())
+    }
+
+    pub(crate) fn insert(&mut self, name: &str, entry: Entry) {
+        self.entries.insert(name.to_string(), entry);
+    }
+
+    /// Writes the cache, keeping only the tries named in `keep`.
+    pub(crate) fn save(&mut self, keep: &HashSet<&str>) -> io::Result<()> {
+        self.entries.retain(|name, _| keep.contains(name.as_str()));
+        let mut lines: Vec<String> = self
+            .entries
+            .iter()
+            .map(|(name, e)| match e.counts {
+                Some((ahead, behind)) => format!("{name}\t{}\t{ahead}\t{behind}", e.checked),
+                None => format!("{name}\t{}\t-\t-", e.checked),
+            })
+            .collect();
+        lines.sort();
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, lines.join("\n") + "\n")
+    }
+}
+
+/// Outcome of one refresh run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Summary {
+    /// Tries whose remote was asked.
+    pub checked: usize,
+    /// Tries skipped because their last check is younger than the TTL.
+    pub fresh: usize,
+    /// Checked tries without an upstream or with an unreachable remote.
+    pub unknown: usize,
+}
+
+/// Asks the remote of every git-backed try in `tries` for new commits, at most `jobs` at a
+/// time, and caches the ahead/behind counts. Tries checked within `ttl` are skipped. Fails
+/// with [`io::ErrorKind::AlreadyExists`] while another refresh of `base_path` is running.
+pub(crate) fn refresh(
+    base_path: &Path,
+    tries: &[TryDir],
+    jobs: usize,
+    ttl: Duration,
+) -> io::Result<Summary> {
+    let _lock = Lock::acquire(base_path)?;
+    let now = SystemTime::now();
+    let mut cache = RefreshCache::load(base_path);
+    let repos: Vec<&TryDir> = tries
+        .iter()
+        .filter(|t| t.path.join(".git").exists())
+        .collect();
+    let due: Vec<&TryDir> = repos
+        .iter()
+        .copied()
+        .filter(|t| !cache.is_fresh(&t.basename, now, ttl))
+        .collect();
+
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(due.len()));
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, due.len().max(1)) {
+            scope.spawn(|| {
+                while let Some(t) = due.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let counts = crate::git::remote_counts(&t.path);
+                    if let Ok(mut results) = results.lock() {
+                        results.push((t.basename.as_str(), counts));
+                    }
+                }
+            });
+        }
+    });
+
+    let results = results.into_inner().unwrap_or_default();
+    let checked = unix_secs(SystemTime::now());
+    let unknown = results.iter().filter(|(_, c)| c.is_none()).count();
+    for &(name, counts) in &results {
+        cache.insert(name, Entry { checked, counts });
+    }
+    let keep: HashSet<&str> = repos.iter().map(|t| t.basename.as_str()).collect();
+    cache.save(&keep)?;
+    Ok(Summary {
+        checked: results.len(),
+        fresh: repos.len() - due.len(),
+        unknown,
+    })
+}
+
+/// Starts `try refresh` for `base_path` as a detached process with no terminal I/O and
+/// returns at once.
+pub(crate) fn spawn_background(base_path: &Path) -> io::Result<()> {
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .arg("--path")
+        .arg(base_path)
+        .args(["--quiet", "refresh"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(unix)]
+    {
+        // Own process group, so Ctrl-C in the terminal does not reach it
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command.spawn().map(drop)
+}
+
+/// Lock file held for the duration of a refresh; removed on drop.
+struct Lock(PathBuf);
+
+impl Lock {
+    fn acquire(base_path: &Path) -> io::Result<Self> {
+        let dir = base_path.join(META_DIR);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(LOCK_FILE);
+        let stale = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .is_ok_and(|at| at.elapsed().unwrap_or_default() > STALE_LOCK);
+        if stale {
+            let _ = fs::remove_file(&path);
+        }
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn unix_secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
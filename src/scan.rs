@@ -0,0 +1,67 @@
+use std::fs::{self, DirEntry};
+use std::path::{Path, PathBuf};
+
+/// Below this many candidate entries the stat calls run on the calling thread; spawning
+/// workers costs more than it saves.
+const PARALLEL_STAT_MIN: usize = 2048;
+const MAX_STAT_WORKERS: usize = 8;
+
+/// A directory found by [`scan_dirs`], with its metadata (the target's, for symlinks).
+pub(crate) struct ScannedDir {
+    pub name: String,
+    pub path: PathBuf,
+    pub meta: fs::Metadata,
+}
+
+/// Lists the directories in `root`, following symlinks so linked tries are included, and
+/// skipping names for which `skip` returns true.
+///
+/// Built for roots with tens of thousands of entries. The entry type comes from the
+/// directory listing itself (`d_type` from `getdents` on Linux, the find data on Windows),
+/// so plain files and skipped names are dropped without a stat call. The remaining entries
+/// are stat'ed relative to the open directory where the platform allows (Windows already
+/// has their times from the listing), in batches spread over a few threads once there are
+/// enough of them. Entries keep the order the listing returned.
+pub(crate) fn scan_dirs(root: &Path, skip: impl Fn(&str) -> bool) -> Vec<ScannedDir> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let candidates: Vec<DirEntry> = entries
+        .flatten()
+        // An unknown type (some filesystems leave `d_type` empty) is resolved by the stat
+        .filter(|e| !e.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|e| !skip(&e.file_name().to_string_lossy()))
+        .collect();
+    let workers = std::thread::available_parallelism()
+        .map_or(1, usize::from)
+        .min(MAX_STAT_WORKERS);
+    if candidates.len() < PARALLEL_STAT_MIN || workers < 2 {
+        return candidates.iter().filter_map(stat_dir).collect();
+    }
+    let batch = candidates.len().div_ceil(workers);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = candidates
+            .chunks(batch)
+            .map(|chunk| scope.spawn(move || chunk.iter().filter_map(stat_dir).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_else(|_| Vec::new()))
+            .collect()
+    })
+}
+
+/// Metadata of `entry` when it is a directory or a symlink to one.
+fn stat_dir(entry: &DirEntry) -> Option<ScannedDir> {
+    let path = entry.path();
+    let meta = match entry.file_type() {
+        // Not a link, so the entry's own metadata is the directory's
+        Ok(t) if t.is_dir() => entry.metadata().ok()?,
+        _ => fs::metadata(&path).ok()?,
+    };
+    meta.is_dir().then(|| ScannedDir {
+        name: entry.file_name().to_string_lossy().to_string(),
+        path,
+        meta,
+    })
+}
@@ -1,16 +1,233 @@
+use std::cmp::Ordering;
 use std::time::SystemTime;
 
+use crate::model::TryDir;
+use crate::timestamps::age;
 use crate::util::split_date_prefixed;
 
-/// Computes a fuzzy match score for `text` against `query`, with recency boosts from ctime/mtime.
-pub(crate) fn calculate_score(
+/// Ordering applied between entries with equal scores.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TieBreak {
+    /// Most recently modified first.
+    Mtime,
+    /// Most recently created first.
+    Ctime,
+    /// Name, A to Z.
+    Name,
+}
+
+/// Default tie-breakers: newest modification, then name.
+pub(crate) const DEFAULT_TIE_BREAK: &[TieBreak] = &[TieBreak::Mtime, TieBreak::Name];
+
+impl TieBreak {
+    /// Parses a comma-separated list such as `mtime,name`.
+    pub(crate) fn parse_list(s: &str) -> Result<Vec<Self>, String> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(|k| match k.to_ascii_lowercase().as_str() {
+                "mtime" => Ok(TieBreak::Mtime),
+                "ctime" => Ok(TieBreak::Ctime),
+                "name" => Ok(TieBreak::Name),
+                _ => Err(format!(
+                    "unknown tie-breaker `{k}` (use mtime, ctime, or name)"
+                )),
+            })
+            .collect()
+    }
+
+    fn compare(self, a: &TryDir, b: &TryDir) -> Ordering {
+        match self {
+            // `None` sorts as oldest
+            TieBreak::Mtime => b.mtime.cmp(&a.mtime),
+            TieBreak::Ctime => b.ctime.cmp(&a.ctime),
+            TieBreak::Name => a.basename.cmp(&b.basename),
+        }
+    }
+}
+
+/// Ranking order: higher score first, then `ties` in order, then name so the result never
+/// depends on directory listing order.
+pub(crate) fn compare_ranked(a: &TryDir, b: &TryDir, ties: &[TieBreak]) -> Ordering {
+    ties.iter()
+        .fold(b.score.total_cmp(&a.score), |ord, tie| {
+            ord.then_with(|| tie.compare(a, b))
+        })
+        .then_with(|| a.basename.cmp(&b.basename))
+}
+
+/// The parts a score is made of, for explaining a ranking.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct ScoreParts {
+    /// Date-prefix bonus plus character matches, scaled by match spread and name length.
+    pub name: f64,
+    /// Boost for a recent creation time.
+    pub created: f64,
+    /// Boost for a recent modification time.
+    pub modified: f64,
+    /// Boost for frequent recent visits from `try cd`.
+    pub visits: f64,
+    /// False when `query` is not a subsequence of the name; the score is then 0.
+    pub matched: bool,
+}
+
+impl ScoreParts {
+    pub(crate) fn total(&self) -> f64 {
+        if self.matched {
+            self.name + self.created + self.modified + self.visits
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Greedy match of a query through one name, kept between keystrokes: a longer query that
+/// starts with the one matched so far continues from here instead of rescanning the name.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct MatchState {
+    /// Bytes of the lowercase query consumed so far.
+    consumed: usize,
+    /// Points from matched characters, word starts, and gaps.
+    points: f64,
+    /// Char position of the last matched character and the byte offset just after it.
+    last: Option<(usize, usize)>,
+    /// A query character was not found; no longer query can match either.
+    failed: bool,
+}
+
+impl MatchState {
+    /// Whether the consumed part can be a prefix of `query` (lowercase). That it actually is
+    /// one is up to the caller; see [`MatchCache`].
+    fn resumes(&self, query: &str) -> bool {
+        self.consumed <= query.len() && query.is_char_boundary(self.consumed)
+    }
+
+    /// Matches the part of `query` beyond what was consumed through `text`; both lowercase.
+    /// Char positions of newly matched characters are appended to `positions` if given.
+    fn extend(&mut self, text: &str, query: &str, mut positions: Option<&mut Vec<usize>>) {
+        let rest = &query[self.consumed..];
+        self.consumed = query.len();
+        if self.failed || rest.is_empty() {
+            return;
+        }
+        let (mut pos, start) = self.last.map_or((0, 0), |(p, b)| (p + 1, b));
+        let mut prev_ch = text[..start].chars().next_back();
+        let mut wanted = rest.chars().peekable();
+        for (offset, ch) in text[start..].char_indices() {
+            let Some(&q) = wanted.peek() else { break };
+            if ch == q {
+                self.points += 1.0;
+                if is_word_start(prev_ch, ch) {
+                    self.points += 1.0;
+                }
+                if let Some((lp, _)) = self.last {
+                    let gap = pos.saturating_sub(lp + 1) as f64;
+                    self.points += 1.0 / (gap + 1.0).sqrt();
+                }
+                self.last = Some((pos, start + offset + ch.len_utf8()));
+                if let Some(out) = positions.as_deref_mut() {
+                    out.push(pos);
+                }
+                wanted.next();
+            }
+            prev_ch = Some(ch);
+            pos += 1;
+        }
+        self.failed = wanted.peek().is_some();
+    }
+}
+
+/// Char positions in `text` of the characters `query` is matched on, case-insensitively, or
+/// `None` when it does not match. Highlighting draws these, so the bold characters are
+/// always the ones the score counted.
+pub(crate) fn match_positions(text: &str, query: &str) -> Option<Vec<usize>> {
+    let mut positions = Vec::new();
+    let mut state = MatchState::default();
+    state.extend(
+        &text.to_lowercase(),
+        &query.to_lowercase(),
+        Some(&mut positions),
+    );
+    (!state.failed).then_some(positions)
+}
+
+/// Match states for a list of names that follow the query as it is edited.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MatchCache {
+    /// Lowercase query every state covers a prefix of.
+    query: String,
+    states: Vec<MatchState>,
+}
+
+impl MatchCache {
+    /// Prepares for `query` over `len` entries: states survive while the query only grows,
+    /// and edits elsewhere drop those that went past the unchanged prefix.
+    pub(crate) fn retarget(&mut self, query: &str, len: usize) {
+        let query = query.to_lowercase();
+        if self.states.len() != len {
+            self.states = vec![MatchState::default(); len];
+        } else if !query.starts_with(&self.query) {
+            let kept = self
+                .query
+                .char_indices()
+                .zip(query.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(self.query.len().min(query.len()), |((i, _), _)| i);
+            for state in &mut self.states {
+                if state.consumed > kept {
+                    *state = MatchState::default();
+                }
+            }
+        }
+        self.query = query;
+    }
+
+    /// State of entry `i`, to pass to [`score_try`].
+    pub(crate) fn state(&mut self, i: usize) -> &mut MatchState {
+        &mut self.states[i]
+    }
+}
+
+/// Score of a listed try against `query`, including its visit boost. `state` holds the
+/// match of an earlier, shorter query (or the default) and is advanced to `query`.
+pub(crate) fn score_try(t: &TryDir, query: &str, state: &mut MatchState) -> f64 {
+    try_score_parts(t, query, state).total()
+}
+
+/// `score_try` split into its parts.
+pub(crate) fn try_score_parts(t: &TryDir, query: &str, state: &mut MatchState) -> ScoreParts {
+    // Grows with the log of the decayed visit count, so habits help without dominating
+    const VISIT_WEIGHT: f64 = 1.5;
+    ScoreParts {
+        visits: VISIT_WEIGHT * t.visits.ln_1p(),
+        ..resume_score_parts(&t.basename, query, state, t.ctime, t.mtime)
+    }
+}
+
+/// Fuzzy match score of `text` against `query`, with recency boosts from ctime/mtime, split
+/// into its parts.
+#[cfg(any(test, feature = "testing"))]
+pub(crate) fn score_parts(
+    text: &str,
+    query: &str,
+    ctime: Option<SystemTime>,
+    mtime: Option<SystemTime>,
+) -> ScoreParts {
+    resume_score_parts(text, query, &mut MatchState::default(), ctime, mtime)
+}
+
+/// `score_parts`, continuing the match in `state`, which must cover a prefix of `query`.
+fn resume_score_parts(
     text: &str,
     query: &str,
+    state: &mut MatchState,
     ctime: Option<SystemTime>,
     mtime: Option<SystemTime>,
-) -> f64 {
+) -> ScoreParts {
     // Tunable weights; kept identical to previous literals.
     const DATE_PREFIX_BONUS: f64 = 2.0;
+    // Per query character when the query spells out word initials; not scaled by spread
+    const INITIALISM_BONUS: f64 = 1.0;
     const LENGTH_SMOOTHING: f64 = 10.0;
     const CTIME_WEIGHT: f64 = 2.0;
     const MTIME_WEIGHT: f64 = 3.0;
@@ -26,57 +243,107 @@ pub(crate) fn calculate_score(
     if !query.is_empty() {
         let tl = text.to_lowercase();
         let ql = query.to_lowercase();
-
-        let q_len = ql.chars().count();
-        let mut q_iter = ql.chars();
-        let mut current_q = q_iter.next();
-        let mut matched = 0usize;
-
-        let mut last_pos: Option<usize> = None;
-        let mut prev_ch: Option<char> = None;
-
-        for (pos, ch) in tl.chars().enumerate() {
-            if current_q.is_none() {
-                break;
-            }
-            if Some(ch) == current_q {
-                score += 1.0;
-                let prev_is_boundary = prev_ch.map(|c| !c.is_alphanumeric()).unwrap_or(true);
-                if prev_is_boundary {
-                    score += 1.0;
-                }
-                if let Some(lp) = last_pos {
-                    let gap = pos.saturating_sub(lp + 1) as f64;
-                    score += 1.0 / (gap + 1.0).sqrt();
-                }
-                last_pos = Some(pos);
-                matched += 1;
-                current_q = q_iter.next();
-            }
-            prev_ch = Some(ch);
+        if !state.resumes(&ql) {
+            *state = MatchState::default();
         }
-        if matched < q_len {
-            return 0.0;
+        state.extend(&tl, &ql, None);
+        if state.failed {
+            return ScoreParts::default();
         }
-        if let Some(lp) = last_pos {
+        let q_len = ql.chars().count();
+        score += state.points;
+        if let Some((lp, _)) = state.last {
             score *= q_len as f64 / (lp as f64 + 1.0);
         }
+        if q_len > 1 && is_initialism(&tl, &ql) {
+            score += INITIALISM_BONUS * q_len as f64;
+        }
         let text_chars_len = text.chars().count() as f64;
         score *= LENGTH_SMOOTHING / (text_chars_len + LENGTH_SMOOTHING);
     }
 
+    let mut parts = ScoreParts {
+        name: score,
+        matched: true,
+        ..ScoreParts::default()
+    };
     let now = SystemTime::now();
-    if let Some(ct) = ctime
-        && let Ok(age) = now.duration_since(ct)
-    {
-        let days = age.as_secs_f64() / SECONDS_PER_DAY;
-        score += CTIME_WEIGHT / (days + 1.0).sqrt();
+    if let Some(ct) = ctime {
+        let days = age(ct, now).as_secs_f64() / SECONDS_PER_DAY;
+        parts.created = CTIME_WEIGHT / (days + 1.0).sqrt();
+    }
+    if let Some(mt) = mtime {
+        let hours = age(mt, now).as_secs_f64() / SECONDS_PER_HOUR;
+        parts.modified = MTIME_WEIGHT / (hours + 1.0).sqrt();
     }
-    if let Some(mt) = mtime
-        && let Ok(age) = now.duration_since(mt)
-    {
-        let hours = age.as_secs_f64() / SECONDS_PER_HOUR;
-        score += MTIME_WEIGHT / (hours + 1.0).sqrt();
+    parts
+}
+
+/// Whether `ch` starts a word: it follows the start, a separator such as `-` or `_`, or a
+/// switch between digits and letters (`v2api` is `v`, `2`, `api`).
+fn is_word_start(prev: Option<char>, ch: char) -> bool {
+    prev.is_none_or(|p| {
+        !p.is_alphanumeric() || (ch.is_alphanumeric() && p.is_numeric() != ch.is_numeric())
+    })
+}
+
+/// Whether `query` matches word initials of `text` only, e.g. `hsp` in
+/// `http-server-playground`.
+fn is_initialism(text: &str, query: &str) -> bool {
+    let mut initials = text
+        .chars()
+        .scan(None, |prev, ch| {
+            let start = is_word_start(*prev, ch);
+            *prev = Some(ch);
+            Some((start, ch))
+        })
+        .filter_map(|(start, ch)| start.then_some(ch));
+    query.chars().all(|q| initials.any(|c| c == q))
+}
+
+/// Levenshtein edit distance between `a` and `b`, compared case-insensitively.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// Names within a small edit distance of `query` (date prefixes ignored), closest first.
+pub(crate) fn suggest_similar<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    query: &str,
+    limit: usize,
+) -> Vec<String> {
+    // Allow roughly one typo per three characters, but always at least two.
+    const MIN_MAX_DISTANCE: usize = 2;
+    const CHARS_PER_TYPO: usize = 3;
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
     }
-    score
+    let max_distance = (query.chars().count() / CHARS_PER_TYPO).max(MIN_MAX_DISTANCE);
+    let mut scored: Vec<(usize, &str)> = names
+        .into_iter()
+        .filter_map(|name| {
+            let stem = split_date_prefixed(name).map_or(name, |(_, rest)| rest);
+            let d = edit_distance(stem, query);
+            (d <= max_distance).then_some((d, name))
+        })
+        .collect();
+    scored.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, name)| name.to_string())
+        .collect()
 }
@@ -0,0 +1,77 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::config::Config;
+use crate::util::today_prefix;
+
+/// Default folder for scratch files under the tries directory; never listed as a try.
+pub(crate) const SCRATCH_DIR: &str = ".try_scratch";
+const DEFAULT_EXT: &str = "txt";
+
+/// Where scratch files go: `scratch.dir` / `TRY_SCRATCH_DIR`, else `.try_scratch` in the
+/// tries directory.
+pub(crate) fn scratch_dir(base_path: &Path, config: &Config) -> PathBuf {
+    config
+        .scratch_dir
+        .clone()
+        .unwrap_or_else(|| base_path.join(SCRATCH_DIR))
+}
+
+/// Dated file name such as `2025-08-26-scratch.rs`; repeats on the same day get `-2`, `-3`,
+/// and so on. A leading dot and characters other than ASCII letters and digits are dropped
+/// from `ext`, falling back to `txt`.
+pub(crate) fn scratch_name(ext: Option<&str>, repeat: usize) -> String {
+    let ext: String = ext
+        .unwrap_or_default()
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect();
+    let ext = if ext.is_empty() { DEFAULT_EXT } else { &ext };
+    let stem = format!("{}-scratch", today_prefix());
+    if repeat > 1 {
+        format!("{stem}-{repeat}.{ext}")
+    } else {
+        format!("{stem}.{ext}")
+    }
+}
+
+/// Creates a new empty scratch file in `dir`, creating `dir` if needed.
+pub(crate) fn create_scratch(dir: &Path, ext: Option<&str>) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    for repeat in 1.. {
+        let path = dir.join(scratch_name(ext, repeat));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(_) => return Ok(path),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("the repeat counter is unbounded")
+}
+
+/// Names of the scratch files in `dir`, most recently modified first.
+pub(crate) fn list_scratches(dir: &Path) -> Vec<String> {
+    let Ok(rd) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(SystemTime, String)> = rd
+        .flatten()
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            meta.is_file().then(|| {
+                (
+                    meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    e.file_name().to_string_lossy().to_string(),
+                )
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    files.into_iter().map(|(_, name)| name).collect()
+}
@@ -1,29 +1,59 @@
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers, read},
+    event::{Event, KeyCode, KeyEvent, KeyModifiers},
     terminal,
     tty::IsTty,
 };
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     env, fs,
     io::{self, Write},
+    ops::Range,
     path::{Path, PathBuf},
-    time::Duration,
+    time::{Duration, Instant, SystemTime},
 };
 
-use crate::error::Result;
-use crate::model::TryDir;
-use crate::score::calculate_score;
-use crate::tui::{self, TermGuard, render};
-use crate::util::{is_printable, sanitize_query, shellexpand_home};
+use crate::archive::{ARCHIVE_DIR, archive_try};
+use crate::charset::{is_search_char, sanitize_query, sanitize_search};
+use crate::error::{Error, Result, WriteBlocked};
+use crate::events::{EventSource, Recorder, Terminal};
+use crate::git::GitStatus;
+use crate::history::QueryHistory;
+use crate::i18n::{Msg, tr, trf};
+use crate::index::CharIndex;
+use crate::model::{MatchList, TryDir};
+use crate::profile::StartupProfile;
+use crate::query::{
+    DIRTY_TOKEN, Filter, ParsedQuery, Recency, cycle_time_chip, parse_query, toggle_token,
+};
+use crate::score::{
+    DEFAULT_TIE_BREAK, MatchCache, TieBreak, compare_ranked, score_try, suggest_similar,
+};
+use crate::status::{Severity, StatusQueue};
+use crate::storage::{
+    CollisionPolicy, KEEP_MARKER, NameCheck, check_new_name, collision_policy,
+    forget_pending_clone, is_protected, name_collision, new_try_name, normalize_query_for_match,
+    scan_tries, suffixed_name, write_blocked,
+};
+use crate::tasks::{TaskKind, TaskOutput, TaskRegistry};
+use crate::tui::{self, Console, Feedback, SectionHeader, TermCaps, TermGuard, render};
+use crate::util::{longest_common_prefix, shellexpand_home, split_date_prefixed, tilde_path};
 
 // Terminal defaults and UI timing
+/// Default `selector.size_warning`: 20 GiB.
+pub(crate) const DEFAULT_SIZE_WARNING: u64 = 20 << 30;
 const DEFAULT_TERM_WIDTH: u16 = 80;
 const DEFAULT_TERM_HEIGHT: u16 = 24;
-const POLL_INTERVAL_MS: u64 = 200;
-// Number of extra rows (e.g., "Create new") accounted for in list sizing
+// Number of extra rows (e.g., "Create new") accounted for in list sizing; lines mode has none
 const EXTRA_LIST_ROWS: usize = 1;
+// Maximum "did you mean" suggestions shown when nothing matches
+const MAX_SUGGESTIONS: usize = 3;
+// How many of the top matches Tab cycles through
+const TAB_CYCLE_LIMIT: usize = 10;
+// Poll interval while background metadata jobs are outstanding
+const TASK_POLL_MS: u64 = 30;
+// Marked names listed in the combined delete confirmation before summarizing the rest
+const MAX_LISTED_MARKS: usize = 8;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum ActionType {
@@ -32,6 +62,50 @@ pub(crate) enum ActionType {
     Cancel,
 }
 
+/// What the selector lists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ListMode {
+    /// Tries under the base path, with metadata, creation, and deletion.
+    Tries,
+    /// Arbitrary candidate lines (`try pick`); selection only.
+    Lines,
+}
+
+/// Outcome of handling one input event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Step {
+    /// Nothing visible changed.
+    Idle,
+    Redraw,
+    /// A selection (or cancel) was made; leave the event loop.
+    Done,
+}
+
+impl Step {
+    fn redraw_if(changed: bool) -> Self {
+        if changed { Step::Redraw } else { Step::Idle }
+    }
+
+    fn done_if(selected: bool) -> Self {
+        if selected { Step::Done } else { Step::Redraw }
+    }
+}
+
+/// The Alt-T tag list, open over the matches until Enter, Esc, or Alt-T.
+#[derive(Clone, Debug)]
+struct TagPicker {
+    /// Every tag with the number of tries carrying it, by name.
+    tags: Vec<(String, usize)>,
+    cursor: usize,
+}
+
+/// Candidates captured on the first Tab press; repeated Tabs step through them.
+#[derive(Clone, Debug)]
+struct TabCycle {
+    candidates: Vec<String>,
+    next: usize,
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct Selection {
     pub(crate) kind: ActionType,
@@ -45,10 +119,70 @@ pub(crate) struct TrySelector {
     pub(crate) scroll: usize,
     pub(crate) input_buf: String,
     pub(crate) all_tries: Option<Vec<TryDir>>,
+    /// Character index over `all_tries` names, built on the first non-empty query.
+    index: Option<CharIndex>,
+    /// Per-entry match progress, so a growing query extends earlier matches.
+    match_cache: MatchCache,
+    /// Indices into `all_tries` of entries matching the query, best first.
+    matches: Vec<usize>,
+    /// Query `matches` was computed for; a longer query only re-scores those entries.
+    matched_query: Option<ParsedQuery>,
     pub(crate) base_path: PathBuf,
+    /// Names of the only tries to offer (`try cd --project`); `None` offers all.
+    pub(crate) only: Option<HashSet<String>>,
     pub(crate) selected: Option<Selection>,
-    status_msg: Option<String>,
+    status: StatusQueue,
     size_cache: HashMap<PathBuf, u64>,
+    git_cache: HashMap<PathBuf, Option<GitStatus>>,
+    tab_cycle: Option<TabCycle>,
+    tag_picker: Option<TagPicker>,
+    history: QueryHistory,
+    // Position while recalling past queries (0 = most recent); reset by any other key
+    history_pos: Option<usize>,
+    /// Startup timings collected when `--profile-startup` is set.
+    pub(crate) profile: Option<StartupProfile>,
+    /// Background metadata jobs; only with the `background-tasks` feature.
+    tasks: Option<TaskRegistry>,
+    mode: ListMode,
+    /// Skip confirmation prompts for destructive actions (`--yes`).
+    pub(crate) assume_yes: bool,
+    /// Delete and archive tries even when they carry a keep marker (`--force`).
+    pub(crate) force: bool,
+    /// Why the base path cannot be written; the selector then only browses.
+    read_only: Option<WriteBlocked>,
+    /// Delay after the last keypress before re-matching (`selector.debounce_ms`); zero
+    /// re-matches after every batch of input.
+    pub(crate) debounce: Duration,
+    /// Order among equal scores (`selector.tie_break`).
+    pub(crate) tie_break: Vec<TieBreak>,
+    /// Destination of Ctrl-A archives (`archive.dir`).
+    pub(crate) archive_dir: PathBuf,
+    /// Hide the "Older" section of the empty-query view until Ctrl-E expands it
+    /// (`selector.collapse_older`).
+    pub(crate) collapse_older: bool,
+    /// Create new tries on the spot and keep the selector open with the new entry
+    /// highlighted (`selector.stay_open_after_create`), instead of leaving to enter it.
+    pub(crate) stay_open_after_create: bool,
+    /// Tries created while the selector stayed open, oldest first.
+    pub(crate) created: Vec<PathBuf>,
+    /// Tries marked with Ctrl-Space for a combined Ctrl-D, in the order they were marked.
+    marked: Vec<PathBuf>,
+    /// How often the idle selector wakes up to check the terminal size
+    /// (`selector.poll_ms`); `None` sleeps until the next key or resize event.
+    pub(crate) poll_interval: Option<Duration>,
+    /// Least time between two frames (`selector.max_fps`); zero draws every change at once.
+    pub(crate) frame_interval: Duration,
+    /// Total size of the tries from which the status line suggests cleaning up
+    /// (`selector.size_warning`); 0 never does.
+    pub(crate) size_warning: u64,
+    older_expanded: bool,
+    /// Match positions where each recency section starts; empty unless the query is empty.
+    sections: Vec<(usize, Recency)>,
+    /// Older entries left out of `matches` while collapsed.
+    hidden_older: usize,
+    /// Frame on screen when drawing in place (`ui.no_clear`), so the next one only rewrites
+    /// the lines that changed; `None` after something else drew over it.
+    painted: Option<String>,
     // no vim/undo mode in Ruby semantics
 }
 
@@ -63,181 +197,769 @@ impl TrySelector {
 
     pub(crate) fn new(initial_query: &str, base_path: PathBuf) -> Result<Self> {
         if !base_path.exists() {
-            fs::create_dir_all(&base_path)?;
+            fs::create_dir_all(&base_path).map_err(|e| Error::from_write(&base_path, e))?;
         }
         let (w, h) = terminal::size().unwrap_or((DEFAULT_TERM_WIDTH, DEFAULT_TERM_HEIGHT));
+        let history = QueryHistory::load(&base_path);
+        let read_only = write_blocked(&base_path);
+        let mut status = StatusQueue::default();
+        if let Some(blocked) = read_only {
+            status.set_persistent(Severity::Warn, read_only_status(blocked));
+        }
+        let archive_dir = base_path.join(ARCHIVE_DIR);
         Ok(Self {
             term_w: w,
             term_h: h,
             cursor: 0,
             scroll: 0,
-            input_buf: sanitize_query(initial_query),
+            input_buf: sanitize_search(initial_query),
             all_tries: None,
+            index: None,
+            match_cache: MatchCache::default(),
+            matches: Vec::new(),
+            matched_query: None,
             base_path,
+            only: None,
             selected: None,
-            status_msg: None,
+            status,
             size_cache: HashMap::new(),
+            git_cache: HashMap::new(),
+            tab_cycle: None,
+            tag_picker: None,
+            history,
+            history_pos: None,
+            profile: None,
+            tasks: cfg!(feature = "background-tasks").then(TaskRegistry::new),
+            mode: ListMode::Tries,
+            assume_yes: false,
+            force: false,
+            read_only,
+            debounce: Duration::ZERO,
+            tie_break: DEFAULT_TIE_BREAK.to_vec(),
+            archive_dir,
+            collapse_older: false,
+            stay_open_after_create: false,
+            created: Vec::new(),
+            marked: Vec::new(),
+            poll_interval: None,
+            frame_interval: Duration::ZERO,
+            size_warning: DEFAULT_SIZE_WARNING,
+            older_expanded: false,
+            sections: Vec::new(),
+            hidden_older: 0,
+            painted: None,
         })
     }
 
+    /// Selector over `lines` instead of a tries directory. Entries keep their input order
+    /// until a query ranks them; nothing is created, deleted, or recorded in history.
+    pub(crate) fn for_lines(initial_query: &str, lines: Vec<String>) -> Self {
+        let (w, h) = terminal::size().unwrap_or((DEFAULT_TERM_WIDTH, DEFAULT_TERM_HEIGHT));
+        let entries = lines
+            .into_iter()
+            .map(|line| TryDir {
+                path: PathBuf::from(&line),
+                basename: line,
+                ctime: None,
+                mtime: None,
+                score: 0.0,
+                size: None,
+                git: None,
+                pending_clone: false,
+                visits: 0.0,
+                behind: None,
+                expires: None,
+                tags: Vec::new(),
+            })
+            .collect();
+        Self {
+            term_w: w,
+            term_h: h,
+            cursor: 0,
+            scroll: 0,
+            input_buf: initial_query.chars().filter(|c| !c.is_control()).collect(),
+            all_tries: Some(entries),
+            index: None,
+            match_cache: MatchCache::default(),
+            matches: Vec::new(),
+            matched_query: None,
+            base_path: PathBuf::new(),
+            only: None,
+            selected: None,
+            status: StatusQueue::default(),
+            size_cache: HashMap::new(),
+            git_cache: HashMap::new(),
+            tab_cycle: None,
+            tag_picker: None,
+            history: QueryHistory::default(),
+            history_pos: None,
+            profile: None,
+            tasks: None,
+            mode: ListMode::Lines,
+            assume_yes: false,
+            force: false,
+            read_only: None,
+            debounce: Duration::ZERO,
+            tie_break: DEFAULT_TIE_BREAK.to_vec(),
+            archive_dir: PathBuf::new(),
+            collapse_older: false,
+            stay_open_after_create: false,
+            created: Vec::new(),
+            marked: Vec::new(),
+            poll_interval: None,
+            frame_interval: Duration::ZERO,
+            size_warning: 0,
+            older_expanded: false,
+            sections: Vec::new(),
+            hidden_older: 0,
+            painted: None,
+        }
+    }
+
+    /// Runs the selector on the terminal until something is chosen or it is cancelled. With
+    /// `--replay`, runs the script headlessly instead, writing every frame to stderr; with
+    /// `--record`, also saves the events read, with their times, for a later replay.
     pub(crate) fn run(&mut self) -> Result<Option<Selection>> {
         let mut err = io::stderr();
-        if !io::stdin().is_tty() || !io::stderr().is_tty() {
-            crate::tui::error(&mut err, "try requires an interactive terminal")?;
+        if let Some(mut script) = crate::events::replay() {
+            let caps = TermCaps {
+                screen: false,
+                no_clear: false,
+                ..TermCaps::screen()
+            };
+            // A recorded session keeps its timing so debouncing and the frame cap play out as
+            // they did; a hand-written script has none, which must not change what it shows
+            if !script.is_timed() {
+                self.debounce = Duration::ZERO;
+                self.frame_interval = Duration::ZERO;
+            }
+            self.tasks = None;
+            return self.drive(&mut script, &mut err, caps);
+        }
+        // Lines mode reads candidates from piped stdin; keys then come from the controlling
+        // terminal
+        let stdin_ok = self.mode == ListMode::Lines || io::stdin().is_tty();
+        if !stdin_ok || !io::stderr().is_tty() {
+            crate::tui::error(&mut err, tr(Msg::NeedsTerminal))?;
             return Ok(None);
         }
 
         let _guard = TermGuard::new()?; // raw mode on; auto-restores on drop
-        self.setup_terminal(&mut err)?; // initial clear + move
+        if !TermCaps::screen().no_clear {
+            self.setup_terminal(&mut err)?; // initial clear + move
+        }
+        let selection = match crate::events::record_path() {
+            Some(path) => {
+                let mut recorder = Recorder::create(Terminal, path)?;
+                self.drive(&mut recorder, &mut err, TermCaps::screen())?
+            }
+            None => self.drive(&mut Terminal, &mut err, TermCaps::screen())?,
+        };
+        self.remember_selection();
+        Ok(selection)
+    }
 
+    /// The selector's event loop: reads `events`, draws frames to `out`, and returns what
+    /// was chosen. [`Self::run`] drives it from the terminal; tests and `--replay` drive it
+    /// from a [`crate::events::Script`] and a buffer. Without a screen (`caps.screen` off),
+    /// each frame is written below the previous one under a `-- frame N --` line.
+    pub(crate) fn drive(
+        &mut self,
+        events: &mut dyn EventSource,
+        out: &mut dyn Write,
+        caps: TermCaps,
+    ) -> Result<Option<Selection>> {
+        let mut con = Console { out, events, caps };
         // Lazy redraw to reduce flicker
         let mut dirty = true;
-        let mut tries: Vec<TryDir> = Vec::new();
-        let (mut last_w, mut last_h) =
-            terminal::size().unwrap_or((DEFAULT_TERM_WIDTH, DEFAULT_TERM_HEIGHT));
+        let (mut last_w, mut last_h) = con.events.size();
         self.term_w = last_w;
         self.term_h = last_h;
+        self.load_all();
+        self.mark_profile("directory scan");
+        let debounce = self.debounce;
+        let mut last_input: Option<Instant> = None;
+        let mut last_render: Option<Instant> = None;
+        let mut frames = 0;
 
-        loop {
-            let (w, h) = terminal::size().unwrap_or((DEFAULT_TERM_WIDTH, DEFAULT_TERM_HEIGHT));
+        'events: loop {
+            let (w, h) = con.events.size();
             if w != last_w || h != last_h {
                 self.term_w = w;
                 self.term_h = h;
                 last_w = w;
                 last_h = h;
+                self.painted = None;
                 dirty = true;
             }
 
-            if dirty {
-                tries = self.get_tries();
-                let total_items = tries.len() + EXTRA_LIST_ROWS;
-                self.cursor = self.cursor.min(total_items.saturating_sub(1));
-
-                // Calculate sizes lazily for visible items only
-                let max_visible = usize::max(
-                    self.term_h.saturating_sub(8) as usize, // RESERVED_LINES from tui.rs
-                    3,                                      // MIN_VISIBLE_ITEMS
-                );
-                let (scroll, end) =
-                    tui::compute_viewport(self.cursor, self.scroll, max_visible, total_items);
-                self.scroll = scroll;
-
-                // Calculate sizes for visible items only to avoid blocking
-                let tries_len = tries.len();
-                let start_idx = scroll.min(tries_len);
-                let end_idx = end.min(tries_len);
-                for t in &mut tries[start_idx..end_idx] {
-                    self.ensure_size_calculated(t);
+            if self.apply_task_results() {
+                dirty = true;
+            }
+            if self.status.expire(Instant::now()) {
+                dirty = true;
+            }
+
+            // Re-match once typing pauses for the debounce window; until then the input
+            // line updates over the previous matches. Without anything due, wait for the
+            // next key or resize event (or `poll_interval`, when set).
+            let mut poll_timeout = self.poll_interval;
+            if self.matches_stale() {
+                let wait =
+                    last_input.map_or(Duration::ZERO, |t| debounce.saturating_sub(t.elapsed()));
+                if wait.is_zero() {
+                    self.refresh_matches();
+                    dirty = true;
+                } else {
+                    poll_timeout = shorter(poll_timeout, wait);
                 }
+            }
+            if self.tasks.as_ref().is_some_and(TaskRegistry::has_pending) {
+                poll_timeout = shorter(poll_timeout, Duration::from_millis(TASK_POLL_MS));
+            }
+            if let Some(at) = self.status.next_change() {
+                poll_timeout = shorter(poll_timeout, at.saturating_duration_since(Instant::now()));
+            }
 
-                let ctx = tui::RenderCtx {
-                    term_w: self.term_w,
-                    term_h: self.term_h,
-                    cursor: self.cursor,
-                    scroll: self.scroll,
-                    input_buf: &self.input_buf,
-                    tries: &tries,
-                    status_msg: self.status_msg.clone(),
-                    show_delete_pending: false,
-                };
-                render(&mut err, &ctx)?;
-                dirty = false;
+            if dirty {
+                // Hold the frame back while the redraw rate is capped
+                let wait = last_render.map_or(Duration::ZERO, |t: Instant| {
+                    self.frame_interval.saturating_sub(t.elapsed())
+                });
+                if wait.is_zero() {
+                    frames += 1;
+                    if !caps.screen {
+                        writeln!(con.out, "-- frame {frames} --")?;
+                    }
+                    self.render_frame(&mut con.out, caps)?;
+                    dirty = false;
+                    last_render = Some(Instant::now());
+                    self.mark_profile("first render");
+                } else {
+                    poll_timeout = shorter(poll_timeout, wait);
+                }
             }
 
-            if !event::poll(Duration::from_millis(POLL_INTERVAL_MS))? {
+            if let Some(timeout) = poll_timeout
+                && !con.events.poll(timeout)?
+            {
                 continue;
             }
 
-            match read()? {
-                Event::Key(KeyEvent {
-                    code, modifiers, ..
-                }) => match (code, modifiers) {
-                    (KeyCode::Esc, _) => {
-                        self.selected = Some(Selection {
-                            kind: ActionType::Cancel,
-                            path: None,
-                        });
-                        break;
+            // Handle every queued event before drawing again so fast typing costs one
+            // re-match and one render per batch instead of per key
+            loop {
+                let event = con.events.read()?;
+                match self.handle_event(event, &mut con)? {
+                    Step::Done => break 'events,
+                    Step::Redraw => dirty = true,
+                    Step::Idle => {}
+                }
+                if !con.events.poll(Duration::ZERO)? {
+                    break;
+                }
+            }
+            last_input = Some(Instant::now());
+        }
+        Ok(self.selected.clone())
+    }
+
+    /// Saves the query to history once something was chosen, along with the pick when it
+    /// was not the top match, so the same query ranks it first next time.
+    pub(crate) fn remember_selection(&mut self) {
+        let Some(sel) = &self.selected else { return };
+        if sel.kind == ActionType::Cancel || self.mode != ListMode::Tries {
+            return;
+        }
+        if sel.kind == ActionType::Cd
+            && self.cursor > 0
+            && let Some(name) = sel.path.as_deref().and_then(Path::file_name)
+        {
+            self.history
+                .record_pick(&self.input_buf, &name.to_string_lossy());
+        }
+        self.history.push(&self.input_buf);
+        let _ = self.history.save();
+    }
+
+    /// Draws one frame for the initial query to `out` at `size` (columns, rows) without
+    /// touching the terminal, for `--render-once` and snapshot tests. Metadata for the
+    /// visible rows is computed inline rather than in the background.
+    pub(crate) fn render_once(
+        &mut self,
+        out: &mut impl Write,
+        caps: TermCaps,
+        size: (u16, u16),
+    ) -> Result<()> {
+        (self.term_w, self.term_h) = size;
+        self.tasks = None;
+        self.refresh_matches();
+        self.render_frame(out, caps)
+    }
+
+    /// Writes the first `limit` matches for the initial query to `out` as list rows, with
+    /// their metadata, fitted to `width` columns. Returns how many rows were written.
+    pub(crate) fn render_top(
+        &mut self,
+        out: &mut impl Write,
+        caps: TermCaps,
+        limit: usize,
+        width: u16,
+    ) -> Result<usize> {
+        self.tasks = None;
+        self.refresh_matches();
+        let top = self.matches[..self.matches.len().min(limit)].to_vec();
+        self.ensure_metadata(&top, true, true);
+        let tries = MatchList {
+            all: self.all_tries.as_deref().unwrap_or_default(),
+            order: &top,
+        };
+        tui::render_rows(out, caps, tries, &self.query_text(), width)?;
+        Ok(top.len())
+    }
+
+    /// Draws the current state, computing metadata for the visible rows first.
+    fn render_frame(&mut self, out: &mut impl Write, caps: TermCaps) -> Result<()> {
+        let total_items = self.matches.len() + self.extra_rows();
+        self.cursor = self.cursor.min(total_items.saturating_sub(1));
+
+        let sections = self.section_headers();
+        let max_visible = tui::layout(self.term_h, sections.len()).rows;
+        let (scroll, end) =
+            tui::compute_viewport(self.cursor, self.scroll, max_visible, total_items);
+        self.scroll = scroll;
+
+        // Calculate sizes for visible items only to avoid blocking
+        let match_count = self.matches.len();
+        if self.mode == ListMode::Lines {
+            // Candidate lines have no size or git metadata
+        } else if self.tasks.is_some() {
+            // Prefetch a page either side so scrolling finds rows filled in
+            let page = end - scroll;
+            self.queue_metadata(scroll.saturating_sub(page)..(end + page).min(match_count));
+        } else {
+            let visible = self.matches[scroll.min(match_count)..end.min(match_count)].to_vec();
+            self.ensure_metadata(&visible, true, true);
+        }
+
+        let name_check = self.check_input_name();
+        let query_text = self.query_text();
+        let suggestions = if self.matches.is_empty() {
+            suggest_similar(self.entry_names(), &query_text, MAX_SUGGESTIONS)
+        } else {
+            Vec::new()
+        };
+        let total_count = self.all_tries.as_ref().map_or(0, Vec::len);
+        let header_stats = self.workspace_stats();
+        let chips: Vec<String> = self
+            .parsed_input()
+            .filters
+            .into_iter()
+            .filter_map(|f| match f {
+                Filter::Tagged(tag) => Some(tag),
+                _ => None,
+            })
+            .collect();
+        let status_msg = self
+            .status
+            .line()
+            .or_else(|| self.size_notice().map(|n| (Severity::Info, n)));
+        let ctx = tui::RenderCtx {
+            term_w: self.term_w,
+            term_h: self.term_h,
+            cursor: self.cursor,
+            scroll: self.scroll,
+            input_buf: &self.input_buf,
+            query_text: &query_text,
+            tries: MatchList {
+                all: self.all_tries.as_deref().unwrap_or_default(),
+                order: &self.matches,
+            },
+            status_msg,
+            show_delete_pending: false,
+            name_check: name_check.as_ref(),
+            total_count,
+            header_stats: &header_stats,
+            suggestions: &suggestions,
+            pick_mode: self.mode == ListMode::Lines,
+            sections: &sections,
+            marked: &self.marked,
+            chips: &chips,
+            tag_picker: self.tag_picker.as_ref().map(|p| tui::TagPickerView {
+                tags: &p.tags,
+                cursor: p.cursor,
+            }),
+        };
+        if !(caps.screen && caps.no_clear) {
+            return render(out, caps, &ctx);
+        }
+        let mut frame = Vec::new();
+        render(&mut frame, caps, &ctx)?;
+        let frame = String::from_utf8_lossy(&frame).into_owned();
+        tui::paint_changes(out, &frame, self.painted.as_deref())?;
+        self.painted = Some(frame);
+        Ok(())
+    }
+
+    /// Applies one input event. Query edits only mark the matches stale; any other key
+    /// brings them up to date first so it acts on what the query currently selects.
+    pub(crate) fn handle_event(&mut self, event: Event, con: &mut Console<'_>) -> Result<Step> {
+        if !matches!(
+            event,
+            Event::Key(KeyEvent {
+                code: KeyCode::Tab,
+                ..
+            })
+        ) {
+            self.tab_cycle = None;
+        }
+        if !is_history_key(&event) {
+            self.history_pos = None;
+        }
+        let Event::Key(KeyEvent {
+            code, modifiers, ..
+        }) = event
+        else {
+            if let Event::Resize(w, h) = event {
+                self.term_w = w;
+                self.term_h = h;
+                self.painted = None;
+                return Ok(Step::Redraw);
+            }
+            return Ok(Step::Idle);
+        };
+        if self.tag_picker.is_some() {
+            return Ok(self.handle_tag_picker_key(code, modifiers));
+        }
+        let edits_query = matches!(code, KeyCode::Backspace)
+            || matches!(code, KeyCode::Char(_)) && is_text_input(modifiers);
+        if !edits_query && self.matches_stale() {
+            self.refresh_matches();
+        }
+        let step = match (code, modifiers) {
+            (KeyCode::Tab, _) => {
+                self.complete_query();
+                Step::Redraw
+            }
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                self.selected = Some(Selection {
+                    kind: ActionType::Cancel,
+                    path: None,
+                });
+                Step::Done
+            }
+            // Filters, remotes, creation, marks, and deletion only apply to tries
+            (KeyCode::Char('t' | 'g' | 'o' | 'a' | 'd' | 'e' | ' '), KeyModifiers::CONTROL)
+            | (KeyCode::Char('n' | 't'), KeyModifiers::ALT)
+            | (KeyCode::Enter, KeyModifiers::SHIFT)
+                if self.mode == ListMode::Lines =>
+            {
+                Step::Idle
+            }
+            (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) if self.cursor > 0 => {
+                self.cursor -= 1;
+                Step::Redraw
+            }
+            (KeyCode::Up, _) if self.input_buf.is_empty() || self.history_pos.is_some() => {
+                Step::redraw_if(self.recall_history(false))
+            }
+            (KeyCode::Char('t'), KeyModifiers::CONTROL) => {
+                self.input_buf = cycle_time_chip(&self.input_buf);
+                self.cursor = 0;
+                Step::Redraw
+            }
+            (KeyCode::Char('g'), KeyModifiers::CONTROL) => {
+                self.input_buf = toggle_token(&self.input_buf, DIRTY_TOKEN);
+                self.cursor = 0;
+                Step::Redraw
+            }
+            // Ctrl-T already cycles the time chip, so the tag list sits on Alt-T
+            (KeyCode::Char('t'), KeyModifiers::ALT) => {
+                self.open_tag_picker();
+                Step::Redraw
+            }
+            (KeyCode::Char('o'), KeyModifiers::CONTROL) => match self.match_at(self.cursor) {
+                Some(t) => {
+                    self.status.push(Severity::Info, open_remote(t));
+                    Step::Redraw
+                }
+                None => Step::Idle,
+            },
+            (KeyCode::Char('e'), KeyModifiers::CONTROL) if self.collapse_older => {
+                self.older_expanded = !self.older_expanded;
+                self.refresh_matches();
+                Step::redraw_if(!self.sections.is_empty())
+            }
+            (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+                Step::redraw_if(self.recall_history(true))
+            }
+            (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
+                let total_items = self.matches.len() + self.extra_rows();
+                let can_move = self.cursor + 1 < total_items;
+                if can_move {
+                    self.cursor += 1;
+                }
+                Step::redraw_if(can_move)
+            }
+            // Alt-N and Shift-Enter create from the query even when matches exist
+            (KeyCode::Char('n'), KeyModifiers::ALT) | (KeyCode::Enter, KeyModifiers::SHIFT) => {
+                Step::done_if(self.create_from_query(con)?)
+            }
+            (KeyCode::Enter, _) => {
+                if let Some(t) = self.match_at(self.cursor) {
+                    let path = t.path.clone();
+                    self.handle_select_existing(path);
+                    return Ok(Step::Done);
+                }
+                if self.mode == ListMode::Lines {
+                    return Ok(Step::Idle);
+                }
+                Step::done_if(self.create_from_query(con)?)
+            }
+            (KeyCode::Backspace, _) => {
+                self.input_buf.pop();
+                self.cursor = 0;
+                Step::Redraw
+            }
+            (KeyCode::Char('a'), KeyModifiers::CONTROL) if self.cursor < self.matches.len() => {
+                let t = self.all_tries.as_ref().expect("matches imply loaded")
+                    [self.matches[self.cursor]]
+                    .clone();
+                // The progress bar and the confirmation draw over the frame
+                self.painted = None;
+                let (severity, text) = match self.read_only {
+                    Some(blocked) => (Severity::Warn, read_only_status(blocked)),
+                    None if !self.force && is_protected(&t.path) => {
+                        (Severity::Warn, protected_status(&t, "archive"))
                     }
-                    (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
-                        if self.cursor > 0 {
-                            self.cursor -= 1;
-                            dirty = true;
-                        }
+                    None if !self.confirm_archive(con, &t)? => {
+                        (Severity::Info, tr(Msg::ArchiveCancelled).into())
                     }
-                    (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
-                        let total_items = tries.len() + EXTRA_LIST_ROWS;
-                        if self.cursor + 1 < total_items {
-                            self.cursor += 1;
-                            dirty = true;
+                    None => match self.archive_with_progress(con, &t) {
+                        Ok(dest) => {
+                            self.invalidate();
+                            let dest = tilde_path(&dest);
+                            let text =
+                                trf(Msg::Archived, &[("name", &t.basename), ("dest", &dest)]);
+                            (Severity::Info, text)
                         }
-                    }
-                    (KeyCode::Left, _) | (KeyCode::Right, _) => {}
-                    (KeyCode::Enter, _) => {
-                        if self.cursor < tries.len() {
-                            self.handle_select_existing(&tries[self.cursor]);
-                            break;
-                        } else if !self.input_buf.is_empty() {
-                            let date_prefix = crate::util::today_prefix();
-                            let final_name = format!("{}-{}", date_prefix, self.input_buf)
-                                .replace(char::is_whitespace, "-");
-                            let full_path = self.base_path.join(final_name);
-                            self.selected = Some(Selection {
-                                kind: ActionType::Mkdir,
-                                path: Some(full_path),
-                            });
-                            break;
-                        } else {
-                            self.prompt_new_name(&mut err)?;
-                            if self.selected.is_some() {
-                                break;
-                            }
-                            dirty = true;
+                        Err(e) => (Severity::Error, e.to_string()),
+                    },
+                };
+                self.status.push(severity, text);
+                Step::Redraw
+            }
+            // Ctrl-Space marks (or unmarks) the row and moves on to the next one
+            (KeyCode::Char(' '), KeyModifiers::CONTROL) => match self.match_at(self.cursor) {
+                Some(t) => {
+                    let path = t.path.clone();
+                    match self.marked.iter().position(|p| *p == path) {
+                        Some(pos) => {
+                            self.marked.remove(pos);
                         }
+                        None => self.marked.push(path),
                     }
-                    (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
-                        self.selected = Some(Selection {
-                            kind: ActionType::Cancel,
-                            path: None,
-                        });
-                        break;
+                    if self.cursor + 1 < self.matches.len() {
+                        self.cursor += 1;
                     }
-                    (KeyCode::Backspace, _) => {
-                        self.input_buf.pop();
-                        self.cursor = 0;
-                        dirty = true;
-                    }
-                    (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
-                        if self.cursor < tries.len() {
-                            let t = &tries[self.cursor];
-                            if self.confirm_and_delete(&mut err, t)? {
-                                self.all_tries = None;
-                                self.status_msg = Some(format!("Deleted: {}", t.basename));
-                                dirty = true;
-                            } else {
-                                self.status_msg = Some("Delete cancelled".into());
-                                dirty = true;
-                            }
-                        }
+                    Step::Redraw
+                }
+                None => Step::Idle,
+            },
+            (KeyCode::Char('d'), KeyModifiers::CONTROL) if !self.marked.is_empty() => {
+                self.delete_marked(con)?;
+                Step::Redraw
+            }
+            (KeyCode::Char('d'), KeyModifiers::CONTROL) if self.cursor < self.matches.len() => {
+                let t = self.all_tries.as_ref().expect("matches imply loaded")
+                    [self.matches[self.cursor]]
+                    .clone();
+                // The progress bar and the confirmation draw over the frame
+                self.painted = None;
+                let (severity, text) = match self.read_only {
+                    Some(blocked) => (Severity::Warn, read_only_status(blocked)),
+                    None if !self.force && is_protected(&t.path) => {
+                        (Severity::Warn, protected_status(&t, "delete"))
                     }
-                    (KeyCode::Char(ch), mods) => {
-                        if mods.is_empty() && is_printable(ch) {
-                            self.input_buf.push(ch);
-                            self.cursor = 0;
-                            dirty = true;
+                    None => match self.confirm_and_delete(con, &t)? {
+                        Ok(true) => {
+                            self.invalidate();
+                            (Severity::Info, trf(Msg::Deleted, &[("name", &t.basename)]))
                         }
-                    }
-                    _ => {}
-                },
-                Event::Resize(w, h) => {
-                    self.term_w = w;
-                    self.term_h = h;
-                    dirty = true;
+                        Ok(false) => (Severity::Info, tr(Msg::DeleteCancelled).into()),
+                        Err(msg) => (Severity::Error, msg),
+                    },
+                };
+                self.status.push(severity, text);
+                Step::Redraw
+            }
+            (KeyCode::Char(ch), mods) if is_text_input(mods) => {
+                if self.accepts_char(ch) {
+                    self.input_buf.push(ch);
+                    self.cursor = 0;
+                    self.status.clear();
+                } else {
+                    self.status.push(Severity::Warn, rejected_char_hint(&[ch]));
                 }
-                _ => {}
+                Step::Redraw
             }
+            _ => Step::Idle,
+        };
+        Ok(step)
+    }
+
+    /// The query split into text and filter tokens; in lines mode it is all text.
+    fn parsed_input(&self) -> ParsedQuery {
+        match self.mode {
+            ListMode::Tries => parse_query(&self.input_buf),
+            ListMode::Lines => ParsedQuery {
+                text: self.input_buf.clone(),
+                ..ParsedQuery::default()
+            },
+        }
+    }
+
+    /// Whether a typed character goes into the query. Candidate lines may contain anything,
+    /// so lines mode only rejects control characters.
+    fn accepts_char(&self, ch: char) -> bool {
+        match self.mode {
+            ListMode::Tries => is_search_char(ch),
+            ListMode::Lines => !ch.is_control(),
+        }
+    }
+
+    /// Whether the list is split into recency sections: the empty-query view of tries.
+    fn sectioned(&self) -> bool {
+        self.mode == ListMode::Tries && self.input_buf.trim().is_empty()
+    }
+
+    /// Opens the tag list with every tag under the base path, or says there are none.
+    fn open_tag_picker(&mut self) {
+        self.load_all();
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for t in self.all_tries.as_deref().unwrap_or_default() {
+            for tag in &t.tags {
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+        if counts.is_empty() {
+            self.status
+                .push(Severity::Info, tr(Msg::NoTags).to_string());
+            return;
+        }
+        let mut tags: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(tag, n)| (tag.to_string(), n))
+            .collect();
+        tags.sort();
+        self.tag_picker = Some(TagPicker { tags, cursor: 0 });
+    }
+
+    /// Keys while the tag list is open: move, toggle the highlighted tag's `#tag` token, or
+    /// close. Anything else is ignored.
+    fn handle_tag_picker_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Step {
+        let Some(picker) = &mut self.tag_picker else {
+            return Step::Idle;
+        };
+        match (code, modifiers) {
+            (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                let can_move = picker.cursor > 0;
+                if can_move {
+                    picker.cursor -= 1;
+                }
+                Step::redraw_if(can_move)
+            }
+            (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
+                let can_move = picker.cursor + 1 < picker.tags.len();
+                if can_move {
+                    picker.cursor += 1;
+                }
+                Step::redraw_if(can_move)
+            }
+            (KeyCode::Char(' '), KeyModifiers::NONE) => {
+                let token = format!("#{}", picker.tags[picker.cursor].0);
+                self.input_buf = toggle_token(&self.input_buf, &token);
+                self.cursor = 0;
+                Step::Redraw
+            }
+            (KeyCode::Enter | KeyCode::Esc, _) | (KeyCode::Char('t'), KeyModifiers::ALT) => {
+                self.tag_picker = None;
+                Step::Redraw
+            }
+            _ => Step::Idle,
+        }
+    }
+
+    /// Headers for the recency sections, or none when everything falls in one section.
+    pub(crate) fn section_headers(&self) -> Vec<SectionHeader> {
+        if self.sections.len() < 2 && self.hidden_older == 0 {
+            return Vec::new();
+        }
+        self.sections
+            .iter()
+            .map(|&(start, recency)| {
+                let label = match recency {
+                    Recency::Older if self.hidden_older > 0 => {
+                        trf(Msg::OlderHidden, &[("count", &self.hidden_older)])
+                    }
+                    Recency::Older if self.collapse_older => tr(Msg::OlderShown).into(),
+                    _ => recency.label().into(),
+                };
+                SectionHeader { start, label }
+            })
+            .collect()
+    }
+
+    /// Rows below the matches: the "Create new" row for tries, none for lines.
+    fn extra_rows(&self) -> usize {
+        match self.mode {
+            ListMode::Tries => EXTRA_LIST_ROWS,
+            ListMode::Lines => 0,
+        }
+    }
+
+    /// Whether `matches` no longer reflects `input_buf` (or entries were rescanned).
+    pub(crate) fn matches_stale(&self) -> bool {
+        if self.all_tries.is_none() {
+            return true;
+        }
+        let Some(prev) = &self.matched_query else {
+            return true;
+        };
+        let query = self.parsed_input();
+        prev.tokens != query.tokens || prev.text != query.text
+    }
+
+    /// Replaces the query with the next older history entry. Returns whether the query
+    /// changed; with `wrap`, stepping past the oldest entry starts over at the newest.
+    pub(crate) fn recall_history(&mut self, wrap: bool) -> bool {
+        let len = self.history.len();
+        if len == 0 {
+            return false;
+        }
+        let mut next = self.history_pos.map_or(0, |p| p + 1);
+        if next >= len {
+            if !wrap {
+                return false;
+            }
+            next = 0;
+        }
+        let Some(query) = self.history.recall(next) else {
+            return false;
+        };
+        self.input_buf = query.to_string();
+        self.history_pos = Some(next);
+        self.cursor = 0;
+        true
+    }
+
+    /// Records a startup phase once; later calls for the same phase are ignored.
+    fn mark_profile(&mut self, phase: &'static str) {
+        if let Some(p) = &mut self.profile
+            && !p.phases().iter().any(|(name, _)| *name == phase)
+        {
+            p.mark(phase);
         }
-        Ok(self.selected.clone())
     }
 
     /// Clears the terminal and moves the cursor to the top-left origin.
@@ -254,47 +976,304 @@ impl TrySelector {
         if self.all_tries.is_some() {
             return;
         }
-        let mut out = Vec::new();
-        if let Ok(entries) = fs::read_dir(&self.base_path) {
-            for e in entries.flatten() {
-                let path = e.path();
-                let Ok(meta) = e.metadata() else { continue };
-                if !meta.is_dir() {
-                    continue;
+        let mut tries = scan_tries(&self.base_path);
+        if let Some(only) = &self.only {
+            tries.retain(|t| only.contains(&t.basename));
+        }
+        self.all_tries = Some(tries);
+    }
+
+    /// Drops the scanned entries so the next refresh rescans the base directory.
+    fn invalidate(&mut self) {
+        self.all_tries = None;
+        self.index = None;
+        self.match_cache = MatchCache::default();
+        self.matches.clear();
+        self.matched_query = None;
+    }
+
+    /// Names of the current matches in display order.
+    #[cfg(test)]
+    pub(crate) fn matched_names(&self) -> Vec<String> {
+        (0..self.matches.len())
+            .filter_map(|pos| self.match_at(pos))
+            .map(|t| t.basename.clone())
+            .collect()
+    }
+
+    /// Status line currently shown under the list.
+    #[cfg(test)]
+    pub(crate) fn status_message(&self) -> Option<&str> {
+        self.status.current().map(|(m, _)| m.text.as_str())
+    }
+
+    /// Matching entry at display position `pos`.
+    fn match_at(&self, pos: usize) -> Option<&TryDir> {
+        let idx = *self.matches.get(pos)?;
+        self.all_tries.as_ref()?.get(idx)
+    }
+
+    /// Recomputes `matches` for the current query, scoring entries in place. When the query
+    /// only grew (same filter tokens, text extended), just the previous matches are re-scored:
+    /// a fuzzy match for the longer text is always a match for its prefix.
+    pub(crate) fn refresh_matches(&mut self) {
+        self.load_all();
+        let query = self.parsed_input();
+        // Collapsed entries are missing from the previous matches, so start over
+        let narrow = self.hidden_older == 0
+            && self.matched_query.as_ref().is_some_and(|prev| {
+                prev.tokens == query.tokens
+                    && query
+                        .text
+                        .to_lowercase()
+                        .starts_with(&prev.text.to_lowercase())
+            });
+        // Reuse the previous allocation either way
+        let mut matches = std::mem::take(&mut self.matches);
+        if !narrow {
+            matches.clear();
+            let all = self.all_tries.as_deref().unwrap_or_default();
+            if query.text.is_empty() {
+                matches.extend(0..all.len());
+            } else {
+                // Only names holding every query character can match
+                let index = self.index.get_or_insert_with(|| {
+                    CharIndex::build(all.iter().map(|t| t.basename.as_str()))
+                });
+                matches.extend(index.candidates(&query.text));
+            }
+        }
+        self.ensure_metadata(
+            &matches,
+            query.filters.iter().any(Filter::needs_size),
+            query.filters.iter().any(Filter::needs_git),
+        );
+        let sectioned = self.sectioned();
+        let all = self.all_tries.as_mut().expect("loaded above");
+        let cache = &mut self.match_cache;
+        cache.retarget(&query.text, all.len());
+        matches.retain(|&i| {
+            let t = &mut all[i];
+            if !query.filters.iter().all(|f| f.matches(t)) {
+                return false;
+            }
+            t.score = score_try(t, &query.text, cache.state(i));
+            query.text.is_empty() || t.score > 0.0
+        });
+        let now = SystemTime::now();
+        if sectioned {
+            // Group by recency first so each section is contiguous
+            matches.sort_by(|&a, &b| {
+                Recency::of(&all[a], now)
+                    .cmp(&Recency::of(&all[b], now))
+                    .then_with(|| compare_ranked(&all[a], &all[b], &self.tie_break))
+            });
+        } else if query.text.is_empty() && query.filters.contains(&Filter::PendingWork) {
+            // Most unfinished work first when browsing dirty tries
+            matches.sort_by(|&a, &b| {
+                pending_work(&all[b])
+                    .cmp(&pending_work(&all[a]))
+                    .then_with(|| compare_ranked(&all[a], &all[b], &self.tie_break))
+            });
+        } else if self.mode == ListMode::Lines {
+            // Stable sort: equally scored lines keep their input order
+            matches.sort_by(|&a, &b| all[b].score.total_cmp(&all[a].score));
+        } else {
+            // The try last picked for this query leads, then the usual ranking
+            let picked = self.history.picked(&self.input_buf);
+            let is_picked = |i: usize| picked == Some(all[i].basename.as_str());
+            matches.sort_by(|&a, &b| {
+                is_picked(b)
+                    .cmp(&is_picked(a))
+                    .then_with(|| compare_ranked(&all[a], &all[b], &self.tie_break))
+            });
+        }
+        self.sections.clear();
+        self.hidden_older = 0;
+        if sectioned {
+            for (pos, &i) in matches.iter().enumerate() {
+                let recency = Recency::of(&all[i], now);
+                if self.sections.last().is_none_or(|&(_, r)| r != recency) {
+                    self.sections.push((pos, recency));
+                }
+            }
+            // Only collapse when something recent stays visible
+            if self.collapse_older
+                && !self.older_expanded
+                && let Some(&(start, Recency::Older)) = self.sections.last()
+                && start > 0
+            {
+                self.hidden_older = matches.len() - start;
+                matches.truncate(start);
+            }
+        }
+        self.matches = matches;
+        self.matched_query = Some(query);
+        if let (Some(tasks), Some(all)) = (&mut self.tasks, &self.all_tries)
+            && tasks.has_pending()
+        {
+            // Keep workers on what can still be shown
+            let shown: HashSet<&Path> = self
+                .matches
+                .iter()
+                .map(|&i| all[i].path.as_path())
+                .collect();
+            tasks.cancel_unless(|path| shown.contains(path));
+        }
+    }
+
+    /// Fills in sizes and/or git status for the entries at `indices` from the caches; used for
+    /// visible rows and for filters that need metadata up front.
+    fn ensure_metadata(&mut self, indices: &[usize], sizes: bool, git: bool) {
+        if !sizes && !git {
+            return;
+        }
+        let Some(mut all) = self.all_tries.take() else {
+            return;
+        };
+        for &i in indices {
+            let t = &mut all[i];
+            if sizes {
+                self.ensure_size_calculated(t);
+            }
+            if git {
+                self.ensure_git_status(t);
+            }
+        }
+        self.all_tries = Some(all);
+    }
+
+    /// Fills the match rows in `rows` from the caches and queues background jobs for
+    /// whatever is still missing, nearest the cursor first; jobs for rows that have scrolled
+    /// out of `rows` drop behind them. Results arrive through
+    /// [`Self::apply_task_results`].
+    fn queue_metadata(&mut self, rows: Range<usize>) {
+        let (Some(tasks), Some(all)) = (&mut self.tasks, &mut self.all_tries) else {
+            return;
+        };
+        // Pending rows still in range get their priority back as they are queued again
+        tasks.defer_waiting();
+        for row in rows {
+            let Some(t) = self.matches.get(row).and_then(|&i| all.get_mut(i)) else {
+                continue;
+            };
+            let priority = row.abs_diff(self.cursor);
+            if t.size.is_none() {
+                match self.size_cache.get(&t.path) {
+                    Some(&size) => t.size = Some(size),
+                    None => {
+                        tasks.spawn(TaskKind::Size, &t.path, priority, |p, cancel| {
+                            let usage = crate::util::dir_usage_until(p, cancel.flag())?;
+                            Some(TaskOutput::Size(usage.1))
+                        });
+                    }
                 }
-                let basename = e.file_name().to_string_lossy().to_string();
-                if basename == ".try_trash" {
-                    continue;
+            }
+            if t.git.is_none() {
+                match self.git_cache.get(&t.path) {
+                    Some(status) => t.git = status.clone(),
+                    None => {
+                        tasks.spawn(TaskKind::Git, &t.path, priority, |p, cancel| {
+                            let status = crate::git::read_status_until(p, cancel);
+                            (!cancel.is_canceled()).then_some(TaskOutput::Git(status))
+                        });
+                    }
                 }
-                let ctime = meta.created().ok();
-                let mtime = meta.modified().ok();
-                out.push(TryDir {
-                    basename,
-                    path,
-                    ctime,
-                    mtime,
-                    score: 0.0,
-                    size: None, // Calculated lazily during render
-                });
             }
         }
-        self.all_tries = Some(out);
     }
 
-    fn get_tries(&mut self) -> Vec<TryDir> {
-        self.load_all();
-        let mut tries = self.all_tries.clone().unwrap_or_default();
-        for t in &mut tries {
-            t.score = calculate_score(&t.basename, &self.input_buf, t.ctime, t.mtime);
+    /// Moves finished background results into the caches and scanned entries. Returns
+    /// whether anything arrived.
+    pub(crate) fn apply_task_results(&mut self) -> bool {
+        let Some(tasks) = &mut self.tasks else {
+            return false;
+        };
+        let done = tasks.drain();
+        for r in &done {
+            let entry = self
+                .all_tries
+                .as_mut()
+                .and_then(|all| all.iter_mut().find(|t| t.path == r.path));
+            match &r.output {
+                TaskOutput::Size(size) => {
+                    self.size_cache.insert(r.path.clone(), *size);
+                    if let Some(t) = entry {
+                        t.size = Some(*size);
+                    }
+                }
+                TaskOutput::Git(status) => {
+                    self.git_cache.insert(r.path.clone(), status.clone());
+                    if let Some(t) = entry {
+                        t.git = status.clone();
+                    }
+                }
+                // Queued again if the entry matches once more
+                TaskOutput::Canceled(_) => {}
+            }
         }
-        if self.input_buf.is_empty() {
-            tries.sort_by(|a, b| b.score.total_cmp(&a.score));
-            tries
+        !done.is_empty()
+    }
+
+    /// Free text of the query with filter tokens removed.
+    fn query_text(&self) -> String {
+        self.parsed_input().text
+    }
+
+    /// Shell-style completion: the first Tab extends the query to the longest common prefix of
+    /// the current matches (ignoring date prefixes); further Tabs cycle through the top matches.
+    /// Filter tokens in the query are kept.
+    pub(crate) fn complete_query(&mut self) {
+        if let Some(cycle) = &mut self.tab_cycle {
+            self.input_buf = cycle.candidates[cycle.next].clone();
+            cycle.next = (cycle.next + 1) % cycle.candidates.len();
+            self.cursor = 0;
+            return;
+        }
+        let mut candidates: Vec<String> = Vec::new();
+        for pos in 0..self.matches.len().min(TAB_CYCLE_LIMIT) {
+            let Some(t) = self.match_at(pos) else { break };
+            let name = match self.mode {
+                ListMode::Tries => sanitize_query(
+                    split_date_prefixed(&t.basename).map_or(t.basename.as_str(), |(_, r)| r),
+                ),
+                ListMode::Lines => t.basename.clone(),
+            };
+            if !name.is_empty() && !candidates.contains(&name) {
+                candidates.push(name);
+            }
+        }
+        if candidates.is_empty() {
+            return;
+        }
+        let names: Vec<&str> = candidates.iter().map(String::as_str).collect();
+        let prefix = longest_common_prefix(&names);
+        let query = self.parsed_input();
+        let mut cycle = TabCycle {
+            candidates: candidates.iter().map(|c| query.with_text(c)).collect(),
+            next: 0,
+        };
+        if prefix.len() > query.text.len() {
+            self.input_buf = query.with_text(&prefix);
         } else {
-            let mut filtered: Vec<_> = tries.into_iter().filter(|t| t.score > 0.0).collect();
-            filtered.sort_by(|a, b| b.score.total_cmp(&a.score));
-            filtered
+            self.input_buf = cycle.candidates[0].clone();
+            cycle.next = 1 % cycle.candidates.len();
         }
+        self.cursor = 0;
+        self.tab_cycle = Some(cycle);
+    }
+
+    /// Live validation of the current query as a new try name; `None` while the query is blank.
+    fn check_input_name(&self) -> Option<NameCheck> {
+        let text = self.query_text();
+        if text.trim().is_empty() || self.mode == ListMode::Lines {
+            return None;
+        }
+        Some(check_new_name(self.entry_names(), &text))
+    }
+
+    fn entry_names(&self) -> impl Iterator<Item = &str> {
+        self.all_tries.iter().flatten().map(|t| t.basename.as_str())
     }
 
     /// Ensures the size is calculated for a TryDir, using cache if available.
@@ -311,128 +1290,412 @@ impl TrySelector {
         t.size = Some(size);
     }
 
-    fn handle_select_existing(&mut self, t: &TryDir) {
+    /// Header summary of the whole workspace, summing sizes already in the cache.
+    fn workspace_stats(&self) -> String {
+        let all = self.all_tries.as_deref().unwrap_or_default();
+        if self.mode == ListMode::Lines {
+            return format!("{} lines", all.len());
+        }
+        let known: Vec<u64> = all
+            .iter()
+            .filter_map(|t| self.size_cache.get(&t.path).copied())
+            .collect();
+        tui::format_workspace_stats(
+            all.len(),
+            known.len(),
+            known.iter().sum(),
+            &tilde_path(&self.base_path),
+        )
+    }
+
+    /// Suggests cleaning up once the sizes in the cache add up to `size_warning`, e.g.
+    /// "23.1G across 140 tries — `try dedupe` or Ctrl-A archive frees space".
+    fn size_notice(&self) -> Option<String> {
+        if self.mode == ListMode::Lines || self.size_warning == 0 {
+            return None;
+        }
+        let all = self.all_tries.as_deref().unwrap_or_default();
+        let bytes: u64 = all
+            .iter()
+            .filter_map(|t| self.size_cache.get(&t.path))
+            .sum();
+        (bytes >= self.size_warning).then(|| {
+            let noun = if all.len() == 1 { "try" } else { "tries" };
+            format!(
+                "{} across {} {noun} — `try dedupe` or Ctrl-A archive frees space",
+                crate::util::format_human_size(bytes),
+                all.len()
+            )
+        })
+    }
+
+    /// Ensures git status is collected for a TryDir, using cache if available.
+    fn ensure_git_status(&mut self, t: &mut TryDir) {
+        if t.git.is_some() {
+            return;
+        }
+        let status = self
+            .git_cache
+            .entry(t.path.clone())
+            .or_insert_with(|| crate::git::read_status(&t.path));
+        t.git = status.clone();
+    }
+
+    fn handle_select_existing(&mut self, path: PathBuf) {
         self.selected = Some(Selection {
             kind: ActionType::Cd,
-            path: Some(t.path.clone()),
+            path: Some(path),
         });
     }
 
-    fn prompt_new_name(&mut self, err: &mut io::Stderr) -> Result<()> {
-        // flip to cooked for line input
-        crossterm::terminal::disable_raw_mode()?;
-        crossterm::execute!(err, crossterm::cursor::Show)?;
-        crossterm::execute!(
-            err,
-            crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
-            crossterm::cursor::MoveTo(0, 0)
-        )?;
-        tui::styled(
-            err,
-            crossterm::style::Attribute::Bold,
-            Some(crossterm::style::Color::Cyan),
-            "Enter new try name",
-        )?;
-        writeln!(err)?;
-        let prefix = crate::util::today_prefix();
-        write!(err, "> ")?;
-        tui::dim(err, &format!("{prefix}-"))?;
-        err.flush()?;
-
-        let mut line = String::new();
-        io::stdin().read_line(&mut line)?;
-        let line = line.trim();
-
-        crossterm::terminal::enable_raw_mode()?;
-        crossterm::execute!(err, crossterm::cursor::Hide)?;
+    /// Selects a new try named after the query text, or opens the name prompt when the query
+    /// is blank. Returns whether a selection was made.
+    pub(crate) fn create_from_query(&mut self, con: &mut Console<'_>) -> Result<bool> {
+        if let Some(blocked) = self.read_only {
+            self.status.push(Severity::Warn, read_only_status(blocked));
+            return Ok(false);
+        }
+        let text = self.query_text();
+        if text.trim().is_empty() {
+            self.prompt_new_name(con)?;
+        } else {
+            self.create_named(con, new_try_name(&text))?;
+        }
+        Ok(self.selected.is_some())
+    }
 
-        if line.is_empty() {
+    /// Creates the try `name` through [`Self::choose_new`], unless it collides with an
+    /// existing try once dates are ignored; then `names.collision` decides between opening
+    /// that try, a suffixed name, refusing, or asking.
+    fn create_named(&mut self, con: &mut Console<'_>, name: String) -> Result<()> {
+        let tries = self.all_tries.as_deref().unwrap_or_default();
+        let Some(taken) = name_collision(tries, &name).cloned() else {
+            self.choose_new(self.base_path.join(name));
             return Ok(());
+        };
+        let suffixed = suffixed_name(tries, &name);
+        match collision_policy() {
+            CollisionPolicy::Reuse => self.handle_select_existing(taken.path),
+            CollisionPolicy::Suffix => self.choose_new(self.base_path.join(suffixed)),
+            CollisionPolicy::Fail => {
+                let msg = trf(
+                    Msg::NameTaken,
+                    &[("name", &name), ("existing", &taken.basename)],
+                );
+                self.status.push(Severity::Error, msg);
+            }
+            CollisionPolicy::Ask if self.assume_yes => self.choose_new(self.base_path.join(name)),
+            CollisionPolicy::Ask => {
+                self.painted = None;
+                let label = trf(Msg::NameTakenChoice, &[("suffixed", &suffixed)]);
+                let mut prompt = tui::Prompt::new(tr(Msg::NameTakenTitle));
+                prompt.body = vec![trf(
+                    Msg::NameTakenAsk,
+                    &[("name", &name), ("existing", &taken.basename)],
+                )];
+                prompt.label = &label;
+                match tui::prompt_line(con, &prompt)?.as_deref() {
+                    Some("" | "o") => self.handle_select_existing(taken.path),
+                    Some("n") => self.choose_new(self.base_path.join(name)),
+                    Some("s") => self.choose_new(self.base_path.join(suffixed)),
+                    _ => {}
+                }
+            }
         }
-        let final_name = format!("{prefix}-{line}").replace(char::is_whitespace, "-");
-        let full = self.base_path.join(final_name);
-        self.selected = Some(Selection {
-            kind: ActionType::Mkdir,
-            path: Some(full),
-        });
         Ok(())
     }
 
-    fn confirm_and_delete(&mut self, err: &mut io::Stderr, t: &TryDir) -> Result<bool> {
-        // Compute size and file count recursively
-        let (mut files, mut bytes) = (0u64, 0u64);
-        fn walk(p: &Path, files: &mut u64, bytes: &mut u64) {
-            if let Ok(md) = std::fs::symlink_metadata(p) {
-                if md.is_file() {
-                    *files += 1;
-                    *bytes += md.len();
-                } else if md.is_dir()
-                    && let Ok(rd) = std::fs::read_dir(p)
-                {
-                    for e in rd.flatten() {
-                        walk(&e.path(), files, bytes);
-                    }
-                }
+    /// Selects `path` to be created on exit, or with `stay_open_after_create` creates it now
+    /// and highlights it in the list.
+    fn choose_new(&mut self, path: PathBuf) {
+        if !self.stay_open_after_create {
+            self.selected = Some(Selection {
+                kind: ActionType::Mkdir,
+                path: Some(path),
+            });
+            return;
+        }
+        if let Err(e) = fs::create_dir_all(&path) {
+            let msg = Error::from_write(&path, e).to_string();
+            self.status.push(Severity::Error, msg);
+            return;
+        }
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if let Some(only) = &mut self.only {
+            only.insert(name.clone());
+        }
+        self.invalidate();
+        self.refresh_matches();
+        self.cursor = (0..self.matches.len())
+            .find(|&pos| self.match_at(pos).is_some_and(|t| t.path == path))
+            .unwrap_or(0);
+        self.status
+            .push(Severity::Info, trf(Msg::Created, &[("name", &name)]));
+        self.created.push(path);
+    }
+
+    fn prompt_new_name(&mut self, con: &mut Console<'_>) -> Result<()> {
+        self.painted = None;
+        let prefix = crate::util::today_prefix();
+        let feedback = |input: &str| {
+            if input.trim().is_empty() {
+                return Vec::new();
             }
+            name_feedback(&check_new_name(self.entry_names(), input))
+        };
+        let mut prompt = tui::Prompt::new(tr(Msg::NewNamePrompt));
+        prompt.prefix = format!("{prefix}-");
+        prompt.feedback = Some(&feedback);
+        let Some(line) = tui::prompt_line(con, &prompt)? else {
+            return Ok(());
+        };
+        if normalize_query_for_match(&line).is_empty() {
+            return Ok(());
         }
-        walk(&t.path, &mut files, &mut bytes);
+        self.create_named(con, new_try_name(&line))
+    }
 
-        // Switch to cooked mode for line input
-        crossterm::terminal::disable_raw_mode()?;
-        crossterm::execute!(err, crossterm::cursor::Show)?;
-        crossterm::execute!(
-            err,
-            crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
-            crossterm::cursor::MoveTo(0, 0)
-        )?;
-        tui::styled(
-            err,
-            crossterm::style::Attribute::Bold,
-            Some(crossterm::style::Color::Cyan),
-            "Delete Directory",
-        )?;
-        writeln!(err)?;
-        writeln!(err)?;
-        write!(
-            err,
-            "Are you sure you want to delete: {}\r\n  in {}\r\n  files: {} files\r\n  size: {}\r\n\r\n",
-            t.basename,
-            t.path.display(),
-            files,
-            crate::util::format_human_size(bytes)
-        )?;
-        tui::styled(
-            err,
-            crossterm::style::Attribute::Bold,
-            Some(crossterm::style::Color::Yellow),
-            "Type ",
-        )?;
-        tui::styled(
-            err,
-            crossterm::style::Attribute::Reset,
-            Some(crossterm::style::Color::Reset),
-            "YES",
-        )?;
-        tui::styled(
-            err,
-            crossterm::style::Attribute::Bold,
-            Some(crossterm::style::Color::Yellow),
-            " to confirm: ",
-        )?;
-        err.flush()?;
+    /// Asks for confirmation before `t` is archived and removed from the tries directory,
+    /// the same way deletion does; `assume_yes` answers yes.
+    fn confirm_archive(&self, con: &mut Console<'_>, t: &TryDir) -> Result<bool> {
+        if self.assume_yes {
+            return Ok(true);
+        }
+        let (files, bytes) = crate::util::dir_usage(&t.path);
+        let size = crate::util::format_human_size(bytes);
+        let body = vec![
+            trf(Msg::ArchiveAsk, &[("name", &t.basename)]),
+            format!("  in {}", tilde_path(&t.path)),
+            format!("  to {}", tilde_path(&self.archive_dir)),
+            trf(Msg::DeleteFiles, &[("count", &files)]),
+            trf(Msg::DeleteSize, &[("size", &size)]),
+        ];
+        tui::confirm(con, tr(Msg::ArchiveTitle), body, Vec::new(), "YES")
+    }
+
+    /// Archives `t` into `archive_dir`, drawing a progress bar on the status line meanwhile.
+    fn archive_with_progress(&self, con: &mut Console<'_>, t: &TryDir) -> Result<PathBuf> {
+        let label = trf(Msg::Archiving, &[("name", &t.basename)]);
+        let mut shown = None;
+        let started = Instant::now();
+        let dest = archive_try(&t.path, &self.archive_dir, |done, total| {
+            let percent = (done * 100).checked_div(total).unwrap_or(100);
+            // Redraw only when the number changes
+            if shown != Some(percent) {
+                shown = Some(percent);
+                let _ = tui::draw_progress(con, self.term_h, &label, percent);
+            }
+        })?;
+        forget_pending_clone(&self.base_path, &t.path);
+        crate::notify::notify_if_slow(started, &format!("Archived {}", t.basename));
+        Ok(dest)
+    }
+
+    /// Asks for confirmation and deletes `t`. The inner result is whether it was deleted, or
+    /// a status line explaining why deletion failed.
+    fn confirm_and_delete(
+        &mut self,
+        con: &mut Console<'_>,
+        t: &TryDir,
+    ) -> Result<std::result::Result<bool, String>> {
+        let (files, bytes) = crate::util::dir_usage(&t.path);
+
+        let size = crate::util::format_human_size(bytes);
+        let body = vec![
+            trf(Msg::DeleteAsk, &[("name", &t.basename)]),
+            format!("  in {}", tilde_path(&t.path)),
+            trf(Msg::DeleteFiles, &[("count", &files)]),
+            trf(Msg::DeleteSize, &[("size", &size)]),
+        ];
+        let warnings = crate::git::deletion_warnings(&t.path);
+        if self.assume_yes || tui::confirm(con, tr(Msg::DeleteTitle), body, warnings, "YES")? {
+            Ok(remove_try(&self.base_path, t).map(|()| true))
+        } else {
+            Ok(Ok(false))
+        }
+    }
 
-        let mut line = String::new();
-        std::io::stdin().read_line(&mut line)?;
+    /// Deletes every marked try after one combined confirmation listing their names and
+    /// total usage. Protected tries are left alone; they and every failed deletion are
+    /// queued on the status line after a summary.
+    fn delete_marked(&mut self, con: &mut Console<'_>) -> Result<()> {
+        // The confirmation draws over the frame
+        self.painted = None;
+        if let Some(blocked) = self.read_only {
+            self.status.push(Severity::Warn, read_only_status(blocked));
+            return Ok(());
+        }
+        let all = self.all_tries.as_deref().unwrap_or_default();
+        // Marks of tries that are gone since are dropped
+        let (protected, tries): (Vec<TryDir>, Vec<TryDir>) = self
+            .marked
+            .iter()
+            .filter_map(|path| all.iter().find(|t| t.path == *path).cloned())
+            .partition(|t| !self.force && is_protected(&t.path));
+        let mut failures: Vec<String> = protected
+            .iter()
+            .map(|t| protected_status(t, "delete"))
+            .collect();
+        if tries.is_empty() {
+            self.marked.clear();
+            for failure in failures {
+                self.status.push(Severity::Warn, failure);
+            }
+            return Ok(());
+        }
 
-        crossterm::terminal::enable_raw_mode()?;
-        crossterm::execute!(err, crossterm::cursor::Hide)?;
+        let (mut files, mut bytes) = (0, 0);
+        for t in &tries {
+            let (f, b) = crate::util::dir_usage(&t.path);
+            files += f;
+            bytes += b;
+        }
+        let mut body = vec![trf(Msg::DeleteManyAsk, &[("count", &tries.len())])];
+        body.extend(
+            tries
+                .iter()
+                .take(MAX_LISTED_MARKS)
+                .map(|t| format!("  {}", t.basename)),
+        );
+        if tries.len() > MAX_LISTED_MARKS {
+            let more = tries.len() - MAX_LISTED_MARKS;
+            body.push(trf(Msg::DeleteMore, &[("count", &more)]));
+        }
+        let size = crate::util::format_human_size(bytes);
+        body.push(trf(Msg::DeleteFiles, &[("count", &files)]));
+        body.push(trf(Msg::DeleteSize, &[("size", &size)]));
+        if !protected.is_empty() {
+            body.push(trf(Msg::DeleteSkipping, &[("count", &protected.len())]));
+        }
+        let warnings = tries
+            .iter()
+            .flat_map(|t| {
+                crate::git::deletion_warnings(&t.path)
+                    .into_iter()
+                    .map(|w| format!("{}: {w}", t.basename))
+            })
+            .collect();
+        if !self.assume_yes && !tui::confirm(con, tr(Msg::DeleteManyTitle), body, warnings, "YES")?
+        {
+            self.status.push(Severity::Info, tr(Msg::DeleteCancelled));
+            return Ok(());
+        }
 
-        if line.trim() == "YES" {
-            // Hard delete
-            let _ = std::fs::remove_dir_all(&t.path);
-            Ok(true)
+        let total = tries.len() + protected.len();
+        let mut deleted = 0;
+        for t in &tries {
+            match remove_try(&self.base_path, t) {
+                Ok(()) => deleted += 1,
+                Err(msg) => failures.push(msg),
+            }
+        }
+        self.marked.clear();
+        self.invalidate();
+        if failures.is_empty() {
+            self.status.push(
+                Severity::Info,
+                trf(Msg::DeletedMany, &[("count", &deleted)]),
+            );
         } else {
-            Ok(false)
+            let summary = trf(
+                Msg::DeletedSome,
+                &[
+                    ("deleted", &deleted),
+                    ("total", &total),
+                    ("failed", &failures.len()),
+                ],
+            );
+            self.status.push(Severity::Error, summary);
+            for failure in failures {
+                self.status.push(Severity::Error, failure);
+            }
         }
+        Ok(())
+    }
+}
+
+/// Opens the git remote of `t` in the browser, returning a status line describing the outcome.
+fn open_remote(t: &TryDir) -> String {
+    match crate::git::browse_url(&t.path) {
+        Ok(url) => match crate::util::open_with_system(&url) {
+            Ok(()) => format!("Opened {url}"),
+            Err(e) => format!("Could not open {url}: {e}"),
+        },
+        Err(msg) => msg,
+    }
+}
+
+/// Deletes `t` for good, along with any unfinished-clone marker under `base_path`, or explains
+/// why it could not be removed.
+fn remove_try(base_path: &Path, t: &TryDir) -> std::result::Result<(), String> {
+    crate::mounts::remove_tree(&t.path).map_err(|e| Error::from_write(&t.path, e).to_string())?;
+    forget_pending_clone(base_path, &t.path);
+    Ok(())
+}
+
+/// Status line shown when a protected try is not deleted or archived.
+fn protected_status(t: &TryDir, action: &str) -> String {
+    format!(
+        "Protected: {} has a {KEEP_MARKER} marker; rerun with --force to {action} it",
+        t.basename
+    )
+}
+
+/// Status line shown while the selector is read-only.
+fn read_only_status(blocked: WriteBlocked) -> String {
+    format!("Read-only: {}", blocked.hint())
+}
+
+/// Uncommitted changes plus unpushed commits, used to rank `@dirty` results.
+fn pending_work(t: &TryDir) -> usize {
+    t.git.as_ref().map_or(0, |g| g.changes + g.ahead)
+}
+
+/// Keys that keep history recall going (Up and Ctrl-R); anything else ends it.
+fn is_history_key(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::Key(KeyEvent {
+            code: KeyCode::Up,
+            ..
+        }) | Event::Key(KeyEvent {
+            code: KeyCode::Char('r'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        })
+    )
+}
+
+/// The earlier of an optional poll timeout (`None` waits indefinitely) and `wait`.
+fn shorter(timeout: Option<Duration>, wait: Duration) -> Option<Duration> {
+    Some(timeout.map_or(wait, |t| t.min(wait)))
+}
+
+/// Whether a key with `mods` should be treated as typed text (Shift is allowed for capitals).
+fn is_text_input(mods: KeyModifiers) -> bool {
+    !mods.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+}
+
+/// Status hint naming characters that were dropped from a name.
+pub(crate) fn rejected_char_hint(rejected: &[char]) -> String {
+    let list: Vec<String> = rejected.iter().map(|c| format!("'{c}'")).collect();
+    format!(
+        "Ignored {}: names allow letters, digits, '-', '_', '.' and spaces",
+        list.join(" ")
+    )
+}
+
+/// Prompt feedback lines for a live name check: final name preview, rejected chars, collisions.
+pub(crate) fn name_feedback(check: &NameCheck) -> Vec<Feedback> {
+    let mut out = vec![Feedback::Info(format!("Creates: {}", check.final_name))];
+    if !check.rejected.is_empty() {
+        out.push(Feedback::Warn(rejected_char_hint(&check.rejected)));
+    }
+    if let Some(existing) = &check.collision {
+        out.push(Feedback::Warn(format!("Already exists: {existing}")));
     }
+    out
 }
@@ -0,0 +1,315 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+
+/// Dialect of the commands printed for the shell wrapper to eval.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Shell {
+    /// sh, bash, zsh, and other POSIX shells.
+    Posix,
+    Fish,
+    /// Nushell.
+    Nu,
+    /// PowerShell 7 or later.
+    Pwsh,
+}
+
+static DIALECT: OnceLock<Shell> = OnceLock::new();
+static HOME_RELATIVE: OnceLock<bool> = OnceLock::new();
+
+/// Sets the process-wide `shell.dialect`; only the first call takes effect.
+pub(crate) fn set_dialect(shell: Shell) {
+    let _ = DIALECT.set(shell);
+}
+
+/// Sets the process-wide `shell.home_relative`; only the first call takes effect.
+pub(crate) fn set_home_relative(on: bool) {
+    let _ = HOME_RELATIVE.set(on);
+}
+
+impl Shell {
+    /// The dialect in effect: `shell.dialect` when set, else fish when `$SHELL` names it,
+    /// else POSIX.
+    pub(crate) fn current() -> Self {
+        DIALECT.get().copied().unwrap_or_else(|| {
+            if crate::util::is_fish_shell() {
+                Shell::Fish
+            } else {
+                Shell::Posix
+            }
+        })
+    }
+
+    /// Name of the dialect as written in `shell.dialect`.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Shell::Posix => "posix",
+            Shell::Fish => "fish",
+            Shell::Nu => "nu",
+            Shell::Pwsh => "pwsh",
+        }
+    }
+
+    /// `s` as a single literal word.
+    pub(crate) fn quote(self, s: &str) -> String {
+        match self {
+            Shell::Posix => format!("'{}'", s.replace('\'', r"'\''")),
+            Shell::Fish => format!("'{}'", s.replace('\\', r"\\").replace('\'', r"\'")),
+            Shell::Nu if !s.contains('\'') => format!("'{s}'"),
+            Shell::Nu => {
+                // Raw string with more `#` than any `'#…` run inside
+                let mut hashes = String::from("#");
+                while s.contains(&format!("'{hashes}")) {
+                    hashes.push('#');
+                }
+                format!("r{hashes}'{s}'{hashes}")
+            }
+            Shell::Pwsh => {
+                // PowerShell also ends single-quoted strings at typographic quotes
+                let mut out = String::from("'");
+                for c in s.chars() {
+                    if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}') {
+                        out.push(c);
+                    }
+                    out.push(c);
+                }
+                out.push('\'');
+                out
+            }
+        }
+    }
+
+    /// `path` as one word; below `home` it is written relative to the home variable.
+    pub(crate) fn home_path(self, path: &Path, home: Option<&Path>) -> String {
+        let Some(rest) = home.and_then(|h| path.strip_prefix(h).ok()) else {
+            return self.quote(&path.to_string_lossy());
+        };
+        let home_var = match self {
+            Shell::Posix | Shell::Fish => "\"$HOME\"",
+            Shell::Nu => "$env.HOME",
+            Shell::Pwsh => "$HOME",
+        };
+        if rest.as_os_str().is_empty() {
+            return home_var.to_string();
+        }
+        self.join(home_var, &rest.to_string_lossy())
+    }
+
+    /// The word `base` (already rendered) with the relative path `sub` appended.
+    fn join(self, base: &str, sub: &str) -> String {
+        match self {
+            Shell::Posix | Shell::Fish => format!("{base}/{}", self.quote(sub)),
+            Shell::Nu => format!("({base} | path join {})", self.quote(sub)),
+            Shell::Pwsh => format!("(Join-Path {base} {})", self.quote(sub)),
+        }
+    }
+
+    fn dir_var(self) -> &'static str {
+        match self {
+            Shell::Posix | Shell::Fish => "\"$dir\"",
+            Shell::Nu | Shell::Pwsh => "$dir",
+        }
+    }
+}
+
+/// A word of a pipeline step, rendered for the dialect.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Arg {
+    /// Literal text, quoted.
+    Lit(String),
+    /// Text passed through as is: command names, flags, and numbers.
+    Bare(String),
+    /// A filesystem path, quoted, or relative to `$HOME` with `shell.home_relative`.
+    Path(PathBuf),
+    /// The directory set by [`ShellPipeline::assign_dir`].
+    Dir,
+    /// A relative path below that directory.
+    DirJoin(String),
+}
+
+impl Arg {
+    /// Literal word.
+    pub(crate) fn lit(s: impl Into<String>) -> Self {
+        Arg::Lit(s.into())
+    }
+
+    /// Unquoted word.
+    pub(crate) fn bare(s: impl Into<String>) -> Self {
+        Arg::Bare(s.into())
+    }
+
+    /// Path word.
+    pub(crate) fn path(p: impl Into<PathBuf>) -> Self {
+        Arg::Path(p.into())
+    }
+}
+
+/// Commands for the shell wrapper to eval, chained so a failing step stops the rest. Each
+/// step is rendered for one [`Shell`] dialect as it is added; the first step usually assigns
+/// the target directory to `dir` so later steps can refer to it as [`Arg::Dir`].
+#[derive(Clone, Debug)]
+pub(crate) struct ShellPipeline {
+    shell: Shell,
+    home: Option<PathBuf>,
+    steps: Vec<String>,
+}
+
+impl ShellPipeline {
+    /// Empty pipeline in the dialect in effect, honoring `shell.home_relative`.
+    pub(crate) fn new() -> Self {
+        Self::for_shell(Shell::current())
+    }
+
+    /// Empty pipeline for `shell`, honoring `shell.home_relative`.
+    pub(crate) fn for_shell(shell: Shell) -> Self {
+        let home = HOME_RELATIVE
+            .get()
+            .copied()
+            .unwrap_or(false)
+            .then(dirs::home_dir)
+            .flatten();
+        Self::with(shell, home)
+    }
+
+    /// Empty pipeline for `shell`, writing paths below `home` relative to it.
+    pub(crate) fn with(shell: Shell, home: Option<PathBuf>) -> Self {
+        Self {
+            shell,
+            home,
+            steps: Vec::new(),
+        }
+    }
+
+    /// `dir=<dir>`, in the dialect's own assignment syntax.
+    pub(crate) fn assign_dir(&mut self, dir: &Path) -> &mut Self {
+        let value = self.render(&Arg::path(dir));
+        let step = match self.shell {
+            Shell::Posix => format!("dir={value}"),
+            Shell::Fish => format!("set -l dir {value}"),
+            Shell::Nu => format!("let dir = {value}"),
+            Shell::Pwsh => format!("$dir = {value}"),
+        };
+        self.push(step)
+    }
+
+    /// Creates the directories `dirs`, parents included.
+    pub(crate) fn mkdir(&mut self, dirs: &[Arg]) -> &mut Self {
+        let words: Vec<String> = dirs.iter().map(|a| self.render(a)).collect();
+        let step = match self.shell {
+            Shell::Posix | Shell::Fish => format!("mkdir -p {}", words.join(" ")),
+            Shell::Nu => format!("mkdir {}", words.join(" ")),
+            Shell::Pwsh => format!(
+                "New-Item -ItemType Directory -Force -Path {} | Out-Null",
+                words.join(", ")
+            ),
+        };
+        self.push(step)
+    }
+
+    /// Sets the modification time of `target` to now.
+    pub(crate) fn touch(&mut self, target: Arg) -> &mut Self {
+        let word = self.render(&target);
+        let step = match self.shell {
+            Shell::Posix | Shell::Fish | Shell::Nu => format!("touch {word}"),
+            Shell::Pwsh => format!("(Get-Item {word}).LastWriteTime = Get-Date"),
+        };
+        self.push(step)
+    }
+
+    /// Changes the shell's working directory to `target`.
+    pub(crate) fn cd(&mut self, target: Arg) -> &mut Self {
+        let word = self.render(&target);
+        let step = match self.shell {
+            Shell::Posix | Shell::Fish | Shell::Nu => format!("cd {word}"),
+            Shell::Pwsh => format!("Set-Location {word}"),
+        };
+        self.push(step)
+    }
+
+    /// Writes `text` and a newline to `file`, replacing its contents.
+    pub(crate) fn write_line(&mut self, text: &str, file: Arg) -> &mut Self {
+        let (text, file) = (self.shell.quote(text), self.render(&file));
+        let step = match self.shell {
+            Shell::Posix | Shell::Fish => format!("printf '%s\\n' {text} > {file}"),
+            Shell::Nu => format!("$\"({text})\\n\" | save -f {file}"),
+            Shell::Pwsh => format!("Set-Content -Path {file} -Value {text}"),
+        };
+        self.push(step)
+    }
+
+    /// Removes `file`, if it exists.
+    pub(crate) fn remove_file(&mut self, file: Arg) -> &mut Self {
+        let word = self.render(&file);
+        let step = match self.shell {
+            Shell::Posix | Shell::Fish | Shell::Nu => format!("rm -f {word}"),
+            Shell::Pwsh => format!("Remove-Item -Force -ErrorAction SilentlyContinue {word}"),
+        };
+        self.push(step)
+    }
+
+    /// Sets the environment variable `name` for the rest of the shell session.
+    pub(crate) fn set_env(&mut self, name: &str, value: Arg) -> &mut Self {
+        let value = self.render(&value);
+        let step = match self.shell {
+            Shell::Posix => format!("export {name}={value}"),
+            Shell::Fish => format!("set -gx {name} {value}"),
+            Shell::Nu => format!("$env.{name} = {value}"),
+            Shell::Pwsh => format!("$env:{name} = {value}"),
+        };
+        self.push(step)
+    }
+
+    /// Runs the external program `command[0]` with the remaining words as arguments.
+    pub(crate) fn run(&mut self, command: &[Arg]) -> &mut Self {
+        let mut words: Vec<String> = command.iter().map(|a| self.render(a)).collect();
+        // A quoted program name is a string to nu and PowerShell unless marked as a call
+        if let (Some(Arg::Path(_) | Arg::Lit(_)), Some(first)) =
+            (command.first(), words.first_mut())
+        {
+            match self.shell {
+                Shell::Nu => first.insert(0, '^'),
+                Shell::Pwsh => first.insert_str(0, "& "),
+                Shell::Posix | Shell::Fish => {}
+            }
+        }
+        self.push(words.join(" "))
+    }
+
+    /// The rendered steps, in order.
+    #[cfg(test)]
+    pub(crate) fn steps(&self) -> &[String] {
+        &self.steps
+    }
+
+    /// All steps as one line that stops at the first failure.
+    pub(crate) fn line(&self) -> String {
+        match self.shell {
+            Shell::Posix | Shell::Fish => self.steps.join(" && "),
+            // Nushell stops at a failing external command on its own
+            Shell::Nu => self.steps.join("; "),
+            // An assignment would take a following `&&` chain as its value, so each step is
+            // a statement of its own, run only when the one before it succeeded
+            Shell::Pwsh => {
+                let mut line = self.steps.join("; if ($?) { ");
+                line.push_str(&" }".repeat(self.steps.len().saturating_sub(1)));
+                line
+            }
+        }
+    }
+
+    fn render(&self, arg: &Arg) -> String {
+        match arg {
+            Arg::Lit(s) => self.shell.quote(s),
+            Arg::Bare(s) => s.clone(),
+            Arg::Path(p) => self.shell.home_path(p, self.home.as_deref()),
+            Arg::Dir => self.shell.dir_var().to_string(),
+            Arg::DirJoin(sub) => self.shell.join(self.shell.dir_var(), sub),
+        }
+    }
+
+    fn push(&mut self, step: String) -> &mut Self {
+        self.steps.push(step);
+        self
+    }
+}
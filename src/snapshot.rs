@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::diff::{TreeDiff, list_files};
+use crate::storage::META_DIR;
+
+const SNAPSHOT_DIR: &str = "snapshots";
+const MANIFEST_EXT: &str = "manifest";
+const MANIFEST_HEADER: &str = "# try snapshot v1";
+const READ_CHUNK: usize = 64 * 1024;
+// FNV-1a (64-bit): stable across releases, unlike std's hasher. It detects edits, not tampering.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Recorded state of each file in a try: size and content hash, by relative path.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Manifest {
+    pub files: BTreeMap<PathBuf, (u64, u64)>,
+}
+
+impl Manifest {
+    /// Hashes every file under `root`, skipping the same folders as `try diff`.
+    pub(crate) fn capture(root: &Path) -> io::Result<Self> {
+        let mut files = BTreeMap::new();
+        for (rel, size) in list_files(root) {
+            files.insert(rel.clone(), (size, hash_file(&root.join(&rel))?));
+        }
+        Ok(Self { files })
+    }
+
+    /// Manifest text: a header, then `hash size path` per line in path order.
+    pub(crate) fn to_text(&self) -> String {
+        let mut out = format!("{MANIFEST_HEADER}\n");
+        for (path, (size, hash)) in &self.files {
+            out.push_str(&format!("{hash:016x} {size} {}\n", path.display()));
+        }
+        out
+    }
+
+    /// Parses manifest text; comment and malformed lines are skipped.
+    pub(crate) fn parse(text: &str) -> Self {
+        let mut files = BTreeMap::new();
+        for line in text.lines().filter(|l| !l.starts_with('#')) {
+            let mut parts = line.splitn(3, ' ');
+            let (Some(hash), Some(size), Some(path)) = (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            if let (Ok(hash), Ok(size)) = (u64::from_str_radix(hash, 16), size.parse()) {
+                files.insert(PathBuf::from(path), (size, hash));
+            }
+        }
+        Self { files }
+    }
+
+    /// Files added, removed, or changed in `current` compared to this snapshot. Removed files
+    /// are reported as `only_left`, added ones as `only_right`.
+    pub(crate) fn compare(&self, current: &Manifest) -> TreeDiff {
+        let mut diff = TreeDiff::default();
+        for (path, entry) in &self.files {
+            match current.files.get(path) {
+                None => diff.only_left.push(path.clone()),
+                Some(now) if now == entry => diff.identical += 1,
+                Some(_) => diff.changed.push(path.clone()),
+            }
+        }
+        diff.only_right = current
+            .files
+            .keys()
+            .filter(|p| !self.files.contains_key(*p))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    pub(crate) fn total_bytes(&self) -> u64 {
+        self.files.values().map(|(size, _)| size).sum()
+    }
+}
+
+/// Where the snapshot of the try named `name` is stored.
+pub(crate) fn manifest_path(base_path: &Path, name: &str) -> PathBuf {
+    base_path
+        .join(META_DIR)
+        .join(SNAPSHOT_DIR)
+        .join(format!("{name}.{MANIFEST_EXT}"))
+}
+
+/// Writes `manifest` as the snapshot of `name`, replacing an earlier one.
+pub(crate) fn save(base_path: &Path, name: &str, manifest: &Manifest) -> io::Result<PathBuf> {
+    let path = manifest_path(base_path, name);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, manifest.to_text())?;
+    Ok(path)
+}
+
+/// The stored snapshot of `name`, or `None` when there is none.
+pub(crate) fn load(base_path: &Path, name: &str) -> Option<Manifest> {
+    let text = fs::read_to_string(manifest_path(base_path, name)).ok()?;
+    Some(Manifest::parse(&text))
+}
+
+fn hash_file(path: &Path) -> io::Result<u64> {
+    let mut file = fs::File::open(path)?;
+    let mut buf = vec![0u8; READ_CHUNK];
+    let mut hash = FNV_OFFSET;
+    loop {
+        let n = match file.read(&mut buf) {
+            Ok(0) => return Ok(hash),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for &byte in &buf[..n] {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME);
+        }
+    }
+}
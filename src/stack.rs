@@ -0,0 +1,153 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::storage::META_DIR;
+
+/// Per-session directory stacks under `.try_meta`, one file per session with one directory
+/// per line, oldest first.
+const STACKS_DIR: &str = "stacks";
+/// Overrides the session a stack belongs to; by default it is the parent process, which is
+/// the interactive shell when `try` runs through the shell function.
+pub(crate) const SESSION_VAR: &str = "TRY_SESSION";
+
+/// The directories `try push` left, for `try pop` to return to.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct DirStack {
+    path: PathBuf,
+    entries: Vec<PathBuf>,
+}
+
+impl DirStack {
+    /// Loads the stack of `session` stored under `base_path`; a missing file is an empty
+    /// stack. Stacks of `ppid-` sessions whose shell has exited are removed on the way.
+    pub(crate) fn load(base_path: &Path, session: &str) -> Self {
+        let dir = base_path.join(META_DIR).join(STACKS_DIR);
+        prune_dead_sessions(&dir, session);
+        Self::read(dir.join(session))
+    }
+
+    fn read(path: PathBuf) -> Self {
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let entries = text
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(PathBuf::from)
+            .collect();
+        Self { path, entries }
+    }
+
+    /// Entries, oldest first.
+    pub(crate) fn entries(&self) -> &[PathBuf] {
+        &self.entries
+    }
+
+    pub(crate) fn push(&mut self, dir: PathBuf) {
+        self.entries.push(dir);
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<PathBuf> {
+        self.entries.pop()
+    }
+
+    /// Writes the stack; an empty stack removes its file.
+    pub(crate) fn save(&self) -> io::Result<()> {
+        if self.entries.is_empty() {
+            return match fs::remove_file(&self.path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let lines: Vec<String> = self
+            .entries
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        fs::write(&self.path, lines.join("\n") + "\n")
+    }
+}
+
+/// Every session's stack under `base_path` with its session key, sorted by key. Stacks of
+/// `ppid-` sessions whose shell has exited are removed first.
+pub(crate) fn all_stacks(base_path: &Path) -> Vec<(String, DirStack)> {
+    let dir = base_path.join(META_DIR).join(STACKS_DIR);
+    prune_dead_sessions(&dir, "");
+    let Ok(rd) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut stacks: Vec<(String, DirStack)> = rd
+        .flatten()
+        .map(|entry| {
+            let session = entry.file_name().to_string_lossy().into_owned();
+            (session, DirStack::read(entry.path()))
+        })
+        .collect();
+    stacks.sort_by(|a, b| a.0.cmp(&b.0));
+    stacks
+}
+
+/// The session key: `$TRY_SESSION` when set, else `ppid-<parent process id>`. Anything but
+/// letters, digits, `-` and `_` is replaced so the key is a safe file name.
+pub(crate) fn session_key() -> String {
+    let key = std::env::var(SESSION_VAR)
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| format!("ppid-{}", parent_pid()));
+    key.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Removes the stack files in `dir` of `ppid-<pid>` sessions other than `current` whose
+/// process no longer runs. Sessions named through `$TRY_SESSION` are kept.
+fn prune_dead_sessions(dir: &Path, current: &str) {
+    let Ok(rd) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in rd.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let pid = name.strip_prefix("ppid-").and_then(|n| n.parse().ok());
+        if name != current && pid.is_some_and(|pid| !pid_alive(pid)) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Whether a process with `pid` exists, even one owned by another user.
+#[cfg(unix)]
+fn pid_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    // SAFETY: signal 0 only checks that the process exists and may be signalled.
+    let alive = unsafe { libc::kill(pid, 0) == 0 };
+    alive || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Without a way to check, every process counts as running, so no stack is removed.
+#[cfg(not(unix))]
+fn pid_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn parent_pid() -> u32 {
+    std::os::unix::process::parent_id()
+}
+
+#[cfg(not(unix))]
+fn parent_pid() -> u32 {
+    0
+}
@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+
+use crate::model::TryDir;
+use crate::util::civil_from_days;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+const DAYS_PER_WEEK: i64 = 7;
+/// 1970-01-01 was a Thursday; weeks start on Monday, four days later.
+const MONDAY_OFFSET: i64 = 4;
+
+/// Label of the row counting the tries that are in no group.
+pub(crate) const UNGROUPED: &str = "(none)";
+
+/// What `try stats` counts tries by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum GroupBy {
+    /// Weeks starting on Monday, labeled by that Monday's date
+    Week,
+    /// Calendar months, labeled `YYYY-MM`
+    #[default]
+    Month,
+    /// Tags set with `try tag`; a try counts under each of its tags
+    Tag,
+    /// Directory stacks of `try push`, by session; a try counts under each stack it is on
+    Stack,
+}
+
+impl GroupBy {
+    /// Name of the label column.
+    pub(crate) fn column(self) -> &'static str {
+        match self {
+            GroupBy::Week => "week",
+            GroupBy::Month => "month",
+            GroupBy::Tag => "tag",
+            GroupBy::Stack => "stack",
+        }
+    }
+}
+
+/// Length of the periods a timeline counts in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Period {
+    Week,
+    Month,
+}
+
+impl Period {
+    /// Number of the period `t` falls in (UTC), counting up without gaps.
+    fn index(self, t: SystemTime) -> i64 {
+        let days = t
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| (d.as_secs() / SECONDS_PER_DAY) as i64);
+        match self {
+            Period::Week => (days - MONDAY_OFFSET).div_euclid(DAYS_PER_WEEK),
+            Period::Month => {
+                let (y, m, _) = civil_from_days(days);
+                i64::from(y) * 12 + i64::from(m) - 1
+            }
+        }
+    }
+
+    /// Label of the period numbered `index`, e.g. `2025-08-25` or `2025-08`.
+    fn label(self, index: i64) -> String {
+        match self {
+            Period::Week => {
+                let (y, m, d) = civil_from_days(index * DAYS_PER_WEEK + MONDAY_OFFSET);
+                format!("{y:04}-{m:02}-{d:02}")
+            }
+            Period::Month => {
+                let (y, m) = (index.div_euclid(12), index.rem_euclid(12) + 1);
+                format!("{y:04}-{m:02}")
+            }
+        }
+    }
+}
+
+/// One row of the histogram.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Bucket {
+    pub label: String,
+    /// Tries created in the period, or all tries of the group.
+    pub created: usize,
+    /// Tries last opened from `try cd` in the period, or those of the group opened at all.
+    pub visited: usize,
+}
+
+/// Creations of `tries` and the `last_visits` from the visit log per `period`, oldest first,
+/// from the first period with either up to the last one. Periods in between with neither
+/// are included with zero counts, so the rows read as a timeline.
+pub(crate) fn histogram(
+    tries: &[TryDir],
+    last_visits: impl IntoIterator<Item = SystemTime>,
+    period: Period,
+) -> Vec<Bucket> {
+    let mut counts: BTreeMap<i64, (usize, usize)> = BTreeMap::new();
+    for created in tries.iter().filter_map(|t| t.ctime) {
+        counts.entry(period.index(created)).or_default().0 += 1;
+    }
+    for visited in last_visits {
+        counts.entry(period.index(visited)).or_default().1 += 1;
+    }
+    let (Some(&first), Some(&last)) = (counts.keys().next(), counts.keys().next_back()) else {
+        return Vec::new();
+    };
+    (first..=last)
+        .map(|index| {
+            let (created, visited) = counts.get(&index).copied().unwrap_or_default();
+            Bucket {
+                label: period.label(index),
+                created,
+                visited,
+            }
+        })
+        .collect()
+}
+
+/// Tries per group, sorted by label with [`UNGROUPED`] last. `groups_of` names the groups of
+/// a try: one in several groups counts in each, one in none under [`UNGROUPED`]. `visited`
+/// tells whether a try was ever opened from `try cd`.
+pub(crate) fn group_counts(
+    tries: &[TryDir],
+    visited: impl Fn(&TryDir) -> bool,
+    groups_of: impl Fn(&TryDir) -> Vec<String>,
+) -> Vec<Bucket> {
+    let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    let mut ungrouped = (0, 0);
+    for t in tries {
+        let seen = usize::from(visited(t));
+        let groups = groups_of(t);
+        if groups.is_empty() {
+            ungrouped.0 += 1;
+            ungrouped.1 += seen;
+        }
+        for group in groups {
+            let count = counts.entry(group).or_default();
+            count.0 += 1;
+            count.1 += seen;
+        }
+    }
+    let mut buckets: Vec<Bucket> = counts
+        .into_iter()
+        .map(|(label, (created, visited))| Bucket {
+            label,
+            created,
+            visited,
+        })
+        .collect();
+    if ungrouped.0 > 0 {
+        buckets.push(Bucket {
+            label: UNGROUPED.to_string(),
+            created: ungrouped.0,
+            visited: ungrouped.1,
+        });
+    }
+    buckets
+}
+
+/// The histogram as CSV with a header row, for spreadsheets.
+pub(crate) fn to_csv(buckets: &[Bucket], by: GroupBy) -> String {
+    let mut out = format!("{},created,visited\n", by.column());
+    for b in buckets {
+        out.push_str(&format!("{},{},{}\n", b.label, b.created, b.visited));
+    }
+    out
+}
+
+/// Bar of `count` scaled so that `max` fills `width` columns; any non-zero count shows at
+/// least one block.
+pub(crate) fn bar(count: usize, max: usize, width: usize) -> String {
+    if count == 0 || max == 0 {
+        return String::new();
+    }
+    "█".repeat((count * width).div_ceil(max).max(1))
+}
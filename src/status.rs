@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long a message stays on the status line once it is shown.
+const INFO_SECS: u64 = 4;
+const WARN_SECS: u64 = 6;
+const ERROR_SECS: u64 = 10;
+
+/// How a status message is styled and how long it stays.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Severity {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Severity {
+    fn duration(self) -> Duration {
+        Duration::from_secs(match self {
+            Severity::Info => INFO_SECS,
+            Severity::Warn => WARN_SECS,
+            Severity::Error => ERROR_SECS,
+        })
+    }
+}
+
+/// One line for the status line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct StatusMessage {
+    pub severity: Severity,
+    pub text: String,
+}
+
+/// Messages for the selector's status line. Messages are shown one at a time, oldest first,
+/// each for a time depending on its severity once it reaches the line; a persistent message
+/// (e.g. browse-only mode) shows whenever nothing else is queued.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StatusQueue {
+    queue: VecDeque<StatusMessage>,
+    /// When the message at the front of `queue` leaves the line.
+    deadline: Option<Instant>,
+    persistent: Option<StatusMessage>,
+}
+
+impl StatusQueue {
+    pub(crate) fn push(&mut self, severity: Severity, text: impl Into<String>) {
+        self.push_at(severity, text, Instant::now());
+    }
+
+    /// [`push`](Self::push) with the clock passed in.
+    pub(crate) fn push_at(&mut self, severity: Severity, text: impl Into<String>, now: Instant) {
+        if self.queue.is_empty() {
+            self.deadline = Some(now + severity.duration());
+        }
+        self.queue.push_back(StatusMessage {
+            severity,
+            text: text.into(),
+        });
+    }
+
+    /// Sets the message shown while the queue is empty.
+    pub(crate) fn set_persistent(&mut self, severity: Severity, text: impl Into<String>) {
+        self.persistent = Some(StatusMessage {
+            severity,
+            text: text.into(),
+        });
+    }
+
+    /// Drops every queued message; the persistent one stays.
+    pub(crate) fn clear(&mut self) {
+        self.queue.clear();
+        self.deadline = None;
+    }
+
+    /// Moves past the messages whose time is up by `now`. Returns whether the line changed.
+    pub(crate) fn expire(&mut self, now: Instant) -> bool {
+        let mut changed = false;
+        while let Some(deadline) = self.deadline
+            && deadline <= now
+        {
+            self.queue.pop_front();
+            changed = true;
+            self.deadline = self
+                .queue
+                .front()
+                .map(|next| now + next.severity.duration());
+        }
+        changed
+    }
+
+    /// When the line changes next on its own, for the render loop to wake up.
+    pub(crate) fn next_change(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// The message on the line, with how many more are waiting behind it.
+    pub(crate) fn current(&self) -> Option<(&StatusMessage, usize)> {
+        match self.queue.front() {
+            Some(front) => Some((front, self.queue.len() - 1)),
+            None => self.persistent.as_ref().map(|p| (p, 0)),
+        }
+    }
+
+    /// Status line text: the current message, followed by `(+N)` when more are waiting.
+    pub(crate) fn line(&self) -> Option<(Severity, String)> {
+        let (message, waiting) = self.current()?;
+        let text = if waiting > 0 {
+            format!("{} (+{waiting})", message.text)
+        } else {
+            message.text.clone()
+        };
+        Some((message.severity, text))
+    }
+}
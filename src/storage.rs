@@ -1,27 +1,76 @@
+use std::collections::HashMap;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::SystemTime;
 
+use clap::ValueEnum;
+
+use crate::archive::ARCHIVE_DIR;
+use crate::charset::{CharClass, charset, is_query_char, sanitize_query};
+use crate::error::WriteBlocked;
+use crate::expiry::Expiries;
+use crate::model::TryDir;
+use crate::refresh::RefreshCache;
+use crate::scan::scan_dirs;
+use crate::score::{MatchState, TieBreak, compare_ranked, score_try};
+use crate::scratch::SCRATCH_DIR;
+use crate::tags::Tags;
+use crate::timestamps::Timestamps;
+use crate::trash::TRASH_DIR;
 use crate::util::{split_date_prefixed, today_prefix};
+use crate::visits::{Visits, half_life_days};
+
+static CASE_SENSITIVE_NAMES: OnceLock<bool> = OnceLock::new();
+static COLLISION_POLICY: OnceLock<CollisionPolicy> = OnceLock::new();
+
+/// What creating a try does when its name is already taken once date prefixes are ignored
+/// (`names.collision`), in the selector and `try clone` alike. Entering an existing try by
+/// its name is not creating one, so the policy never applies there.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum CollisionPolicy {
+    /// Open the newest try with that name instead
+    Reuse,
+    /// Create the first free `<name>-2`, `<name>-3`, ...
+    Suffix,
+    /// Ask which of those to do, or whether to create the name anyway
+    #[default]
+    Ask,
+    /// Refuse to create it
+    Fail,
+}
 
-/// Normalize a user query for exact-match comparison: sanitize allowed chars and
-/// replace consecutive whitespace with single '-'.
+/// Normalize a user query into the name part of a try: characters names cannot hold are
+/// dropped, a run of spaces and separators becomes one separator (the first one typed, else
+/// `-`), other runs are kept as typed, and separators and dots are trimmed from both ends.
+/// See [`crate::charset`] for the classes.
 pub(crate) fn normalize_query_for_match(query: &str) -> String {
-    let sanitized = crate::util::sanitize_query(query);
-    let mut out = String::with_capacity(sanitized.len());
-    let mut last_dash = false;
-    for ch in sanitized.chars() {
-        if ch.is_whitespace() {
-            if !last_dash {
-                out.push('-');
-                last_dash = true;
+    let charset = charset();
+    let mut out = String::with_capacity(query.len());
+    let mut run = String::new();
+    let mut spaced = false;
+    for ch in query.chars() {
+        match charset.class(ch) {
+            CharClass::Space => spaced = true,
+            CharClass::Separator => run.push(ch),
+            CharClass::Word | CharClass::Literal => {
+                if !out.is_empty() {
+                    if spaced {
+                        out.push(run.chars().next().unwrap_or('-'));
+                    } else {
+                        out.push_str(&run);
+                    }
+                }
+                run.clear();
+                spaced = false;
+                out.push(ch);
             }
-        } else {
-            out.push(ch);
-            last_dash = false;
+            CharClass::Sigil | CharClass::Rejected => {}
         }
     }
-    out
+    out.trim_matches(|c| c == '.' || charset.class(c) == CharClass::Separator)
+        .to_string()
 }
 
 /// Return Some(new_dir_path) if no exact match exists under root for the given query,
@@ -32,27 +81,369 @@ pub(crate) fn fast_create_target_if_no_exact(
 ) -> io::Result<Option<PathBuf>> {
     let norm = normalize_query_for_match(query);
     // Scan existing directories for an exact match (ignoring date prefix)
+    let names = list_dir_names(root);
+    if find_exact_match(names.iter().map(String::as_str), &norm).is_some() {
+        return Ok(None); // exact match exists; do not fast-create
+    }
+    // No exact match: propose today's path
+    Ok(Some(root.join(new_try_name(query))))
+}
+
+/// Checks, without writing anything, that new entries can be created in `dir`: from the
+/// filesystem's read-only flag and free space and the directory's permissions. Returns why
+/// not when the cause is one the user can act on; other failures (including a missing `dir`)
+/// yield `None` and surface later.
+#[cfg(unix)]
+pub(crate) fn write_blocked(dir: &Path) -> Option<WriteBlocked> {
+    use std::os::unix::ffi::OsStrExt;
+    let path = std::ffi::CString::new(dir.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is NUL-terminated and `stat` is large enough for the kernel to fill.
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: statvfs succeeded, so the struct is initialized.
+    let stat = unsafe { stat.assume_init() };
+    if stat.f_flag & libc::ST_RDONLY != 0 {
+        return Some(WriteBlocked::ReadOnly);
+    }
+    // SAFETY: `path` is NUL-terminated.
+    if unsafe { libc::access(path.as_ptr(), libc::W_OK | libc::X_OK) } != 0 {
+        return WriteBlocked::classify(&io::Error::last_os_error());
+    }
+    // Pseudo filesystems report no blocks at all rather than none free
+    (stat.f_blocks > 0 && stat.f_bavail == 0).then_some(WriteBlocked::NoSpace)
+}
+
+/// Checks, without writing anything, that new entries can be created in `dir`; only the
+/// read-only attribute is known here.
+#[cfg(not(unix))]
+pub(crate) fn write_blocked(dir: &Path) -> Option<WriteBlocked> {
+    let readonly = fs::metadata(dir).ok()?.permissions().readonly();
+    readonly.then_some(WriteBlocked::ReadOnly)
+}
+
+/// Marker file that protects a try from deletion, archiving, and dedupe without `--force`.
+pub(crate) const KEEP_MARKER: &str = ".try-keep";
+
+/// Whether the try at `dir` carries a keep marker.
+pub(crate) fn is_protected(dir: &Path) -> bool {
+    dir.join(KEEP_MARKER).exists()
+}
+
+/// Folder under the tries directory for try's own bookkeeping, such as snapshots.
+pub(crate) const META_DIR: &str = ".try_meta";
+const PENDING_CLONES_DIR: &str = "pending-clones";
+
+/// Marker of an unfinished clone into the try `name`, holding the clone URI. Written before
+/// `git clone` runs and removed once it succeeds; it lives outside the try because git only
+/// clones into empty directories.
+pub(crate) fn pending_clone_marker(root: &Path, name: &str) -> PathBuf {
+    root.join(META_DIR).join(PENDING_CLONES_DIR).join(name)
+}
+
+/// Drops the pending-clone marker of the try at `dir`, once it has been deleted, trashed, or
+/// archived; `try clone --resume` has nothing left to finish then.
+pub(crate) fn forget_pending_clone(root: &Path, dir: &Path) {
+    if let Some(name) = dir.file_name() {
+        let _ = fs::remove_file(pending_clone_marker(root, &name.to_string_lossy()));
+    }
+}
+
+/// Unfinished clones under `root`, by try name, with their clone URIs.
+pub(crate) fn pending_clones(root: &Path) -> HashMap<String, String> {
+    let Ok(rd) = fs::read_dir(root.join(META_DIR).join(PENDING_CLONES_DIR)) else {
+        return HashMap::new();
+    };
+    rd.flatten()
+        .filter_map(|e| {
+            let uri = fs::read_to_string(e.path()).ok()?;
+            Some((
+                e.file_name().to_string_lossy().to_string(),
+                uri.trim().to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Whether `name` is one of try's own folders (trash, archive, scratch, metadata) rather than
+/// a try.
+fn is_reserved(name: &str) -> bool {
+    [TRASH_DIR, ARCHIVE_DIR, SCRATCH_DIR, META_DIR].contains(&name)
+}
+
+/// `path` itself when nothing exists there yet, else the first free `<path>-2`, `<path>-3`, ...
+pub(crate) fn free_sibling(path: &Path) -> PathBuf {
+    let mut candidate = path.to_path_buf();
+    let mut n = 2;
+    while fs::symlink_metadata(&candidate).is_ok() {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(format!("-{n}"));
+        candidate = PathBuf::from(name);
+        n += 1;
+    }
+    candidate
+}
+
+/// The try that `path` lies in: its first component below `root`, unless that is one of
+/// try's own folders. Compares both the given and the canonical form of `root`.
+pub(crate) fn containing_try(root: &Path, path: &Path) -> Option<PathBuf> {
+    let canonical = fs::canonicalize(root).ok();
+    [Some(root), canonical.as_deref()]
+        .into_iter()
+        .flatten()
+        .find_map(|root| {
+            let first = path.strip_prefix(root).ok()?.components().next()?;
+            let name = first.as_os_str().to_string_lossy();
+            (!is_reserved(&name)).then(|| root.join(first))
+        })
+}
+
+/// Scans the try directories directly under `root`, skipping try's own folders. Sizes and git
+/// status are left unset for lazy collection.
+pub(crate) fn scan_tries(root: &Path) -> Vec<TryDir> {
+    let pending = pending_clones(root);
+    let visits = Visits::load(root);
+    let remote = RefreshCache::load(root);
+    let expiries = Expiries::load(root);
+    let tags = Tags::load(root);
+    let (now, half_life) = (SystemTime::now(), half_life_days());
+    let mut out = Vec::new();
+    for dir in scan_dirs(root, is_reserved) {
+        let times = Timestamps::from_metadata(&dir.meta, &dir.name);
+        out.push(TryDir {
+            pending_clone: pending.contains_key(&dir.name),
+            visits: visits.weight(&dir.name, now, half_life),
+            behind: remote.behind(&dir.name),
+            expires: expiries.get(&dir.name),
+            tags: tags.get(&dir.name).into_iter().map(String::from).collect(),
+            basename: dir.name,
+            path: dir.path,
+            ctime: times.created,
+            mtime: times.modified,
+            score: 0.0,
+            size: None, // Calculated lazily during render
+            git: None,  // Likewise collected lazily
+        });
+    }
+    out
+}
+
+/// Resolves `query` to a single try for non-interactive commands: an exact name match (date
+/// prefix ignored) wins, otherwise the best fuzzy score. A blank query picks the most recent.
+pub(crate) fn resolve_try(root: &Path, query: &str, ties: &[TieBreak]) -> Option<TryDir> {
+    rank_tries(root, query, ties).into_iter().next()
+}
+
+/// Every try matching `query`, best first, with `score` filled in. An exact name match (date
+/// prefix ignored) is moved to the front regardless of its score; equal scores are ordered
+/// by `ties`.
+pub(crate) fn rank_tries(root: &Path, query: &str, ties: &[TieBreak]) -> Vec<TryDir> {
+    let (mut tries, exact) = score_tries(root, query, ties);
+    let matches_all = sanitize_query(query.trim()).is_empty();
+    tries.retain(|t| matches_all || t.score > 0.0 || exact.as_deref() == Some(&t.basename));
+    tries
+}
+
+/// Like `rank_tries`, but keeps the tries `query` does not match (score 0) and also returns
+/// the name of the exact match, if any.
+pub(crate) fn score_tries(
+    root: &Path,
+    query: &str,
+    ties: &[TieBreak],
+) -> (Vec<TryDir>, Option<String>) {
+    let mut tries = scan_tries(root);
+    let norm = normalize_query_for_match(query.trim());
+    let exact = (!norm.is_empty())
+        .then(|| find_exact_match(tries.iter().map(|t| t.basename.as_str()), &norm))
+        .flatten()
+        .map(str::to_string);
+    let text = sanitize_query(query.trim());
+    for t in &mut tries {
+        t.score = score_try(t, &text, &mut MatchState::default());
+    }
+    let is_exact = |t: &TryDir| exact.as_deref() == Some(t.basename.as_str());
+    tries.sort_by(|a, b| {
+        is_exact(b)
+            .cmp(&is_exact(a))
+            .then_with(|| compare_ranked(a, b, ties))
+    });
+    (tries, exact)
+}
+
+/// Names of the try directories directly under `root`, skipping try's own folders.
+pub(crate) fn list_dir_names(root: &Path) -> Vec<String> {
+    let mut out = Vec::new();
     if let Ok(entries) = fs::read_dir(root) {
         for e in entries.flatten() {
-            let Ok(meta) = e.metadata() else { continue };
+            let Ok(meta) = fs::metadata(e.path()) else {
+                continue;
+            };
             if !meta.is_dir() {
                 continue;
             }
             let name = e.file_name().to_string_lossy().to_string();
-            if name == ".try_trash" {
-                continue;
+            if !is_reserved(&name) {
+                out.push(name);
             }
-            let stripped = if let Some((_, rest)) = split_date_prefixed(&name) {
-                rest.to_string()
+        }
+    }
+    out
+}
+
+/// Tries whose name, date prefix ignored, matches `query` exactly (see [`find_exact_match`]),
+/// newest date prefix first; equal dates put the most recently modified first.
+pub(crate) fn exact_matches(root: &Path, query: &str) -> Vec<TryDir> {
+    let norm = normalize_query_for_match(query.trim());
+    if norm.is_empty() {
+        return Vec::new();
+    }
+    let mut tries: Vec<TryDir> = scan_tries(root)
+        .into_iter()
+        .filter(|t| find_exact_match([t.basename.as_str()], &norm).is_some())
+        .collect();
+    tries.sort_by(newest_dated_first);
+    tries
+}
+
+/// Orders tries by date prefix, newest first, then by modification time.
+fn newest_dated_first(a: &TryDir, b: &TryDir) -> std::cmp::Ordering {
+    let date = |t: &TryDir| split_date_prefixed(&t.basename).map(|(d, _)| d.to_string());
+    date(b).cmp(&date(a)).then_with(|| b.mtime.cmp(&a.mtime))
+}
+
+/// `name` without its date prefix, if it has one.
+fn undated(name: &str) -> &str {
+    split_date_prefixed(name).map_or(name, |(_, rest)| rest)
+}
+
+/// The newest of `tries` that a new try called `name` would collide with: the one whose
+/// name is the same once date prefixes are ignored, including `name` itself.
+pub(crate) fn name_collision<'a>(tries: &'a [TryDir], name: &str) -> Option<&'a TryDir> {
+    let norm = normalize_query_for_match(undated(name));
+    tries
+        .iter()
+        .filter(|t| find_exact_match([t.basename.as_str()], &norm).is_some())
+        .min_by(|a, b| newest_dated_first(a, b))
+}
+
+/// `name` with the first `-2`, `-3`, ... suffix that collides with none of `tries` (see
+/// [`name_collision`]); the name `names.collision = suffix` creates.
+pub(crate) fn suffixed_name(tries: &[TryDir], name: &str) -> String {
+    (2..)
+        .map(|n| format!("{name}-{n}"))
+        .find(|candidate| name_collision(tries, candidate).is_none())
+        .expect("some suffix is free")
+}
+
+/// Sets the process-wide `names.case_sensitive`; only the first call takes effect.
+pub(crate) fn set_case_sensitive_names(on: bool) {
+    let _ = CASE_SENSITIVE_NAMES.set(on);
+}
+
+/// Sets the process-wide `names.collision`; only the first call takes effect.
+pub(crate) fn set_collision_policy(policy: CollisionPolicy) {
+    let _ = COLLISION_POLICY.set(policy);
+}
+
+/// The `names.collision` policy; [`CollisionPolicy::Ask`] until set.
+pub(crate) fn collision_policy() -> CollisionPolicy {
+    COLLISION_POLICY.get().copied().unwrap_or_default()
+}
+
+/// Returns the first name that matches `norm` once its date prefix (if any) is stripped.
+/// Both sides are compared as slugs (see [`name_slug`]), so `Foo Bar` finds `foo_bar`.
+pub(crate) fn find_exact_match<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    norm: &str,
+) -> Option<&'a str> {
+    let case_sensitive = CASE_SENSITIVE_NAMES.get().copied().unwrap_or(false);
+    let wanted = name_slug(norm, case_sensitive);
+    names.into_iter().find(|name| {
+        let stripped = split_date_prefixed(name).map_or(*name, |(_, rest)| rest);
+        name_slug(stripped, case_sensitive) == wanted
+    })
+}
+
+/// Comparison key for try names: runs of separators (`names.separators`, by default `-` and
+/// `_`) and spaces become one `-`, those at either end are dropped, and letters are
+/// lowercased unless `case_sensitive`. Other punctuation, such as the `.` of `v1.2`, counts.
+pub(crate) fn name_slug(name: &str, case_sensitive: bool) -> String {
+    let charset = charset();
+    let mut out = String::with_capacity(name.len());
+    for word in name
+        .split(|c| matches!(charset.class(c), CharClass::Separator | CharClass::Space))
+        .filter(|w| !w.is_empty())
+    {
+        if !out.is_empty() {
+            out.push('-');
+        }
+        out.push_str(word);
+    }
+    if case_sensitive {
+        out
+    } else {
+        out.to_lowercase()
+    }
+}
+
+/// Try name for the git branch `branch`: runs of characters a try name cannot hold, such as
+/// the `/` of `feature/login`, become one `-`, and letters are lowercased.
+pub(crate) fn branch_try_name(branch: &str) -> String {
+    let spaced: String = branch
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
             } else {
-                name.clone()
-            };
-            if stripped == norm {
-                return Ok(None); // exact match exists; do not fast-create
+                ' '
             }
+        })
+        .collect();
+    name_slug(&spaced, false)
+}
+
+/// Final directory name for a new try created from `input`: today's date prefix plus the
+/// normalized input.
+pub(crate) fn new_try_name(input: &str) -> String {
+    format!(
+        "{}-{}",
+        today_prefix(),
+        normalize_query_for_match(input.trim())
+    )
+}
+
+/// Live validation of a prospective try name, used to give feedback while typing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct NameCheck {
+    /// Directory name that would be created.
+    pub final_name: String,
+    /// Distinct characters that sanitizing drops from the input.
+    pub rejected: Vec<char>,
+    /// Existing entry the new name collides with, ignoring date prefixes.
+    pub collision: Option<String>,
+}
+
+/// Validates `input` against the existing entry `names` without touching the filesystem.
+pub(crate) fn check_new_name<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    input: &str,
+) -> NameCheck {
+    let mut rejected: Vec<char> = Vec::new();
+    for ch in input.chars() {
+        if !is_query_char(ch) && !rejected.contains(&ch) {
+            rejected.push(ch);
         }
     }
-    // No exact match: propose today's path
-    let final_name = format!("{}-{}", today_prefix(), norm);
-    Ok(Some(root.join(final_name)))
+    let norm = normalize_query_for_match(input.trim());
+    let collision = if norm.is_empty() {
+        None
+    } else {
+        find_exact_match(names, &norm).map(str::to_string)
+    };
+    NameCheck {
+        final_name: new_try_name(input),
+        rejected,
+        collision,
+    }
 }
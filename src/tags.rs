@@ -0,0 +1,91 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::storage::META_DIR;
+
+/// Tags of tries under `.try_meta`: `name<TAB>tag,tag` per line.
+const TAGS_FILE: &str = "tags.tsv";
+
+/// Which tags each try carries, set with `try tag`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Tags {
+    path: PathBuf,
+    entries: HashMap<String, BTreeSet<String>>,
+}
+
+impl Tags {
+    /// Loads the tags stored under `base_path`; a missing file or malformed lines are
+    /// treated as no tags.
+    pub(crate) fn load(base_path: &Path) -> Self {
+        let path = base_path.join(META_DIR).join(TAGS_FILE);
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let entries = text
+            .lines()
+            .filter_map(|line| {
+                let (name, tags) = line.split_once('\t')?;
+                let tags: BTreeSet<String> = tags.split(',').filter_map(normalize_tag).collect();
+                (!tags.is_empty()).then(|| (name.to_string(), tags))
+            })
+            .collect();
+        Self { path, entries }
+    }
+
+    /// Tags of `name`, sorted.
+    pub(crate) fn get(&self, name: &str) -> Vec<&str> {
+        self.entries
+            .get(name)
+            .map(|tags| tags.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Adds `tag` to `name`; whether it was new.
+    pub(crate) fn add(&mut self, name: &str, tag: &str) -> bool {
+        self.entries
+            .entry(name.to_string())
+            .or_default()
+            .insert(tag.to_string())
+    }
+
+    /// Removes `tag` from `name`; whether it had it.
+    pub(crate) fn remove(&mut self, name: &str, tag: &str) -> bool {
+        let Some(tags) = self.entries.get_mut(name) else {
+            return false;
+        };
+        let removed = tags.remove(tag);
+        if tags.is_empty() {
+            self.entries.remove(name);
+        }
+        removed
+    }
+
+    pub(crate) fn save(&self) -> io::Result<()> {
+        let mut lines: Vec<String> = self
+            .entries
+            .iter()
+            .map(|(name, tags)| {
+                let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+                format!("{name}\t{}", tags.join(","))
+            })
+            .collect();
+        lines.sort();
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, lines.join("\n") + "\n")
+    }
+}
+
+/// `tag` as stored: lowercase, without a leading `#`. `None` when that leaves nothing, or
+/// when it holds anything but ASCII letters, digits, `-`, `_`, and `.`, so every tag can be
+/// typed as a `#tag` query token.
+pub(crate) fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim();
+    let tag = tag.strip_prefix('#').unwrap_or(tag).to_ascii_lowercase();
+    let valid = !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    valid.then_some(tag)
+}
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+use crate::git::GitStatus;
+
+// Upper bound on worker threads; IO-bound jobs gain little beyond this
+const MAX_WORKERS: usize = 4;
+
+/// Kind of background job, used together with the path to deduplicate work.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub(crate) enum TaskKind {
+    Size,
+    Git,
+}
+
+/// Value produced by a background job.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TaskOutput {
+    Size(u64),
+    Git(Option<GitStatus>),
+    /// The job was canceled before it finished; there is no value.
+    Canceled(TaskKind),
+}
+
+impl TaskOutput {
+    fn kind(&self) -> TaskKind {
+        match self {
+            TaskOutput::Size(_) => TaskKind::Size,
+            TaskOutput::Git(_) => TaskKind::Git,
+            TaskOutput::Canceled(kind) => *kind,
+        }
+    }
+}
+
+type CancelHook = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct CancelState {
+    canceled: AtomicBool,
+    /// Interrupts the blocking call a job is in, if any; see [`CancelToken::interrupt_with`].
+    hook: Mutex<Option<CancelHook>>,
+}
+
+/// Cooperative cancellation flag handed to every job. Long-running jobs check it between
+/// steps and return `None` once it is set; jobs that block on IO register a hook that
+/// unblocks them instead.
+#[derive(Clone, Default)]
+pub(crate) struct CancelToken(Arc<CancelState>);
+
+impl CancelToken {
+    pub(crate) fn is_canceled(&self) -> bool {
+        self.0.canceled.load(Ordering::Relaxed)
+    }
+
+    /// The underlying flag, for helpers that take a plain `&AtomicBool`.
+    pub(crate) fn flag(&self) -> &AtomicBool {
+        &self.0.canceled
+    }
+
+    /// Runs `f`, calling `hook` from the canceling thread if the token is canceled before
+    /// `f` returns (or right away when it already is). `hook` should make `f` return soon,
+    /// e.g. by killing the child process `f` reads from.
+    pub(crate) fn interrupt_with<T>(
+        &self,
+        hook: impl FnOnce() + Send + 'static,
+        f: impl FnOnce() -> T,
+    ) -> T {
+        if let Ok(mut slot) = self.0.hook.lock() {
+            if self.is_canceled() {
+                drop(slot);
+                hook();
+            } else {
+                *slot = Some(Box::new(hook));
+            }
+        }
+        let out = f();
+        if let Ok(mut slot) = self.0.hook.lock() {
+            slot.take();
+        }
+        out
+    }
+
+    fn cancel(&self) {
+        self.0.canceled.store(true, Ordering::Relaxed);
+        // Run the hook outside the lock so it cannot deadlock with the job
+        let hook = self.0.hook.lock().ok().and_then(|mut slot| slot.take());
+        if let Some(hook) = hook {
+            hook();
+        }
+    }
+}
+
+/// A finished job, delivered through the registry's result channel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TaskResult {
+    pub path: PathBuf,
+    pub output: TaskOutput,
+}
+
+type Job = Box<dyn FnOnce(&CancelToken) -> TaskResult + Send>;
+
+/// Priority of jobs whose row is no longer near the viewport; they run after everything
+/// the user can see.
+const BACKGROUND_PRIORITY: usize = usize::MAX;
+
+/// A job waiting for a worker. Lower `priority` runs first; `seq` keeps queue order among
+/// equal priorities.
+struct Queued {
+    key: (TaskKind, PathBuf),
+    priority: usize,
+    seq: u64,
+    job: Job,
+    token: CancelToken,
+}
+
+#[derive(Default)]
+struct Queue {
+    waiting: Vec<Queued>,
+    next_seq: u64,
+    closed: bool,
+}
+
+/// Runs IO-bound jobs (directory sizes, git status) on a small worker pool. Every result
+/// flows back through one channel that the selector drains between frames, so no job
+/// touches selector state directly. Waiting jobs are taken by priority, which the selector
+/// sets from the viewport, so visible rows fill in before off-screen ones, and jobs for
+/// entries that stop matching are canceled.
+pub(crate) struct TaskRegistry {
+    queue: Arc<(Mutex<Queue>, Condvar)>,
+    started: bool,
+    results_tx: Sender<TaskResult>,
+    results: Receiver<TaskResult>,
+    /// Cancellation tokens of the jobs that have not reported back, waiting or running.
+    pending: HashMap<(TaskKind, PathBuf), CancelToken>,
+    workers: usize,
+}
+
+impl TaskRegistry {
+    /// Creates an idle registry; worker threads start with the first job.
+    pub(crate) fn new() -> Self {
+        let workers = thread::available_parallelism()
+            .map_or(1, usize::from)
+            .min(MAX_WORKERS);
+        Self::with_workers(workers)
+    }
+
+    /// Creates an idle registry that runs at most `workers` jobs at once.
+    pub(crate) fn with_workers(workers: usize) -> Self {
+        let (results_tx, results) = mpsc::channel();
+        Self {
+            queue: Arc::default(),
+            started: false,
+            results_tx,
+            results,
+            pending: HashMap::new(),
+            workers,
+        }
+    }
+
+    /// Queues `job` for `path` at `priority` (lower runs first) unless the same kind of job
+    /// for it is already pending; a pending job that has not started takes the new priority
+    /// instead. `job` returns `None` when it stopped early because its token was canceled.
+    /// Returns whether a job was queued.
+    pub(crate) fn spawn<F>(&mut self, kind: TaskKind, path: &Path, priority: usize, job: F) -> bool
+    where
+        F: FnOnce(&Path, &CancelToken) -> Option<TaskOutput> + Send + 'static,
+    {
+        let key = (kind, path.to_path_buf());
+        if self.pending.contains_key(&key) {
+            self.with_queue(|q| {
+                if let Some(w) = q.waiting.iter_mut().find(|w| w.key == key) {
+                    w.priority = priority;
+                }
+            });
+            return false;
+        }
+        let path = path.to_path_buf();
+        let boxed: Job = Box::new(move |token| {
+            let output = if token.is_canceled() {
+                None
+            } else {
+                job(&path, token)
+            };
+            TaskResult {
+                path,
+                output: output.unwrap_or(TaskOutput::Canceled(kind)),
+            }
+        });
+        let token = CancelToken::default();
+        self.start_workers();
+        self.with_queue(|q| {
+            let seq = q.next_seq;
+            q.next_seq += 1;
+            q.waiting.push(Queued {
+                key: key.clone(),
+                priority,
+                seq,
+                job: boxed,
+                token: token.clone(),
+            });
+        });
+        self.queue.1.notify_one();
+        self.pending.insert(key, token);
+        true
+    }
+
+    /// Cancels every pending job whose path `keep` rejects: waiting ones are dropped, running
+    /// ones are told to stop and report [`TaskOutput::Canceled`]. Returns how many were
+    /// canceled.
+    pub(crate) fn cancel_unless(&mut self, keep: impl Fn(&Path) -> bool) -> usize {
+        let dropped: Vec<(TaskKind, PathBuf)> = self
+            .with_queue(|q| {
+                let (gone, kept) = std::mem::take(&mut q.waiting)
+                    .into_iter()
+                    .partition(|w| !keep(&w.key.1));
+                q.waiting = kept;
+                gone.into_iter().map(|w: Queued| w.key).collect()
+            })
+            .unwrap_or_default();
+        for key in &dropped {
+            self.pending.remove(key);
+        }
+        let mut canceled = dropped.len();
+        for ((_, path), token) in &self.pending {
+            if !keep(path) && !token.is_canceled() {
+                token.cancel();
+                canceled += 1;
+            }
+        }
+        canceled
+    }
+
+    /// Moves every job that has not started to [`BACKGROUND_PRIORITY`], behind anything
+    /// queued or re-queued afterwards; called when the viewport moves.
+    pub(crate) fn defer_waiting(&mut self) {
+        self.with_queue(|q| {
+            for w in &mut q.waiting {
+                w.priority = BACKGROUND_PRIORITY;
+            }
+        });
+    }
+
+    /// Collects every result that has arrived, without blocking.
+    pub(crate) fn drain(&mut self) -> Vec<TaskResult> {
+        let done: Vec<TaskResult> = self.results.try_iter().collect();
+        for r in &done {
+            self.pending.remove(&(r.output.kind(), r.path.clone()));
+        }
+        done
+    }
+
+    /// Whether any queued job has not reported back yet.
+    pub(crate) fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    fn with_queue<T>(&self, f: impl FnOnce(&mut Queue) -> T) -> Option<T> {
+        self.queue.0.lock().ok().map(|mut q| f(&mut q))
+    }
+
+    fn start_workers(&mut self) {
+        if std::mem::replace(&mut self.started, true) {
+            return;
+        }
+        for _ in 0..self.workers {
+            let queue = Arc::clone(&self.queue);
+            let results = self.results_tx.clone();
+            thread::spawn(move || {
+                while let Some((job, token)) = next_job(&queue) {
+                    if results.send(job(&token)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    }
+}
+
+impl Drop for TaskRegistry {
+    fn drop(&mut self) {
+        self.with_queue(|q| q.closed = true);
+        self.queue.1.notify_all();
+    }
+}
+
+/// Blocks until a job is waiting and takes the most urgent one; `None` once the registry
+/// is dropped.
+fn next_job(queue: &(Mutex<Queue>, Condvar)) -> Option<(Job, CancelToken)> {
+    let (lock, ready) = queue;
+    let mut q = lock.lock().ok()?;
+    loop {
+        if q.closed {
+            return None;
+        }
+        let best = (0..q.waiting.len()).min_by_key(|&i| (q.waiting[i].priority, q.waiting[i].seq));
+        if let Some(i) = best {
+            // Hold the lock only while taking the job
+            let taken = q.waiting.swap_remove(i);
+            return Some((taken.job, taken.token));
+        }
+        q = ready.wait(q).ok()?;
+    }
+}
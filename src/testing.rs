@@ -0,0 +1,146 @@
+//! Generators and invariant checks for scoring and name normalization, used by this crate's
+//! unit tests; the `testing` feature also compiles them into the binary. Each check returns a
+//! description of the first violation instead of panicking, so callers can report the seed
+//! that found it.
+
+use std::time::{Duration, SystemTime};
+
+use crate::score::{match_positions, score_parts};
+use crate::storage::normalize_query_for_match;
+
+const WORD_CHARS: &[char] = &[
+    'a', 'b', 'e', 'h', 'i', 'o', 'p', 'r', 's', 't', 'x', 'z', 'A', 'S', '0', '7', 'ü', 'é',
+];
+const SEPARATORS: &[char] = &['-', '_', '.'];
+const QUERY_NOISE: &[char] = &['a', 's', 'q', 'Z', '2', '-', ' ', 'ü'];
+
+/// Small deterministic generator (xorshift64*), so a failing seed reproduces everywhere.
+#[derive(Clone, Debug)]
+pub(crate) struct Gen(u64);
+
+impl Gen {
+    pub(crate) fn new(seed: u64) -> Self {
+        Gen(seed.max(1))
+    }
+
+    /// Uniform value in `0..n`; `n` must be non-zero.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 33) as usize % n
+    }
+
+    fn pick(&mut self, from: &[char]) -> char {
+        from[self.below(from.len())]
+    }
+
+    /// A try-like name: one to four words joined by separators, sometimes date-prefixed.
+    pub(crate) fn name(&mut self) -> String {
+        let mut out = String::new();
+        if self.below(2) == 0 {
+            out.push_str(&format!("2025-0{}-1{}-", 1 + self.below(9), self.below(10)));
+        }
+        for word in 0..=self.below(4) {
+            if word > 0 {
+                out.push(self.pick(SEPARATORS));
+            }
+            for _ in 0..=self.below(6) {
+                out.push(self.pick(WORD_CHARS));
+            }
+        }
+        out
+    }
+
+    /// A query for `name`: usually some of its characters in order, otherwise noise.
+    pub(crate) fn query(&mut self, name: &str) -> String {
+        if self.below(4) == 0 {
+            return (0..=self.below(4))
+                .map(|_| self.pick(QUERY_NOISE))
+                .collect();
+        }
+        name.chars().filter(|_| self.below(3) == 0).collect()
+    }
+}
+
+/// A more recent creation or modification time never lowers the score.
+pub(crate) fn check_recency_monotonic(name: &str, query: &str) -> Result<(), String> {
+    let now = SystemTime::now();
+    let (newer, older) = (
+        now - Duration::from_secs(60),
+        now - Duration::from_secs(86_400 * 9),
+    );
+    let score = |ctime, mtime| score_parts(name, query, ctime, mtime).total();
+    let pairs = [
+        (
+            score(Some(newer), None),
+            score(Some(older), None),
+            "creation",
+        ),
+        (
+            score(None, Some(newer)),
+            score(None, Some(older)),
+            "modification",
+        ),
+    ];
+    for (new, old, which) in pairs {
+        if new < old {
+            return Err(format!(
+                "{name:?} / {query:?}: newer {which} time scored {new} < {old}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Normalizing a query a second time changes nothing.
+pub(crate) fn check_normalization_idempotent(input: &str) -> Result<(), String> {
+    let once = normalize_query_for_match(input);
+    let twice = normalize_query_for_match(&once);
+    if once == twice {
+        Ok(())
+    } else {
+        Err(format!(
+            "{input:?} normalizes to {once:?}, then to {twice:?}"
+        ))
+    }
+}
+
+/// Highlighted positions exist exactly when the name scores, and mark each query character
+/// once, in order.
+pub(crate) fn check_highlight_matches_score(name: &str, query: &str) -> Result<(), String> {
+    let parts = score_parts(name, query, None, None);
+    let positions = match_positions(name, query);
+    let Some(positions) = positions else {
+        if parts.matched {
+            return Err(format!("{name:?} / {query:?}: scored without highlights"));
+        }
+        return Ok(());
+    };
+    if !parts.matched {
+        return Err(format!("{name:?} / {query:?}: highlighted without a score"));
+    }
+    let lower: Vec<char> = name.to_lowercase().chars().collect();
+    let marked: String = positions.iter().filter_map(|&p| lower.get(p)).collect();
+    if !positions.is_sorted_by(|a, b| a < b) || marked != query.to_lowercase() {
+        return Err(format!(
+            "{name:?} / {query:?}: highlights {positions:?} spell {marked:?}"
+        ));
+    }
+    Ok(())
+}
+
+/// Runs every check on `cases` generated names and queries starting from `seed`.
+pub(crate) fn check_all(seed: u64, cases: usize) -> Result<(), String> {
+    let mut g = Gen::new(seed);
+    for case in 0..cases {
+        let name = g.name();
+        let query = g.query(&name);
+        let at = |e: String| format!("seed {seed}, case {case}: {e}");
+        check_recency_monotonic(&name, &query).map_err(at)?;
+        check_normalization_idempotent(&query).map_err(at)?;
+        check_normalization_idempotent(&name).map_err(at)?;
+        check_highlight_matches_score(&name, &query).map_err(at)?;
+    }
+    Ok(())
+}
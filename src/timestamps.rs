@@ -0,0 +1,67 @@
+use std::fs;
+use std::io;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::util::{days_from_civil, split_date_prefixed};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Creation and modification times of a try, for ranking and display.
+///
+/// Platforms disagree on what they report: Windows and macOS record a creation time, Linux
+/// only through `statx` on filesystems that keep one (not on many network, FUSE, or older
+/// filesystems), and some filesystems report the Unix epoch for a time they do not track.
+/// Missing times are filled in from the other sources so scoring keeps a recency component.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Timestamps {
+    pub created: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+}
+
+impl Timestamps {
+    /// Times of the entry `name` described by `meta`.
+    pub(crate) fn from_metadata(meta: &fs::Metadata, name: &str) -> Self {
+        Self::resolve(meta.created(), meta.modified(), name)
+    }
+
+    /// Picks the times from what the platform returned. Without a creation time, the date
+    /// of a `YYYY-MM-DD-` name stands in (it was the creation day), then the modification
+    /// time; without a modification time, the creation time.
+    pub(crate) fn resolve(
+        created: io::Result<SystemTime>,
+        modified: io::Result<SystemTime>,
+        name: &str,
+    ) -> Self {
+        let created = known(created);
+        let modified = known(modified);
+        Self {
+            created: created.or_else(|| name_date(name)).or(modified),
+            modified: modified.or(created),
+        }
+    }
+}
+
+/// A timestamp the filesystem actually tracks; errors and the epoch placeholder are `None`.
+fn known(time: io::Result<SystemTime>) -> Option<SystemTime> {
+    time.ok().filter(|&t| t > UNIX_EPOCH)
+}
+
+/// Midnight UTC of the date a `YYYY-MM-DD-` name starts with.
+fn name_date(name: &str) -> Option<SystemTime> {
+    let (date, _) = split_date_prefixed(name)?;
+    let mut parts = date.splitn(3, '-');
+    let y: i32 = parts.next()?.parse().ok()?;
+    let m: u32 = parts.next()?.parse().ok()?;
+    let d: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return None;
+    }
+    let days = u64::try_from(days_from_civil(y, m, d)).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(days * SECONDS_PER_DAY))
+}
+
+/// How long before `now` the time `t` was. Times in the future (clock skew, or files copied
+/// from a machine whose clock runs ahead) count as just now rather than as unknown.
+pub(crate) fn age(t: SystemTime, now: SystemTime) -> Duration {
+    now.duration_since(t).unwrap_or(Duration::ZERO)
+}
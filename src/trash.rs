@@ -0,0 +1,491 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::copy::move_tree;
+use crate::storage::{META_DIR, find_exact_match, forget_pending_clone};
+use crate::tui::ProgressLine;
+use crate::util::calculate_dir_size;
+
+/// Folder under the tries directory holding soft-deleted entries; never listed as a try.
+pub(crate) const TRASH_DIR: &str = ".try_trash";
+/// Folder inside the trash holding items `try trash empty` has taken out but not finished
+/// removing; never listed as an item.
+const EMPTYING_DIR: &str = ".emptying";
+/// Index of the trash under `.try_meta`: one `[[item]]` TOML table per trashed entry.
+const INDEX_FILE: &str = "trash.toml";
+const INDEX_HEADER: &str = "# try trash index v1";
+/// File under `.try_meta` whose modification time is when the trash was last pruned on its
+/// own.
+const PRUNE_STAMP: &str = "trash-pruned";
+const DEFAULT_RETENTION_DAYS: u64 = 30;
+const DEFAULT_MAX_BYTES: u64 = 5 << 30;
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Limits applied to the trash; `None` disables a limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TrashPolicy {
+    /// Items trashed longer ago than this are purged.
+    pub retention: Option<Duration>,
+    /// Oldest items are purged until the trash fits in this many bytes.
+    pub max_bytes: Option<u64>,
+}
+
+impl TrashPolicy {
+    /// Policy from the `trash.*` settings, defaulting to 30 days and 5G. A value of `0`
+    /// turns the corresponding limit off.
+    pub(crate) fn from_config(config: &Config) -> Self {
+        let days = config
+            .trash_retention_days
+            .unwrap_or(DEFAULT_RETENTION_DAYS);
+        let max_bytes = config.trash_max_size.unwrap_or(DEFAULT_MAX_BYTES);
+        Self {
+            retention: (days > 0).then(|| Duration::from_secs(days * SECONDS_PER_DAY)),
+            max_bytes: (max_bytes > 0).then_some(max_bytes),
+        }
+    }
+}
+
+/// An entry in the trash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TrashItem {
+    pub name: String,
+    pub path: PathBuf,
+    /// When the entry was trashed, from the index; for entries that reached the trash without
+    /// a record, when try first found them there.
+    pub trashed: SystemTime,
+    pub size: u64,
+    /// Where the entry was trashed from, when the index recorded it.
+    pub original: Option<PathBuf>,
+    /// Why the entry was trashed, when the index recorded it.
+    pub reason: Option<String>,
+}
+
+impl TrashItem {
+    fn new(path: PathBuf, record: &TrashRecord) -> Self {
+        Self {
+            name: record.name.clone(),
+            trashed: record.deleted,
+            size: record.size,
+            original: record.original.clone(),
+            reason: (!record.reason.is_empty()).then(|| record.reason.clone()),
+            path,
+        }
+    }
+}
+
+/// What the trash index keeps about one trashed entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TrashRecord {
+    /// Name of the entry inside the trash folder.
+    pub name: String,
+    /// Absolute path the entry was trashed from; restoring puts it back there. Unknown for
+    /// entries that reached the trash without a record.
+    pub original: Option<PathBuf>,
+    pub deleted: SystemTime,
+    /// Size in bytes when it was trashed.
+    pub size: u64,
+    /// What trashed it, e.g. `expired` or `duplicate of 2025-01-01-foo`.
+    pub reason: String,
+}
+
+/// The trash index (`.try_meta/trash.toml`) of a tries directory.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TrashIndex {
+    path: PathBuf,
+    records: Vec<TrashRecord>,
+}
+
+impl TrashIndex {
+    /// Loads the index under `base_path`; a missing file or malformed tables are treated as
+    /// no records.
+    pub(crate) fn load(base_path: &Path) -> Self {
+        let path = base_path.join(META_DIR).join(INDEX_FILE);
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        Self {
+            path,
+            records: parse_index(&text),
+        }
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&TrashRecord> {
+        self.records.iter().find(|r| r.name == name)
+    }
+
+    /// Records the entries of the trash under `base_path` that have no record yet, such as
+    /// ones moved there by hand, as trashed now. Their age then counts from when try first
+    /// saw them rather than from their contents' modification time. Returns whether any were
+    /// added.
+    fn adopt_untracked(&mut self, base_path: &Path) -> bool {
+        let Ok(rd) = fs::read_dir(base_path.join(TRASH_DIR)) else {
+            return false;
+        };
+        let now = SystemTime::now();
+        let mut added = false;
+        for e in rd.flatten().filter(|e| e.file_name() != EMPTYING_DIR) {
+            let name = e.file_name().to_string_lossy().to_string();
+            if self.get(&name).is_some() {
+                continue;
+            }
+            let Ok(meta) = e.metadata() else { continue };
+            let size = if meta.is_dir() {
+                calculate_dir_size(&e.path())
+            } else {
+                meta.len()
+            };
+            self.records.push(TrashRecord {
+                name,
+                original: None,
+                deleted: now,
+                size,
+                reason: String::new(),
+            });
+            added = true;
+        }
+        added
+    }
+
+    /// The index under `base_path` with every trash entry recorded; see
+    /// [`TrashIndex::adopt_untracked`].
+    fn load_complete(base_path: &Path) -> Self {
+        let mut index = Self::load(base_path);
+        if index.adopt_untracked(base_path) {
+            // Unsaved, the entries are adopted again, as new, on the next load
+            let _ = index.save(base_path);
+        }
+        index
+    }
+
+    /// Adds `record`, replacing an earlier one for the same trash entry.
+    pub(crate) fn insert(&mut self, record: TrashRecord) {
+        self.records.retain(|r| r.name != record.name);
+        self.records.push(record);
+    }
+
+    /// Writes the index, dropping records of entries no longer in the trash under
+    /// `base_path`.
+    pub(crate) fn save(&mut self, base_path: &Path) -> io::Result<()> {
+        let trash = base_path.join(TRASH_DIR);
+        self.records
+            .retain(|r| fs::symlink_metadata(trash.join(&r.name)).is_ok());
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, index_text(&self.records))
+    }
+}
+
+/// Index text: a header comment, then one `[[item]]` table per record.
+fn index_text(records: &[TrashRecord]) -> String {
+    let mut out = format!("{INDEX_HEADER}\n");
+    for r in records {
+        let deleted = r
+            .deleted
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        out.push_str(&format!("\n[[item]]\nname = {}\n", toml_string(&r.name)));
+        if let Some(original) = &r.original {
+            out.push_str(&format!(
+                "original = {}\n",
+                toml_string(&original.to_string_lossy())
+            ));
+        }
+        out.push_str(&format!(
+            "deleted = {deleted}\nsize = {}\nreason = {}\n",
+            r.size,
+            toml_string(&r.reason),
+        ));
+    }
+    out
+}
+
+/// Parses index text; tables missing a name are skipped, as are lines that are not
+/// `key = value`.
+fn parse_index(text: &str) -> Vec<TrashRecord> {
+    let mut tables: Vec<HashMap<&str, String>> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line == "[[item]]" {
+            tables.push(HashMap::new());
+            continue;
+        }
+        let (Some(table), Some((key, value))) = (tables.last_mut(), line.split_once('=')) else {
+            continue;
+        };
+        let value = value.trim();
+        let value = match value.strip_prefix('"') {
+            Some(_) => match parse_toml_string(value) {
+                Some(v) => v,
+                None => continue,
+            },
+            None => value.to_string(),
+        };
+        table.insert(key.trim(), value);
+    }
+    tables
+        .into_iter()
+        .filter_map(|mut t| {
+            let secs = t.get("deleted").and_then(|d| d.parse().ok()).unwrap_or(0);
+            Some(TrashRecord {
+                name: t.remove("name")?,
+                original: t.remove("original").map(PathBuf::from),
+                deleted: UNIX_EPOCH + Duration::from_secs(secs),
+                size: t.get("size").and_then(|s| s.parse().ok()).unwrap_or(0),
+                reason: t.remove("reason").unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// `s` as a TOML basic string, escaping quotes, backslashes, and control characters.
+fn toml_string(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// The value of a TOML basic string written by [`toml_string`]; `None` when unterminated.
+fn parse_toml_string(value: &str) -> Option<String> {
+    let mut chars = value.strip_prefix('"')?.chars();
+    let mut out = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(out),
+            '\\' => match chars.next()? {
+                'n' => out.push('\n'),
+                't' => out.push('\t'),
+                'r' => out.push('\r'),
+                'u' => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    out.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                c => out.push(c),
+            },
+            c => out.push(c),
+        }
+    }
+}
+
+/// Entries in the trash under `base_path`; empty when there is no trash folder. Details come
+/// from the trash index, which first records any entries it lacks.
+pub(crate) fn list_trash(base_path: &Path) -> Vec<TrashItem> {
+    let Ok(rd) = fs::read_dir(base_path.join(TRASH_DIR)) else {
+        return Vec::new();
+    };
+    let index = TrashIndex::load_complete(base_path);
+    rd.flatten()
+        .filter(|e| e.file_name() != EMPTYING_DIR)
+        .filter_map(|e| {
+            let record = index.get(&e.file_name().to_string_lossy())?;
+            Some(TrashItem::new(e.path(), record))
+        })
+        .collect()
+}
+
+/// The trashed try whose name (or original name), date prefix ignored, is exactly `norm` (a
+/// normalized query); the most recently trashed one when there are several.
+pub(crate) fn find_trashed(base_path: &Path, norm: &str) -> Option<TrashItem> {
+    let rd = fs::read_dir(base_path.join(TRASH_DIR)).ok()?;
+    let index = TrashIndex::load_complete(base_path);
+    let entries: Vec<(PathBuf, fs::Metadata, &TrashRecord)> = rd
+        .flatten()
+        .filter(|e| e.file_name() != EMPTYING_DIR)
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            let record = index.get(&e.file_name().to_string_lossy())?;
+            Some((e.path(), meta, record))
+        })
+        .collect();
+    let matches = |(path, _, record): &&(PathBuf, fs::Metadata, &TrashRecord)| {
+        let original = record.original.as_deref().and_then(Path::file_name);
+        [path.file_name(), original]
+            .into_iter()
+            .flatten()
+            .any(|name| find_exact_match([&*name.to_string_lossy()], norm).is_some())
+    };
+    // The index keeps whole seconds; the modification time orders entries trashed within one
+    let trashed = |(_, meta, record): &&(PathBuf, fs::Metadata, &TrashRecord)| {
+        (record.deleted, meta.modified().unwrap_or(UNIX_EPOCH))
+    };
+    let (path, _, record) = entries.iter().filter(matches).max_by_key(trashed)?;
+    Some(TrashItem::new(path.clone(), record))
+}
+
+/// Moves `item` out of the trash back to where it was trashed from (recreating missing
+/// parent folders), or into `base_path` under its name when the index does not know, and
+/// returns the new path. Fails with [`io::ErrorKind::AlreadyExists`] when that path is taken.
+pub(crate) fn restore(base_path: &Path, item: &TrashItem) -> io::Result<PathBuf> {
+    let dest = item
+        .original
+        .clone()
+        .unwrap_or_else(|| base_path.join(&item.name));
+    if dest.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", dest.display()),
+        ));
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = ProgressLine::new(format!("Restoring {}", item.name));
+    move_tree(&item.path, &dest, |done, total| line.update(done, total))?;
+    // Losing the record only costs the details of an entry that is gone anyway
+    let _ = TrashIndex::load(base_path).save(base_path);
+    Ok(dest)
+}
+
+/// Moves the try at `path` into the trash under `base_path`, numbering the name if the trash
+/// already holds one like it, and records where it came from, its size, and `reason` in the
+/// trash index. The entry's modification time is set to now as well where possible, which
+/// orders entries trashed within the same second.
+pub(crate) fn move_to_trash(base_path: &Path, path: &Path, reason: &str) -> io::Result<PathBuf> {
+    let trash = base_path.join(TRASH_DIR);
+    fs::create_dir_all(&trash)?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut dest = trash.join(&name);
+    let mut n = 2;
+    while dest.exists() {
+        dest = trash.join(format!("{name}-{n}"));
+        n += 1;
+    }
+    let original = std::path::absolute(path)?;
+    let size = if path.is_dir() {
+        calculate_dir_size(path)
+    } else {
+        fs::metadata(path).map_or(0, |m| m.len())
+    };
+    let mut line = ProgressLine::new(format!("Moving {name} to the trash"));
+    move_tree(path, &dest, |done, total| line.update(done, total))?;
+    drop(line);
+    forget_pending_clone(base_path, path);
+    let now = SystemTime::now();
+    let mut index = TrashIndex::load(base_path);
+    index.insert(TrashRecord {
+        name: dest
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        original: Some(original),
+        deleted: now,
+        size,
+        reason: reason.to_string(),
+    });
+    // The entry is trashed either way; without its record it is listed from the folder alone
+    let _ = index.save(base_path);
+    // Only a tiebreak for sorting, so failing here (e.g. directories on Windows) is fine;
+    // links are skipped because opening one would touch its target instead
+    if fs::symlink_metadata(&dest).is_ok_and(|m| !m.file_type().is_symlink()) {
+        let _ = fs::File::open(&dest).and_then(|f| f.set_modified(now));
+    }
+    Ok(dest)
+}
+
+/// Indices of `items` to purge under `policy`, oldest first: everything past the retention
+/// period, then the oldest of the rest until the total fits the size cap.
+pub(crate) fn plan_prune(items: &[TrashItem], policy: &TrashPolicy, now: SystemTime) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by_key(|&i| items[i].trashed);
+    let expired = |item: &TrashItem| {
+        policy
+            .retention
+            .is_some_and(|keep| now.duration_since(item.trashed).is_ok_and(|age| age > keep))
+    };
+    let mut remaining: u64 = items.iter().filter(|t| !expired(t)).map(|t| t.size).sum();
+    let mut purge = Vec::new();
+    for i in order {
+        if expired(&items[i]) {
+            purge.push(i);
+        } else if policy.max_bytes.is_some_and(|cap| remaining > cap) {
+            remaining -= items[i].size;
+            purge.push(i);
+        }
+    }
+    purge
+}
+
+/// Whether the trash under `base_path` is due to be pruned on its own: it has not been in the
+/// past day. Marks it pruned now when it is; when the mark cannot be written, it is never due.
+pub(crate) fn auto_prune_due(base_path: &Path) -> bool {
+    let stamp = base_path.join(META_DIR).join(PRUNE_STAMP);
+    let day = Duration::from_secs(SECONDS_PER_DAY);
+    let recent = fs::metadata(&stamp)
+        .and_then(|m| m.modified())
+        .is_ok_and(|at| at.elapsed().is_ok_and(|age| age < day));
+    if recent {
+        return false;
+    }
+    fs::create_dir_all(base_path.join(META_DIR)).is_ok() && fs::write(&stamp, "").is_ok()
+}
+
+/// Applies `policy` to the trash under `base_path`, returning the purged items. Stops at the
+/// first entry that cannot be removed.
+pub(crate) fn prune(base_path: &Path, policy: &TrashPolicy) -> io::Result<Vec<TrashItem>> {
+    let items = list_trash(base_path);
+    let mut purged = Vec::new();
+    for i in plan_prune(&items, policy, SystemTime::now()) {
+        let item = &items[i];
+        remove_entry(&item.path)?;
+        purged.push(item.clone());
+    }
+    if !purged.is_empty() {
+        let _ = TrashIndex::load(base_path).save(base_path);
+    }
+    Ok(purged)
+}
+
+/// Removes `items` from the trash under `base_path` one at a time, calling `progress` after
+/// each, and returns the removed ones. Each item is first moved into a staging folder the
+/// trash does not list, so an interrupted run never leaves a half-deleted item behind;
+/// leftovers of such a run are removed first. Setting `cancel` stops before the next item.
+pub(crate) fn empty(
+    base_path: &Path,
+    items: &[TrashItem],
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(&TrashItem),
+) -> io::Result<Vec<TrashItem>> {
+    let staging = base_path.join(TRASH_DIR).join(EMPTYING_DIR);
+    if staging.exists() {
+        crate::mounts::remove_tree(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+    let mut removed = Vec::new();
+    for item in items {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        let staged = staging.join(&item.name);
+        fs::rename(&item.path, &staged)?;
+        remove_entry(&staged)?;
+        progress(item);
+        removed.push(item.clone());
+    }
+    fs::remove_dir(&staging)?;
+    let _ = TrashIndex::load(base_path).save(base_path);
+    Ok(removed)
+}
+
+fn remove_entry(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        crate::mounts::remove_tree(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
@@ -1,5 +1,10 @@
 use crossterm::{
-    cursor, execute,
+    cursor,
+    event::{
+        Event, KeyCode, KeyEvent, KeyModifiers, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
     style::{Attribute, Color, SetAttribute, SetForegroundColor},
     terminal::{
         self, ClearType, EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode,
@@ -7,46 +12,111 @@ use crossterm::{
     },
     tty::IsTty,
 };
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use std::cmp::min;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+use std::thread::{self, ThreadId};
 
 use crate::error::Result;
+use crate::events::EventSource;
+use crate::i18n::{Msg, tr, trf};
+
+use crate::model::{MatchList, TryDir};
+use crate::score::match_positions;
+use crate::status::Severity;
+use crate::storage::NameCheck;
 
-use crate::model::TryDir;
+/// Below this many columns rows drop their metadata and the header its long title, so the
+/// names keep the room.
+const NARROW_WIDTH: u16 = 40;
 
 pub struct TermGuard;
 
+/// The thread holding the live [`TermGuard`], if any; the panic hook restores the terminal
+/// only for a panic on that thread, since other threads' panics may be recovered from.
+static TERM_OWNER: Mutex<Option<ThreadId>> = Mutex::new(None);
+
 impl TermGuard {
     /// Enables raw mode and hides the cursor; restored automatically on drop via `Drop`.
+    /// Also asks for disambiguated key codes so modified keys like Shift-Enter are reported on
+    /// terminals implementing the kitty keyboard protocol; others ignore the request.
     pub fn new() -> Result<Self> {
         enable_raw_mode()?;
+        *TERM_OWNER.lock().unwrap_or_else(PoisonError::into_inner) = Some(thread::current().id());
         let mut err = io::stderr();
         let _ = execute!(err, EnterAlternateScreen, cursor::Hide);
+        let _ = execute!(
+            err,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        );
         Ok(Self)
     }
 }
 impl Drop for TermGuard {
     fn drop(&mut self) {
-        let _ = disable_raw_mode();
-        let mut err = io::stderr();
-        // Leave alt screen, clear, and restore cursor visibility
-        let _ = execute!(
-            err,
-            terminal::Clear(ClearType::All),
-            cursor::MoveTo(0, 0),
-            cursor::Show,
-            LeaveAlternateScreen
-        );
+        restore_terminal();
     }
 }
 
+/// Whether a [`TermGuard`] is live; `Some(true)` when it belongs to the calling thread.
+pub(crate) fn terminal_owned() -> Option<bool> {
+    let owner = *TERM_OWNER.lock().unwrap_or_else(PoisonError::into_inner);
+    owner.map(|id| id == thread::current().id())
+}
+
+/// Undoes [`TermGuard::new`]: raw mode off, main screen back, cursor shown. Does nothing
+/// once restored, so the guard's drop after the panic hook leaves the report alone.
+pub(crate) fn restore_terminal() {
+    if TERM_OWNER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+        .is_none()
+    {
+        return;
+    }
+    let _ = disable_raw_mode();
+    let mut err = io::stderr();
+    let _ = execute!(err, PopKeyboardEnhancementFlags);
+    // Leave alt screen, clear, and restore cursor visibility
+    let _ = execute!(
+        err,
+        terminal::Clear(ClearType::All),
+        cursor::MoveTo(0, 0),
+        cursor::Show,
+        LeaveAlternateScreen
+    );
+}
+
 /// Display width accounting for Unicode (wide) characters.
 pub(crate) fn display_width(s: &str) -> usize {
     UnicodeWidthStr::width(s)
 }
 
+/// `s` cut to at most `width` columns, ending in `…` when something was cut.
+pub(crate) fn fit(s: &str, width: usize) -> String {
+    if display_width(s) <= width {
+        return s.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for ch in s.chars() {
+        let w = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(ch);
+        used += w;
+    }
+    if width > 0 {
+        out.push('…');
+    }
+    out
+}
+
 /// Formats an optional timestamp as a concise relative string like `3h ago`.
 pub(crate) fn format_relative_time(t: Option<std::time::SystemTime>) -> String {
     const JUST_NOW_MAX: u64 = 9; // seconds
@@ -79,42 +149,54 @@ pub(crate) fn format_relative_time(t: Option<std::time::SystemTime>) -> String {
     }
 }
 
-/// Writes `s` with the given attribute and optional foreground color; resets color afterward.
-fn colors_enabled_stderr(err: &io::Stderr) -> bool {
-    if !err.is_tty() {
-        return false;
-    }
-    if std::env::var_os("NO_COLOR").is_some() {
-        return false;
-    }
-    if let Ok(v) = std::env::var("CLICOLOR_FORCE")
-        && v != "0"
-    {
-        return true;
-    }
-    if let Ok(v) = std::env::var("CLICOLOR")
-        && v == "0"
-    {
-        return false;
+/// Compact length of time: `45m`, `5h`, or `3d` (rounded up, so a little time is never `0`).
+pub(crate) fn format_duration(d: std::time::Duration) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 3_600;
+    const DAY: u64 = 86_400;
+    let secs = d.as_secs();
+    if secs < HOUR {
+        format!("{}m", secs.div_ceil(MINUTE).max(1))
+    } else if secs < DAY {
+        format!("{}h", secs.div_ceil(HOUR))
+    } else {
+        format!("{}d", secs.div_ceil(DAY))
     }
-    true
 }
 
+/// Whether stderr output should be styled, per `--color` and the environment.
+fn colors_enabled_stderr(err: &io::Stderr) -> bool {
+    crate::output::colors_enabled(err.is_tty())
+}
+
+/// Writes `s` with the given attribute and optional foreground color; resets color afterward.
 pub(crate) fn styled(
     err: &mut io::Stderr,
     attr: Attribute,
     fg: Option<Color>,
     s: &str,
 ) -> Result<()> {
-    if colors_enabled_stderr(err) {
+    let color = colors_enabled_stderr(err);
+    write_styled(err, color, attr, fg, s)
+}
+
+/// [`styled`] for any writer, with the color decision made by the caller.
+fn write_styled(
+    out: &mut impl Write,
+    color: bool,
+    attr: Attribute,
+    fg: Option<Color>,
+    s: &str,
+) -> Result<()> {
+    if color {
         if let Some(c) = fg {
-            execute!(err, SetForegroundColor(c))?;
+            execute!(out, SetForegroundColor(c))?;
         }
-        execute!(err, SetAttribute(attr))?;
-        write!(err, "{s}")?;
-        execute!(err, SetForegroundColor(Color::Reset))?;
+        execute!(out, SetAttribute(attr))?;
+        write!(out, "{s}")?;
+        execute!(out, SetForegroundColor(Color::Reset))?;
     } else {
-        write!(err, "{s}")?;
+        write!(out, "{s}")?;
     }
     Ok(())
 }
@@ -128,56 +210,310 @@ pub(crate) fn highlight(err: &mut io::Stderr, s: &str) -> Result<()> {
 }
 
 /// Styled warning line: prints "Warning: " in bold yellow, then the message, and a newline.
+/// Suppressed by `--quiet`.
 pub(crate) fn warn(err: &mut io::Stderr, msg: &str) -> Result<()> {
-    highlight(err, "Warning: ")?;
-    execute!(err, SetAttribute(Attribute::Reset))?;
+    if crate::output::quiet() {
+        return Ok(());
+    }
+    highlight(err, &format!("{}: ", tr(Msg::WarningLabel)))?;
+    reset_attributes(err)?;
     writeln!(err, "{msg}")?;
     Ok(())
 }
 
 /// Styled error line: prints "Error: " in bold red, then the message, and a newline.
 pub(crate) fn error(err: &mut io::Stderr, msg: &str) -> Result<()> {
-    styled(err, Attribute::Bold, Some(Color::Red), "Error: ")?;
-    execute!(err, SetAttribute(Attribute::Reset))?;
+    let label = format!("{}: ", tr(Msg::ErrorLabel));
+    styled(err, Attribute::Bold, Some(Color::Red), &label)?;
+    reset_attributes(err)?;
     writeln!(err, "{msg}")?;
     Ok(())
 }
 
-/// Writes text highlighting the next matching characters from `query` in bold, case-insensitively.
-pub(crate) fn write_highlighted(
-    err: &mut io::Stderr,
-    text: &str,
-    query: &str,
-    is_sel: bool,
-) -> Result<()> {
-    if query.is_empty() {
-        write!(err, "{text}")?;
-        return Ok(());
+/// Resets styling on `out` when it is colored.
+fn reset_if(out: &mut impl Write, color: bool) -> Result<()> {
+    if color {
+        execute!(out, SetAttribute(Attribute::Reset))?;
     }
-    let tl_chars: Vec<char> = text.to_lowercase().chars().collect();
-    let text_chars: Vec<char> = text.chars().collect();
-    let q_chars: Vec<char> = query.to_lowercase().chars().collect();
-    let mut qi = 0usize;
+    Ok(())
+}
 
-    for i in 0..text_chars.len() {
-        let ch = text_chars[i];
-        if qi < q_chars.len() && tl_chars[i] == q_chars[qi] {
-            // Don't reset all attributes, just bold and color
-            highlight(err, &ch.to_string())?;
-            if is_sel {
-                // If selected, we need to re-apply the reverse attribute
-                execute!(err, SetAttribute(Attribute::Reverse))?;
-            } else {
-                execute!(err, SetAttribute(Attribute::Reset))?;
+/// Clears attributes left by [`styled`]; writes nothing when colors are off.
+fn reset_attributes(err: &mut io::Stderr) -> Result<()> {
+    if colors_enabled_stderr(err) {
+        execute!(err, SetAttribute(Attribute::Reset))?;
+    }
+    Ok(())
+}
+
+/// Outcome of feeding one key event into a [`LineInput`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PromptKey {
+    Continue,
+    Submit,
+    Cancel,
+}
+
+/// Editable single-line buffer shared by modal prompts; kept free of terminal IO so it can be
+/// driven from tests.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LineInput {
+    pub(crate) buf: String,
+}
+
+impl LineInput {
+    /// Applies a key press: Enter submits, Esc/Ctrl-C cancel, Backspace edits, text appends.
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> PromptKey {
+        match (key.code, key.modifiers) {
+            (KeyCode::Enter, _) => PromptKey::Submit,
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => PromptKey::Cancel,
+            (KeyCode::Backspace, _) => {
+                self.buf.pop();
+                PromptKey::Continue
             }
-            qi += 1;
-        } else {
-            write!(err, "{ch}")?;
+            (KeyCode::Char(ch), mods) if !mods.contains(KeyModifiers::CONTROL) => {
+                self.buf.push(ch);
+                PromptKey::Continue
+            }
+            _ => PromptKey::Continue,
+        }
+    }
+}
+
+/// A line of live feedback drawn under a prompt's input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Feedback {
+    Info(String),
+    Warn(String),
+}
+
+/// Callback producing live feedback for the current prompt input.
+pub(crate) type FeedbackFn<'a> = &'a dyn Fn(&str) -> Vec<Feedback>;
+
+/// Full-screen single-line prompt. Drawn while raw mode stays on, so callers inside the
+/// selector do not need to flip terminal modes themselves.
+pub(crate) struct Prompt<'a> {
+    /// Bold cyan heading on the first line.
+    pub title: &'a str,
+    /// Informational lines shown between the heading and the input line.
+    pub body: Vec<String>,
+    /// Bold red lines after the body, for risks the user must not miss.
+    pub warnings: Vec<String>,
+    /// Bold yellow label written directly before the input (e.g. `"> "`).
+    pub label: &'a str,
+    /// Dimmed fixed text shown between the label and the typed input (e.g. a date prefix).
+    pub prefix: String,
+    /// Recomputed on every keystroke to show validation below the input line.
+    pub feedback: Option<FeedbackFn<'a>>,
+}
+
+impl<'a> Prompt<'a> {
+    pub(crate) fn new(title: &'a str) -> Self {
+        Self {
+            title,
+            body: Vec::new(),
+            warnings: Vec::new(),
+            label: "> ",
+            prefix: String::new(),
+            feedback: None,
         }
     }
+
+    fn draw(&self, con: &mut Console<'_>, input: &str) -> Result<()> {
+        let color = con.caps.color;
+        let out = &mut con.out;
+        if con.caps.screen {
+            execute!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        }
+        write_styled(out, color, Attribute::Bold, Some(Color::Cyan), self.title)?;
+        reset_if(out, color)?;
+        write!(out, "\r\n\r\n")?;
+        for line in &self.body {
+            write!(out, "{line}\r\n")?;
+        }
+        if !self.warnings.is_empty() {
+            write!(out, "\r\n")?;
+        }
+        for line in &self.warnings {
+            write_styled(out, color, Attribute::Bold, Some(Color::Red), line)?;
+            reset_if(out, color)?;
+            write!(out, "\r\n")?;
+        }
+        if !self.body.is_empty() || !self.warnings.is_empty() {
+            write!(out, "\r\n")?;
+        }
+        write_styled(out, color, Attribute::Bold, Some(Color::Yellow), self.label)?;
+        reset_if(out, color)?;
+        if !self.prefix.is_empty() {
+            write_styled(out, color, Attribute::Dim, Some(Color::Grey), &self.prefix)?;
+            reset_if(out, color)?;
+        }
+        write!(out, "{input}")?;
+        if let Some(feedback) = self.feedback {
+            let lines = feedback(input);
+            if !lines.is_empty() {
+                execute!(out, cursor::SavePosition)?;
+                write!(out, "\r\n")?;
+                for line in lines {
+                    write!(out, "\r\n")?;
+                    match line {
+                        Feedback::Info(msg) => {
+                            write_styled(out, color, Attribute::Dim, Some(Color::Grey), &msg)?
+                        }
+                        Feedback::Warn(msg) => {
+                            write_styled(out, color, Attribute::Bold, Some(Color::Yellow), &msg)?
+                        }
+                    }
+                    reset_if(out, color)?;
+                }
+                execute!(out, cursor::RestorePosition)?;
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Where the selector and its prompts draw and read keys: stderr and the terminal, or a
+/// buffer and a [`crate::events::Script`] when driven headlessly.
+pub(crate) struct Console<'a> {
+    pub out: &'a mut dyn Write,
+    pub events: &'a mut dyn EventSource,
+    pub caps: TermCaps,
+}
+
+/// Runs `prompt` until the user submits or cancels. Returns the trimmed input on Enter and
+/// `None` on Esc/Ctrl-C. The cursor is shown while typing and hidden again afterwards.
+pub(crate) fn prompt_line(con: &mut Console<'_>, prompt: &Prompt<'_>) -> Result<Option<String>> {
+    let mut input = LineInput::default();
+    execute!(&mut con.out, cursor::Show)?;
+    let outcome = loop {
+        prompt.draw(con, &input.buf)?;
+        if let Event::Key(key) = con.events.read()? {
+            match input.handle_key(key) {
+                PromptKey::Continue => {}
+                PromptKey::Submit => break Some(input.buf.trim().to_string()),
+                PromptKey::Cancel => break None,
+            }
+        }
+    };
+    execute!(&mut con.out, cursor::Hide)?;
+    Ok(outcome)
+}
+
+/// Asks the user to type `token` to confirm a destructive action; anything else declines.
+/// `warnings` are drawn in bold red below `body`.
+pub(crate) fn confirm(
+    con: &mut Console<'_>,
+    title: &str,
+    body: Vec<String>,
+    warnings: Vec<String>,
+    token: &str,
+) -> Result<bool> {
+    let label = trf(Msg::ConfirmToken, &[("token", &token)]);
+    let prompt = Prompt {
+        title,
+        body,
+        warnings,
+        label: &label,
+        prefix: String::new(),
+        feedback: None,
+    };
+    Ok(prompt_line(con, &prompt)?.as_deref() == Some(token))
+}
+
+/// Text progress bar such as `[█████░░░░░]  50%` for `percent` (clamped to 100).
+pub(crate) fn progress_bar(percent: u64, width: usize) -> String {
+    let percent = percent.min(100);
+    let filled = (percent as usize * width).div_ceil(100);
+    format!(
+        "[{}{}] {percent:>3}%",
+        "█".repeat(filled),
+        "░".repeat(width - filled)
+    )
+}
+
+/// Draws `label` and a progress bar over the bottom line of the screen, leaving the rest of
+/// the frame as it was.
+pub(crate) fn draw_progress(
+    con: &mut Console<'_>,
+    term_h: u16,
+    label: &str,
+    percent: u64,
+) -> Result<()> {
+    const BAR_WIDTH: usize = 20;
+    let out = &mut con.out;
+    execute!(
+        out,
+        cursor::MoveTo(0, term_h.saturating_sub(1)),
+        terminal::Clear(ClearType::CurrentLine)
+    )?;
+    write!(out, "{label}  ")?;
+    let bar = progress_bar(percent, BAR_WIDTH);
+    write_styled(out, con.caps.color, Attribute::Dim, Some(Color::Grey), &bar)?;
+    reset_if(out, con.caps.color)?;
+    out.flush()?;
     Ok(())
 }
 
+/// Progress of a long copy outside the selector (a move across filesystems), drawn as one
+/// line on stderr that is redrawn in place and cleared when dropped. Stays silent unless
+/// stderr is a terminal and `--quiet` is off.
+pub(crate) struct ProgressLine {
+    label: String,
+    enabled: bool,
+    shown: Option<u64>,
+}
+
+impl ProgressLine {
+    pub(crate) fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            enabled: io::stderr().is_tty() && !crate::output::quiet(),
+            shown: None,
+        }
+    }
+
+    /// Redraws the line for `done` of `total` bytes when the percentage changed.
+    pub(crate) fn update(&mut self, done: u64, total: u64) {
+        const BAR_WIDTH: usize = 20;
+        let percent = (done * 100).checked_div(total).unwrap_or(100);
+        if !self.enabled || self.shown == Some(percent) {
+            return;
+        }
+        self.shown = Some(percent);
+        let mut err = io::stderr();
+        let _ = execute!(
+            err,
+            cursor::MoveToColumn(0),
+            terminal::Clear(ClearType::CurrentLine)
+        );
+        let _ = write!(err, "{}  ", self.label);
+        let _ = dim(
+            &mut err,
+            &format!(
+                "{} {}/{}",
+                progress_bar(percent, BAR_WIDTH),
+                crate::util::format_human_size(done),
+                crate::util::format_human_size(total)
+            ),
+        );
+        let _ = err.flush();
+    }
+}
+
+impl Drop for ProgressLine {
+    fn drop(&mut self) {
+        if self.shown.is_some() {
+            let _ = execute!(
+                io::stderr(),
+                cursor::MoveToColumn(0),
+                terminal::Clear(ClearType::CurrentLine)
+            );
+        }
+    }
+}
+
 /// Computes the viewport scroll and end index for a list UI given the current cursor position.
 /// Returns (scroll, end), where end is exclusive and clamped to total.
 pub(crate) fn compute_viewport(
@@ -196,139 +532,787 @@ pub(crate) fn compute_viewport(
     (s, end)
 }
 
+/// What a frame on a terminal of a given height shows besides the list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Layout {
+    /// Title line and the separator below it.
+    pub header: bool,
+    /// Key hints line.
+    pub hints: bool,
+    /// Rows available for list entries.
+    pub rows: usize,
+}
+
+/// Lays out a frame `term_h` lines tall with `section_rows` section headers. Short
+/// terminals give up the key hints first, then the header, so that at least
+/// `MIN_VISIBLE_ITEMS` entries stay visible.
+pub(crate) fn layout(term_h: u16, section_rows: usize) -> Layout {
+    // Search line, spacing, "Create new" gap, bottom separator, and status line
+    const BASE_LINES: usize = 5;
+    const HEADER_LINES: usize = 2;
+    const HINT_LINES: usize = 1;
+    const MIN_VISIBLE_ITEMS: usize = 3;
+    let avail = (term_h as usize).saturating_sub(section_rows);
+    let fits = |chrome: usize| avail >= chrome + MIN_VISIBLE_ITEMS;
+    let (header, hints) = if fits(BASE_LINES + HEADER_LINES + HINT_LINES) {
+        (true, true)
+    } else if fits(BASE_LINES + HEADER_LINES) {
+        (true, false)
+    } else {
+        (false, false)
+    };
+    let chrome =
+        BASE_LINES + if header { HEADER_LINES } else { 0 } + if hints { HINT_LINES } else { 0 };
+    Layout {
+        header,
+        hints,
+        rows: avail.saturating_sub(chrome).max(1),
+    }
+}
+
+/// Dim heading drawn above the match at position `start`; a `start` past the last match
+/// heads an empty (collapsed) section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SectionHeader {
+    pub start: usize,
+    pub label: String,
+}
+
+/// Compact workspace summary for the header: entry count, total size, and base path.
+/// `sized` is how many entries contributed to `bytes`; a partial total is marked with `≥`.
+pub(crate) fn format_workspace_stats(total: usize, sized: usize, bytes: u64, base: &str) -> String {
+    let noun = if total == 1 { "try" } else { "tries" };
+    let size = crate::util::format_human_size(bytes);
+    let size = if sized < total {
+        format!("≥{size}")
+    } else {
+        size
+    };
+    format!("{total} {noun} · {size} · {base}")
+}
+
 pub struct RenderCtx<'a> {
     pub term_w: u16,
     pub term_h: u16,
     pub cursor: usize,
     pub scroll: usize,
     pub input_buf: &'a str,
-    pub tries: &'a [TryDir],
-    pub status_msg: Option<String>,
+    /// Free text of the query (filter tokens removed), used for match highlighting.
+    pub query_text: &'a str,
+    pub tries: MatchList<'a>,
+    /// Status line and how it is styled.
+    pub status_msg: Option<(Severity, String)>,
     pub show_delete_pending: bool,
+    /// Validation of the query as a new name, shown on the "Create new" row.
+    pub name_check: Option<&'a NameCheck>,
+    /// Number of entries before filtering, for the match counter.
+    pub total_count: usize,
+    /// Close names offered when the query matches nothing.
+    pub suggestions: &'a [String],
+    /// Workspace summary shown right-aligned on the title line.
+    pub header_stats: &'a str,
+    /// Plain candidate lines (`try pick`): no icons, metadata, or "Create new" row.
+    pub pick_mode: bool,
+    /// Recency sections of the empty-query view, in list order.
+    pub sections: &'a [SectionHeader],
+    /// Tries marked for a combined delete.
+    pub marked: &'a [PathBuf],
+    /// Tags the query filters on (`#tag` tokens), shown as chips under the search line.
+    pub chips: &'a [String],
+    /// The Alt-T tag list, drawn in place of the matches while open.
+    pub tag_picker: Option<TagPickerView<'a>>,
+}
+
+/// The Alt-T tag list: every tag with the number of tries carrying it, and the
+/// highlighted row. Tags among the chips are shown as selected.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TagPickerView<'a> {
+    pub tags: &'a [(String, usize)],
+    pub cursor: usize,
+}
+
+/// What the render target supports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TermCaps {
+    /// Emit colors and bold/dim styling.
+    pub color: bool,
+    /// Drawing the full-screen TUI in raw mode: clear first, end lines with `\r\n`, and
+    /// mark the selection in reverse video even without colors. Off for static listings
+    /// (`--render-once`), which end lines with `\n` and contain no escapes unless colored.
+    pub screen: bool,
+    /// Screen-reader mode (`ui.accessible`): plain labeled lines without box drawing, emoji,
+    /// or reverse video, ending with what is selected.
+    pub accessible: bool,
+    /// Never clear the whole screen (`ui.no_clear` / `NO_FLICKER`): frames are drawn over the
+    /// previous one, rewriting only the lines that changed (see [`paint_changes`]).
+    pub no_clear: bool,
+}
+
+impl TermCaps {
+    /// The interactive selector on stderr.
+    pub(crate) fn screen() -> Self {
+        Self {
+            color: colors_enabled_stderr(&io::stderr()),
+            screen: true,
+            accessible: crate::output::accessible(),
+            no_clear: crate::output::no_clear(),
+        }
+    }
+
+    /// A one-off listing on stdout, colored per `--color` and whether stdout is a terminal.
+    pub(crate) fn listing() -> Self {
+        Self {
+            color: crate::output::colors_enabled(io::stdout().is_tty()),
+            screen: false,
+            accessible: crate::output::accessible(),
+            no_clear: false,
+        }
+    }
 }
 
-/// Renders the interactive UI for the list of tries and the input query.
-pub(crate) fn render(err: &mut io::Stderr, ctx: &RenderCtx<'_>) -> Result<()> {
-    execute!(err, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+/// Render target: a writer plus what it supports.
+struct Canvas<'w, W: Write> {
+    out: &'w mut W,
+    caps: TermCaps,
+}
+
+impl<W: Write> Canvas<'_, W> {
+    fn text(&mut self, s: &str) -> Result<()> {
+        write!(self.out, "{s}")?;
+        Ok(())
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.text(if self.caps.screen { "\r\n" } else { "\n" })
+    }
+
+    fn dim(&mut self, s: &str) -> Result<()> {
+        write_styled(
+            self.out,
+            self.caps.color,
+            Attribute::Dim,
+            Some(Color::Grey),
+            s,
+        )
+    }
+
+    fn highlight(&mut self, s: &str) -> Result<()> {
+        write_styled(
+            self.out,
+            self.caps.color,
+            Attribute::Bold,
+            Some(Color::Yellow),
+            s,
+        )
+    }
+
+    fn alert(&mut self, s: &str) -> Result<()> {
+        write_styled(
+            self.out,
+            self.caps.color,
+            Attribute::Bold,
+            Some(Color::Red),
+            s,
+        )
+    }
+
+    fn attr(&mut self, attr: Attribute) -> Result<()> {
+        if self.caps.color || self.caps.screen {
+            execute!(self.out, SetAttribute(attr))?;
+        }
+        Ok(())
+    }
+
+    /// Writes text highlighting the characters `query` matched on (see
+    /// [`crate::score::match_positions`]) in bold.
+    fn highlighted(&mut self, text: &str, query: &str, is_sel: bool) -> Result<()> {
+        let positions = match_positions(text, query).unwrap_or_default();
+        let mut marked = positions.into_iter().peekable();
+        for (i, ch) in text.chars().enumerate() {
+            if marked.next_if_eq(&i).is_some() {
+                // Don't reset all attributes, just bold and color
+                self.highlight(&ch.to_string())?;
+                // The selected row needs its reverse attribute back
+                self.attr(if is_sel {
+                    Attribute::Reverse
+                } else {
+                    Attribute::Reset
+                })?;
+            } else {
+                write!(self.out, "{ch}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// One try row: arrow when selected, dot when marked, folder icon, highlighted name, and
+    /// right-aligned meta (git, size, age) fitted into `term_w`. Leaves the line open.
+    fn try_row(
+        &mut self,
+        t: &TryDir,
+        query: &str,
+        is_sel: bool,
+        is_marked: bool,
+        pick_mode: bool,
+        term_w: u16,
+    ) -> Result<()> {
+        // Compose and print prefix (arrow + mark + icon), measure width accurately
+        let arrow = if is_sel { '→' } else { ' ' };
+        let mark = if is_marked { '•' } else { ' ' };
+        let prefix = format!("{arrow}{mark}");
+        let icon = if pick_mode { "" } else { "📁 " };
+        let prefix = fit(&format!("{prefix}{icon}"), term_w as usize);
+        self.text(&prefix)?;
+        let prefix_w = display_width(&prefix);
+
+        // Selected row: enter reverse for the name portion only
+        if is_sel {
+            self.attr(Attribute::Reverse)?;
+        }
+        let name = fit(&t.basename, (term_w as usize).saturating_sub(prefix_w));
+        self.highlighted(&name, query, is_sel)?;
+
+        // Right-side meta: size and mtime
+        let size_text = t
+            .size
+            .map(crate::util::format_human_size)
+            .unwrap_or_else(|| "...".to_string());
+        let time_text = format_relative_time(t.mtime);
+        let mut git_text = t.git.as_ref().map(|g| g.indicator()).unwrap_or_default();
+        if let Some(behind) = t.behind.filter(|&n| n > 0) {
+            let sep = if git_text.is_empty() { "" } else { " " };
+            git_text = format!("{git_text}{sep}↓{behind}");
+        }
+        let mut meta = if git_text.is_empty() {
+            format!("{size_text}, {time_text}")
+        } else {
+            format!("{git_text}  {size_text}, {time_text}")
+        };
+        if t.pending_clone {
+            meta = format!("⚠ incomplete clone  {meta}");
+        }
+        if let Some(expires) = t.expires {
+            let flag = match expires.duration_since(std::time::SystemTime::now()) {
+                Ok(left) => format!("⌛ {} left", format_duration(left)),
+                Err(_) => "⌛ expired".to_string(),
+            };
+            meta = format!("{flag}  {meta}");
+        }
+
+        // Compute remaining columns; ensure we never overflow terminal width. Narrow
+        // terminals keep the whole width for the name.
+        let name_w = display_width(&name);
+        let left_w = prefix_w + name_w;
+        if !pick_mode && term_w >= NARROW_WIDTH && (left_w as u16) < term_w {
+            let rem = term_w as usize - left_w;
+            let meta_w = display_width(&meta);
+            self.attr(Attribute::Reset)?; // meta not reversed
+            if rem == 0 {
+                // Nothing fits
+            } else if meta_w >= rem {
+                // Print a space then a truncated meta to avoid wrap
+                self.text(" ")?;
+                let keep = rem.saturating_sub(1);
+                let truncated: String = meta.chars().take(keep).collect();
+                self.dim(&truncated)?;
+            } else {
+                // Right align meta within the remaining width
+                self.dim(&format!("{meta:>rem$}"))?;
+            }
+        }
+        self.attr(Attribute::Reset)?;
+        Ok(())
+    }
+}
+
+/// Writes `tries` as unselected selector rows, one per line, for `try show`.
+pub(crate) fn render_rows(
+    out: &mut impl Write,
+    caps: TermCaps,
+    tries: MatchList<'_>,
+    query: &str,
+    term_w: u16,
+) -> Result<()> {
+    let mut c = Canvas { out, caps };
+    for idx in 0..tries.len() {
+        if caps.accessible {
+            c.text(&spoken_row(&tries[idx], false))?;
+        } else {
+            c.try_row(&tries[idx], query, false, false, false, term_w)?;
+        }
+        c.newline()?;
+    }
+    c.out.flush()?;
+    Ok(())
+}
+
+/// Renders the interactive UI for the list of tries and the input query to `out`.
+pub(crate) fn render(out: &mut impl Write, caps: TermCaps, ctx: &RenderCtx<'_>) -> Result<()> {
+    let mut c = Canvas { out, caps };
+    if caps.screen && !caps.no_clear {
+        execute!(c.out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    }
+    if caps.accessible {
+        return render_accessible(&mut c, ctx);
+    }
     const MIN_SEPARATOR_WIDTH: usize = 1;
-    const RESERVED_LINES: u16 = 8; // header, spacing, footer, etc.
-    const MIN_VISIBLE_ITEMS: usize = 3;
     let sep_w = ctx.term_w.saturating_sub(1) as usize;
     let separator = "─".repeat(std::cmp::max(sep_w, MIN_SEPARATOR_WIDTH));
 
-    highlight(err, "📁 Try Directory Selection")?;
-    execute!(err, SetAttribute(Attribute::Reset))?;
-    write!(err, "\r\n")?;
-    dim(err, &separator)?;
-    execute!(err, SetAttribute(Attribute::Reset))?;
-    write!(err, "\r\n")?;
+    let width = ctx.term_w as usize;
+    let layout = layout(ctx.term_h, ctx.sections.len());
+    let max_visible = layout.rows;
+    if layout.header {
+        let title = tr(if ctx.pick_mode {
+            Msg::TitlePick
+        } else if ctx.term_w < NARROW_WIDTH {
+            Msg::TitleShort
+        } else {
+            Msg::Title
+        });
+        let title = fit(title, width);
+        c.highlight(&title)?;
+        c.attr(Attribute::Reset)?;
+        // Right-align the workspace summary, dropping it when the terminal is too narrow
+        let stats_room = width.saturating_sub(display_width(&title) + 2);
+        let stats_w = display_width(ctx.header_stats);
+        if stats_w > 0 && stats_w <= stats_room {
+            // Padded by display width, as `{:>n}` counts wide glyphs as one column
+            c.text(&" ".repeat(stats_room - stats_w))?;
+            c.dim(ctx.header_stats)?;
+            c.attr(Attribute::Reset)?;
+        }
+        c.newline()?;
+        c.dim(&separator)?;
+        c.attr(Attribute::Reset)?;
+        c.newline()?;
+    }
 
-    write!(err, "Search: {}", ctx.input_buf)?;
-    write!(err, "\r\n\r\n")?;
+    let search = trf(Msg::Search, &[("query", &ctx.input_buf)]);
+    c.text(&fit(&search, width))?;
+    // Match counter after the query, e.g. "3/42", when it fits
+    let counter = format!("{}/{}", ctx.tries.len(), ctx.total_count);
+    let mut used = display_width(&search);
+    if !ctx.input_buf.is_empty() && used + 2 + display_width(&counter) <= width {
+        c.text("  ")?;
+        c.dim(&counter)?;
+        c.attr(Attribute::Reset)?;
+        used += 2 + display_width(&counter);
+    }
+    let marked = trf(Msg::Marked, &[("count", &ctx.marked.len())]);
+    if !ctx.marked.is_empty() && used + 2 + display_width(&marked) <= width {
+        c.text("  ")?;
+        c.highlight(&marked)?;
+        c.attr(Attribute::Reset)?;
+    }
+    c.newline()?;
+    // Selected tags take the spacing line, so the layout stays the same
+    if !ctx.chips.is_empty() {
+        let chips: Vec<String> = ctx.chips.iter().map(|t| format!("[#{t}]")).collect();
+        c.highlight(&fit(&chips.join(" "), width))?;
+        c.attr(Attribute::Reset)?;
+    }
+    c.newline()?;
 
-    let max_visible = usize::max(
-        ctx.term_h.saturating_sub(RESERVED_LINES) as usize,
-        MIN_VISIBLE_ITEMS,
-    );
-    const EXTRA_LIST_ROWS: usize = 1; // "Create new" row
-    let total = ctx.tries.len() + EXTRA_LIST_ROWS;
+    if let Some(picker) = ctx.tag_picker {
+        let (start, end) = compute_viewport(picker.cursor, 0, max_visible, picker.tags.len());
+        for (idx, (tag, count)) in picker.tags.iter().enumerate().take(end).skip(start) {
+            let is_sel = idx == picker.cursor;
+            let check = if ctx.chips.contains(tag) { 'x' } else { ' ' };
+            c.text(if is_sel { "→ " } else { "  " })?;
+            if is_sel {
+                c.attr(Attribute::Reverse)?;
+            }
+            c.text(&fit(&format!("[{check}] #{tag}"), width.saturating_sub(2)))?;
+            c.attr(Attribute::Reset)?;
+            c.dim(&format!("  {count}"))?;
+            c.attr(Attribute::Reset)?;
+            c.newline()?;
+        }
+        c.dim(&separator)?;
+        c.attr(Attribute::Reset)?;
+        c.newline()?;
+        if layout.hints && caps.screen {
+            c.dim(&fit(tr(Msg::HintsTags), width))?;
+            c.attr(Attribute::Reset)?;
+            c.newline()?;
+        }
+        c.out.flush()?;
+        return Ok(());
+    }
+
+    if ctx.tries.is_empty()
+        && let Some(check) = ctx.name_check
+    {
+        c.highlight(&fit(
+            &trf(Msg::NoMatchesCreates, &[("name", &check.final_name)]),
+            width,
+        ))?;
+        c.attr(Attribute::Reset)?;
+        c.newline()?;
+        if !ctx.suggestions.is_empty() {
+            let names = ctx.suggestions.join(", ");
+            c.dim(&fit(&trf(Msg::DidYouMean, &[("names", &names)]), width))?;
+            c.attr(Attribute::Reset)?;
+            c.newline()?;
+        }
+        c.newline()?;
+    } else if ctx.tries.is_empty() && ctx.pick_mode {
+        c.dim(tr(Msg::NoMatches))?;
+        c.attr(Attribute::Reset)?;
+        c.newline()?;
+        c.newline()?;
+    }
+
+    // "Create new" row
+    let extra_rows = if ctx.pick_mode { 0 } else { 1 };
+    let total = ctx.tries.len() + extra_rows;
 
     let (_, end) = compute_viewport(ctx.cursor, ctx.scroll, max_visible, total);
 
     for idx in ctx.scroll..end {
+        for section in ctx.sections.iter().filter(|s| s.start == idx) {
+            c.dim(&fit(&format!("── {}", section.label), width))?;
+            c.attr(Attribute::Reset)?;
+            c.newline()?;
+        }
         if idx == ctx.tries.len() && !ctx.tries.is_empty() {
-            write!(err, "\r\n")?;
+            c.newline()?;
         }
 
         let is_sel = idx == ctx.cursor;
         if idx < ctx.tries.len() {
             let t = &ctx.tries[idx];
-            // Compose and print prefix (arrow + icon), measure width accurately
-            let prefix = if is_sel { "→ " } else { "  " };
-            write!(err, "{prefix}")?;
-            write!(err, "📁 ")?;
-            let prefix_w = display_width(&format!("{}{}", prefix, "📁 "));
-
-            // Selected row: enter reverse for the name portion only
-            if is_sel {
-                execute!(err, SetAttribute(Attribute::Reverse))?;
-            }
-            write_highlighted(err, &t.basename, ctx.input_buf, is_sel)?;
-
-            // Right-side meta: size and mtime
-            let size_text = t
-                .size
-                .map(crate::util::format_human_size)
-                .unwrap_or_else(|| "...".to_string());
-            let time_text = format_relative_time(t.mtime);
-            let meta = format!("{size_text}, {time_text}");
-
-            // Compute remaining columns; ensure we never overflow terminal width
-            let name_w = display_width(&t.basename);
-            let left_w = prefix_w + name_w;
-            if (left_w as u16) < ctx.term_w {
-                let rem = ctx.term_w as usize - left_w;
-                let meta_w = meta.len(); // ASCII-only, width == len
-                execute!(err, SetAttribute(Attribute::Reset))?; // meta not reversed
-                if rem == 0 {
-                    // Nothing fits
-                } else if meta_w >= rem {
-                    // Print a space then a truncated meta to avoid wrap
-                    write!(err, " ")?;
-                    let keep = rem.saturating_sub(1);
-                    let truncated: String = meta.chars().take(keep).collect();
-                    dim(err, &truncated)?;
-                } else {
-                    // Right align meta within the remaining width
-                    let meta_fmt = format!("{meta:>rem$}");
-                    dim(err, &meta_fmt)?;
-                }
-            }
-            execute!(err, SetAttribute(Attribute::Reset))?;
+            c.try_row(
+                t,
+                ctx.query_text,
+                is_sel,
+                ctx.marked.contains(&t.path),
+                ctx.pick_mode,
+                ctx.term_w,
+            )?;
         } else {
             // New entry row
             if is_sel {
-                highlight(err, "→ ")?;
-                execute!(err, SetAttribute(Attribute::Reset))?;
+                c.highlight(&fit("→ ", width))?;
+                c.attr(Attribute::Reset)?;
             } else {
-                write!(err, "  ")?;
+                c.text(&fit("  ", width))?;
             }
-            write!(err, "+ ")?;
+            c.text(&fit("+ ", width.saturating_sub(2)))?;
             if is_sel {
-                execute!(err, SetAttribute(Attribute::Reverse))?;
+                c.attr(Attribute::Reverse)?;
             }
-            if ctx.input_buf.is_empty() {
-                write!(err, "Create new")?;
-            } else {
-                write!(err, "Create new: {}", ctx.input_buf)?;
+            let label = match ctx.name_check {
+                None => tr(Msg::CreateNew).to_string(),
+                Some(check) => trf(Msg::CreateNewNamed, &[("name", &check.final_name)]),
+            };
+            let room = width.saturating_sub(4);
+            c.text(&fit(&label, room))?;
+            c.attr(Attribute::Reset)?;
+            if let Some(existing) = ctx.name_check.and_then(|c| c.collision.as_deref()) {
+                let exists = trf(Msg::Exists, &[("name", &existing)]);
+                if display_width(&label) + 2 + display_width(&exists) <= room {
+                    c.text("  ")?;
+                    c.highlight(&exists)?;
+                    c.attr(Attribute::Reset)?;
+                }
             }
-            execute!(err, SetAttribute(Attribute::Reset))?;
         }
-        write!(err, "\r\n")?;
+        c.newline()?;
     }
 
     // Separator below list and new-entry row
-    dim(err, &separator)?;
-    execute!(err, SetAttribute(Attribute::Reset))?;
-    write!(err, "\r\n")?;
+    c.dim(&separator)?;
+    c.attr(Attribute::Reset)?;
+    c.newline()?;
 
-    // Instructions
-    dim(
-        err,
-        "↑↓: Navigate  Enter: Select  Ctrl-D: Delete  ESC: Cancel",
-    )?;
-    execute!(err, SetAttribute(Attribute::Reset))?;
-    write!(err, "\r\n")?;
+    // Instructions, abbreviated when the full list does not fit and left out when the
+    // terminal is too short; a static listing takes no input, so it has none
+    if layout.hints && caps.screen {
+        let (full, short) = if ctx.pick_mode {
+            (tr(Msg::HintsPick), tr(Msg::HintsPickShort))
+        } else {
+            (tr(Msg::Hints), tr(Msg::HintsShort))
+        };
+        let hints = if display_width(full) <= width {
+            full
+        } else {
+            short
+        };
+        c.dim(&fit(hints, width))?;
+        c.attr(Attribute::Reset)?;
+        c.newline()?;
+    }
 
     // Status/prompt line
     if ctx.show_delete_pending {
-        dim(err, "delete pending: press d to confirm; Esc to cancel")?;
-        execute!(err, SetAttribute(Attribute::Reset))?;
-    } else if let Some(s) = &ctx.status_msg {
-        dim(err, s)?;
-        execute!(err, SetAttribute(Attribute::Reset))?;
+        c.dim(&fit(tr(Msg::DeletePending), width))?;
+        c.attr(Attribute::Reset)?;
+    } else if let Some((severity, s)) = &ctx.status_msg {
+        let s = fit(s, width);
+        match severity {
+            Severity::Info => c.dim(&s)?,
+            Severity::Warn => c.highlight(&s)?,
+            Severity::Error => c.alert(&s)?,
+        }
+        c.attr(Attribute::Reset)?;
+    }
+
+    c.out.flush()?;
+    Ok(())
+}
+
+/// Draws `frame` (lines ending in `\r\n`) from the top-left corner over `previous`, the frame
+/// already on screen: lines equal to the previous ones are skipped, changed ones are
+/// rewritten and cleared to their end, and only the rows below a shorter frame are cleared.
+/// Without a previous frame every line is written.
+pub(crate) fn paint_changes(
+    out: &mut impl Write,
+    frame: &str,
+    previous: Option<&str>,
+) -> Result<()> {
+    let old: Vec<&str> = previous.map_or_else(Vec::new, |p| p.split("\r\n").collect());
+    let lines: Vec<&str> = frame.split("\r\n").collect();
+    for (row, line) in lines.iter().enumerate() {
+        if previous.is_some() && old.get(row) == Some(line) {
+            continue;
+        }
+        execute!(out, cursor::MoveTo(0, row as u16))?;
+        write!(out, "{line}")?;
+        execute!(out, terminal::Clear(ClearType::UntilNewLine))?;
+    }
+    if previous.is_none() || old.len() > lines.len() {
+        execute!(
+            out,
+            cursor::MoveTo(0, lines.len() as u16),
+            terminal::Clear(ClearType::FromCursorDown)
+        )?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// [`render`] for screen readers: one plain line per item, read top to bottom, with the
+/// status and then the selection announced last.
+fn render_accessible<W: Write>(c: &mut Canvas<'_, W>, ctx: &RenderCtx<'_>) -> Result<()> {
+    let title = tr(if ctx.pick_mode {
+        Msg::SpokenTitlePick
+    } else {
+        Msg::SpokenTitle
+    });
+    if ctx.header_stats.is_empty() {
+        c.text(title)?;
+    } else {
+        c.text(&format!("{title}: {}", spoken(ctx.header_stats)))?;
+    }
+    c.newline()?;
+    let mut search = trf(Msg::Search, &[("query", &ctx.input_buf)]);
+    if !ctx.input_buf.is_empty() {
+        let noun = if ctx.tries.len() == 1 {
+            "match"
+        } else {
+            "matches"
+        };
+        search.push_str(&format!(
+            ", {} {noun} of {}",
+            ctx.tries.len(),
+            ctx.total_count
+        ));
+    }
+    if !ctx.marked.is_empty() {
+        search.push_str(&format!(", {} marked", ctx.marked.len()));
+    }
+    if !ctx.chips.is_empty() {
+        search.push_str(&format!(", tags {}", ctx.chips.join(", ")));
+    }
+    c.text(&search)?;
+    c.newline()?;
+
+    if let Some(picker) = ctx.tag_picker {
+        let mut selected = None;
+        for (idx, (tag, count)) in picker.tags.iter().enumerate() {
+            let state = if ctx.chips.contains(tag) {
+                "selected"
+            } else {
+                "not selected"
+            };
+            let noun = if *count == 1 { "try" } else { "tries" };
+            let line = format!("tag {tag}, {count} {noun}, {state}");
+            let is_sel = idx == picker.cursor;
+            c.text(if is_sel { "> " } else { "  " })?;
+            c.text(&line)?;
+            c.newline()?;
+            if is_sel {
+                selected = Some(format!("{line}, {} of {}", idx + 1, picker.tags.len()));
+            }
+        }
+        if c.caps.screen {
+            c.text(tr(Msg::SpokenKeysTags))?;
+            c.newline()?;
+            if let Some(selected) = selected {
+                c.text(&trf(Msg::Selected, &[("row", &selected)]))?;
+            }
+        }
+        c.out.flush()?;
+        return Ok(());
+    }
+
+    if ctx.tries.is_empty() {
+        match ctx.name_check {
+            Some(check) => c.text(&trf(
+                Msg::SpokenNoMatchesCreates,
+                &[("name", &check.final_name)],
+            ))?,
+            None => c.text(tr(Msg::NoMatches))?,
+        }
+        c.newline()?;
+        if !ctx.suggestions.is_empty() {
+            let names = ctx.suggestions.join(", ");
+            c.text(&trf(Msg::DidYouMean, &[("names", &names)]))?;
+            c.newline()?;
+        }
     }
 
-    err.flush()?;
+    let max_visible = layout(ctx.term_h, ctx.sections.len()).rows;
+    let extra_rows = if ctx.pick_mode { 0 } else { 1 };
+    let total = ctx.tries.len() + extra_rows;
+    let (_, end) = compute_viewport(ctx.cursor, ctx.scroll, max_visible, total);
+    let mut selected = None;
+    for idx in ctx.scroll..end {
+        for section in ctx.sections.iter().filter(|s| s.start == idx) {
+            c.text(&format!("{}:", spoken(&section.label)))?;
+            c.newline()?;
+        }
+        let line = if idx < ctx.tries.len() {
+            let t = &ctx.tries[idx];
+            let line = spoken_row(t, ctx.pick_mode);
+            if ctx.marked.contains(&t.path) {
+                format!("{line}, marked")
+            } else {
+                line
+            }
+        } else {
+            spoken_create_row(ctx.name_check)
+        };
+        let is_sel = idx == ctx.cursor;
+        c.text(if is_sel { "> " } else { "  " })?;
+        c.text(&line)?;
+        c.newline()?;
+        if is_sel {
+            selected = Some(format!("{line}, {} of {total}", idx + 1));
+        }
+    }
+
+    // A static listing takes no input, so key hints and announcements stop here
+    if !c.caps.screen {
+        c.out.flush()?;
+        return Ok(());
+    }
+    c.text(tr(if ctx.pick_mode {
+        Msg::SpokenKeysPick
+    } else {
+        Msg::SpokenKeys
+    }))?;
+    c.newline()?;
+    if ctx.show_delete_pending {
+        c.text(tr(Msg::SpokenDeletePending))?;
+        c.newline()?;
+    } else if let Some((severity, s)) = &ctx.status_msg {
+        let label = tr(match severity {
+            Severity::Info => Msg::StatusLabel,
+            Severity::Warn => Msg::WarningLabel,
+            Severity::Error => Msg::ErrorLabel,
+        });
+        c.text(&format!("{label}: {}", spoken(s)))?;
+        c.newline()?;
+    }
+    if let Some(selected) = selected {
+        c.text(&trf(Msg::Selected, &[("row", &selected)]))?;
+    }
+    c.out.flush()?;
     Ok(())
 }
+
+/// A try row in words, e.g. `parser-test, modified 3 hours ago, 1.2K, 2 changed files`.
+fn spoken_row(t: &TryDir, pick_mode: bool) -> String {
+    if pick_mode {
+        return t.basename.clone();
+    }
+    let mut parts = vec![
+        t.basename.clone(),
+        format!("modified {}", spoken_age(t.mtime)),
+    ];
+    parts.extend(t.size.map(crate::util::format_human_size));
+    if let Some(git) = &t.git {
+        if git.changes > 0 {
+            let noun = if git.changes == 1 { "file" } else { "files" };
+            parts.push(format!("{} changed {noun}", git.changes));
+        }
+        if git.ahead > 0 {
+            let noun = if git.ahead == 1 { "commit" } else { "commits" };
+            parts.push(format!("{} {noun} ahead", git.ahead));
+        }
+    }
+    if let Some(behind) = t.behind.filter(|&n| n > 0) {
+        let noun = if behind == 1 { "commit" } else { "commits" };
+        parts.push(format!("{behind} {noun} behind"));
+    }
+    if t.pending_clone {
+        parts.push("incomplete clone".to_string());
+    }
+    if let Some(expires) = t.expires {
+        parts.push(match expires.duration_since(std::time::SystemTime::now()) {
+            Ok(left) => format!("expires in {}", spoken_span(&format_duration(left))),
+            Err(_) => "expired".to_string(),
+        });
+    }
+    parts.join(", ")
+}
+
+/// The "Create new" row in words, naming the try it would create and any collision.
+fn spoken_create_row(check: Option<&NameCheck>) -> String {
+    match check {
+        None => tr(Msg::CreateNew).to_string(),
+        Some(check) => match &check.collision {
+            Some(existing) => format!("Create new {}, {existing} exists", check.final_name),
+            None => format!("Create new {}", check.final_name),
+        },
+    }
+}
+
+/// [`format_relative_time`] with the units spelled out: `3 hours ago`.
+fn spoken_age(t: Option<std::time::SystemTime>) -> String {
+    let short = format_relative_time(t);
+    match short.strip_suffix(" ago") {
+        Some(span) => format!("{} ago", spoken_span(span)),
+        None if short == "?" => "at an unknown time".to_string(),
+        None => short,
+    }
+}
+
+/// A compact span such as `3h` or `1mo` with its unit spelled out: `3 hours`, `1 month`.
+fn spoken_span(span: &str) -> String {
+    let digits = span
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(span.len());
+    let (count, unit) = span.split_at(digits);
+    let unit = match unit {
+        "m" => "minute",
+        "h" => "hour",
+        "d" => "day",
+        "mo" => "month",
+        "y" => "year",
+        _ => return span.to_string(),
+    };
+    let plural = if count == "1" { "" } else { "s" };
+    format!("{count} {unit}{plural}")
+}
+
+/// `text` with the symbols used as separators in the visual UI replaced by words or commas.
+fn spoken(text: &str) -> String {
+    text.replace(" · ", ", ")
+        .replace(" — ", ", ")
+        .replace(" → ", " to ")
+        .replace('≥', "at least ")
+}
@@ -1,6 +1,7 @@
 use dirs::home_dir;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 // Time constants
 const SECONDS_PER_DAY: u64 = 86_400;
@@ -21,11 +22,28 @@ pub(crate) fn shellexpand_home(p: &str) -> PathBuf {
     PathBuf::from(p)
 }
 
+/// Displays `p` with the home directory abbreviated to `~` for compact UI output.
+pub(crate) fn tilde_path(p: &Path) -> String {
+    if let Some(home) = home_dir()
+        && let Ok(rest) = p.strip_prefix(&home)
+    {
+        if rest.as_os_str().is_empty() {
+            return "~".into();
+        }
+        return format!("~/{}", rest.display());
+    }
+    p.display().to_string()
+}
+
 /// Returns today's date prefix in UTC as `YYYY-MM-DD` using a civil-from-days conversion.
 pub(crate) fn today_prefix() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let secs = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
+    date_prefix(std::time::SystemTime::now())
+}
+
+/// Date prefix in UTC as `YYYY-MM-DD` for `time`.
+pub(crate) fn date_prefix(time: std::time::SystemTime) -> String {
+    let secs = time
+        .duration_since(std::time::UNIX_EPOCH)
         .map(|d| d.as_secs())
         .unwrap_or(0);
     let days = (secs / SECONDS_PER_DAY) as i64;
@@ -66,6 +84,18 @@ pub(crate) fn civil_from_days(days: i64) -> (i32, u32, u32) {
     (y, m as u32, d as u32)
 }
 
+/// Howard Hinnant days-from-civil (UTC): days since 1970-01-01 for a proleptic Gregorian
+/// date; the inverse of [`civil_from_days`].
+pub(crate) fn days_from_civil(y: i32, m: u32, d: u32) -> i64 {
+    let y = i64::from(y) - i64::from(m <= 2);
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (i64::from(m) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(d) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
 /// If the input begins with `YYYY-MM-DD-...`, returns that date part and the remainder.
 pub(crate) fn split_date_prefixed(s: &str) -> Option<(&str, &str)> {
     // Check for YYYY-MM-DD- pattern at the beginning
@@ -80,23 +110,52 @@ pub(crate) fn split_date_prefixed(s: &str) -> Option<(&str, &str)> {
     None
 }
 
-/// Shell-escapes a path using single quotes suitable for POSIX shells.
-pub(crate) fn shell_escape(p: PathBuf) -> String {
-    let s = p.to_string_lossy();
-    let esc = s.replace('\'', r#"'\''"#);
-    format!("'{esc}'")
-}
-
-/// Filters a free-form query to a safe subset of characters for display and matching.
-pub(crate) fn sanitize_query(q: &str) -> String {
-    q.chars()
-        .filter(|&c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ' '))
-        .collect()
+/// Longest common prefix of `names`, compared case-insensitively; the returned text keeps the
+/// casing of the first name.
+pub(crate) fn longest_common_prefix(names: &[&str]) -> String {
+    let Some((first, rest)) = names.split_first() else {
+        return String::new();
+    };
+    let mut len = first.len();
+    for name in rest {
+        let common: usize = first
+            .chars()
+            .zip(name.chars())
+            .take_while(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        len = len.min(common);
+    }
+    first[..len].to_string()
 }
 
-/// Returns whether a typed character should be accepted into the query buffer.
-pub(crate) fn is_printable(ch: char) -> bool {
-    ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.' | ' ')
+/// Whether `text` matches the shell-style `pattern`, where `*` matches any run of characters
+/// and `?` exactly one; the whole of `text` must match.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        match p.get(pi) {
+            Some('*') => {
+                backtrack = Some((pi, ti));
+                pi += 1;
+            }
+            Some(&c) if c == '?' || c == t[ti] => {
+                pi += 1;
+                ti += 1;
+            }
+            _ => match backtrack {
+                Some((star, from)) => {
+                    pi = star + 1;
+                    ti = from + 1;
+                    backtrack = Some((star, from + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
 }
 
 /// Extracts `--flag value` and `--flag=value` from `args`, removing all occurrences; returns the last value.
@@ -189,6 +248,48 @@ pub(crate) fn parse_git_uri(input: &str) -> Option<GitUri> {
     None
 }
 
+/// Web link to a folder inside a repository, such as
+/// `https://github.com/org/repo/tree/main/examples/basic`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TreeUrl {
+    /// Cloneable repository URL, e.g. `https://github.com/org/repo`.
+    pub repo: String,
+    /// Branch or tag after `/tree/`.
+    pub git_ref: String,
+    /// Folder path inside the repository, without surrounding slashes.
+    pub subdir: String,
+}
+
+/// Parses GitHub-style `https://host/owner/repo/tree/<ref>/<path>` and GitLab-style
+/// `.../-/tree/<ref>/<path>` links. The ref is taken to be a single path segment.
+pub(crate) fn parse_tree_url(input: &str) -> Option<TreeUrl> {
+    let input = input.trim();
+    let (scheme, rest) = input.split_once("://")?;
+    if scheme != "https" && scheme != "http" {
+        return None;
+    }
+    let mut parts = rest.trim_end_matches('/').split('/');
+    let (host, owner, repo) = (parts.next()?, parts.next()?, parts.next()?);
+    let mut marker = parts.next()?;
+    if marker == "-" {
+        marker = parts.next()?;
+    }
+    if marker != "tree" {
+        return None;
+    }
+    let git_ref = parts.next()?.to_string();
+    let subdir = parts.collect::<Vec<_>>().join("/");
+    if subdir.is_empty() {
+        return None;
+    }
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+    Some(TreeUrl {
+        repo: format!("{scheme}://{host}/{owner}/{repo}"),
+        git_ref,
+        subdir,
+    })
+}
+
 /// Heuristic to decide if an argument looks like a git URI.
 pub(crate) fn is_git_uri(arg: &str) -> bool {
     let a = arg.trim();
@@ -200,6 +301,19 @@ pub(crate) fn is_git_uri(arg: &str) -> bool {
         || a.ends_with(".git")
 }
 
+/// Applies the longest matching `(prefix, replacement)` rule to `url`, as git's `insteadOf`
+/// does; `url` is returned unchanged when no prefix matches.
+pub(crate) fn rewrite_url(url: &str, rules: &[(String, String)]) -> String {
+    rules
+        .iter()
+        .filter(|(prefix, _)| url.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map_or_else(
+            || url.to_string(),
+            |(prefix, replacement)| format!("{replacement}{}", &url[prefix.len()..]),
+        )
+}
+
 /// Generate directory name for cloning.
 /// If `custom_name` provided and non-empty, returns it as-is; otherwise uses
 /// `YYYY-MM-DD-user-repo` based on the parsed git URI.
@@ -217,19 +331,19 @@ pub(crate) fn generate_clone_directory_name(
     Some(format!("{}-{}-{}", date_prefix, parsed.user, parsed.repo))
 }
 
-/// Join commands with ` && `, returning a single shell-evaluable line.
-pub(crate) fn join_shell(parts: &[String]) -> String {
-    parts.join(" && ")
-}
-
-/// Build a shell assignment for directory variable depending on shell.
-pub(crate) fn dir_assign_for_shell(dir: &Path) -> String {
-    let escaped = shell_escape(dir.to_path_buf());
-    if is_fish_shell() {
-        format!("set -l dir {escaped}")
-    } else {
-        format!("dir={escaped}")
+/// `s` as a JSON string literal, quotes included.
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if u32::from(c) < 0x20 => out.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => out.push(c),
+        }
     }
+    out.push('"');
+    out
 }
 
 /// Format a byte size as a human-readable string (e.g., "1.5K", "23.4M").
@@ -251,20 +365,81 @@ pub(crate) fn format_human_size(bytes: u64) -> String {
 
 /// Calculate the total size of a directory recursively.
 pub(crate) fn calculate_dir_size(path: &Path) -> u64 {
-    let mut total = 0u64;
-    fn walk(p: &Path, total: &mut u64) {
+    dir_usage(path).1
+}
+
+/// Number of files and their total bytes under `path`. Symlinks are not followed, and
+/// directories beyond the filesystem boundary (see `mounts::Boundary`) are not counted.
+pub(crate) fn dir_usage(path: &Path) -> (u64, u64) {
+    dir_usage_until(path, &AtomicBool::new(false)).unwrap_or_default()
+}
+
+/// [`dir_usage`] that checks `cancel` before each directory and gives up with `None` once it
+/// is set.
+pub(crate) fn dir_usage_until(path: &Path, cancel: &AtomicBool) -> Option<(u64, u64)> {
+    use crate::mounts::Boundary;
+    fn walk(p: &Path, boundary: &Boundary, cancel: &AtomicBool, usage: &mut (u64, u64)) -> bool {
         if let Ok(md) = std::fs::symlink_metadata(p) {
             if md.is_file() {
-                *total += md.len();
+                usage.0 += 1;
+                usage.1 += md.len();
             } else if md.is_dir()
+                && boundary.allows(p, &md)
                 && let Ok(rd) = std::fs::read_dir(p)
             {
+                if cancel.load(Ordering::Relaxed) {
+                    return false;
+                }
                 for e in rd.flatten() {
-                    walk(&e.path(), total);
+                    if !walk(&e.path(), boundary, cancel, usage) {
+                        return false;
+                    }
                 }
             }
         }
+        true
     }
-    walk(path, &mut total);
-    total
+    let mut usage = (0, 0);
+    walk(path, &Boundary::at(path), cancel, &mut usage).then_some(usage)
+}
+
+/// Opens `target` (a URL or path) with the platform's default handler without waiting for it.
+/// Output of the opener is discarded so stdout stays clean for the shell wrapper.
+pub(crate) fn open_with_system(target: &str) -> std::io::Result<()> {
+    use std::process::{Command, Stdio};
+    let mut cmd = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.args(["/C", "start", ""]);
+        c
+    } else {
+        Command::new("xdg-open")
+    };
+    cmd.arg(target)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+/// The user's editor command from `$VISUAL` or `$EDITOR`, split into program and arguments
+/// (e.g. `code --wait`); `vi` when neither is set.
+pub(crate) fn editor_command(env: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(env)
+        .map(|v| v.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+        .find(|parts| !parts.is_empty())
+        .unwrap_or_else(|| vec!["vi".to_string()])
+}
+
+/// Opens `path` in the user's editor and waits for it to exit.
+pub(crate) fn open_in_editor(path: &Path) -> std::io::Result<std::process::ExitStatus> {
+    let cmd = editor_command(|key| std::env::var(key).ok());
+    std::process::Command::new(&cmd[0])
+        .args(&cmd[1..])
+        .arg(path)
+        .status()
 }
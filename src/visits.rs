@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::storage::META_DIR;
+
+/// Visit log under `.try_meta`: `name<TAB>count<TAB>last visit (Unix seconds)` per line.
+const VISITS_FILE: &str = "visits.tsv";
+/// Default `selector.visit_half_life_days`.
+pub(crate) const DEFAULT_HALF_LIFE_DAYS: u64 = 14;
+// Entries that have decayed below this weight are dropped on save
+const FORGET_BELOW: f64 = 0.05;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+static HALF_LIFE_DAYS: OnceLock<u64> = OnceLock::new();
+
+/// Sets the process-wide visit half-life; only the first call takes effect.
+pub(crate) fn set_half_life_days(days: u64) {
+    let _ = HALF_LIFE_DAYS.set(days);
+}
+
+/// Days after which a visit counts half as much; 0 ignores visits altogether.
+pub(crate) fn half_life_days() -> u64 {
+    HALF_LIFE_DAYS
+        .get()
+        .copied()
+        .unwrap_or(DEFAULT_HALF_LIFE_DAYS)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Entry {
+    /// Decayed count as of `last`.
+    count: f64,
+    last: u64,
+}
+
+/// How often each try was opened from `try cd`. Counts are stored as of their last visit
+/// and aged only when read, so nothing rewrites the log just because time passed.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Visits {
+    path: PathBuf,
+    entries: HashMap<String, Entry>,
+}
+
+impl Visits {
+    /// Loads the log stored under `base_path`; a missing file or malformed lines are
+    /// treated as no visits.
+    pub(crate) fn load(base_path: &Path) -> Self {
+        let path = base_path.join(META_DIR).join(VISITS_FILE);
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let entries = text
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let (name, count, last) = (fields.next()?, fields.next()?, fields.next()?);
+                let entry = Entry {
+                    count: count.parse().ok()?,
+                    last: last.parse().ok()?,
+                };
+                Some((name.to_string(), entry))
+            })
+            .collect();
+        Self { path, entries }
+    }
+
+    /// Number of tries with recorded visits.
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether `name` has a visit in the log.
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Time of the latest visit of every try in the log.
+    pub(crate) fn last_visits(&self) -> impl Iterator<Item = SystemTime> + '_ {
+        self.entries
+            .values()
+            .map(|e| UNIX_EPOCH + Duration::from_secs(e.last))
+    }
+
+    /// Visit weight of `name` at `now`: each visit counts 1, halving every `half_life_days`.
+    pub(crate) fn weight(&self, name: &str, now: SystemTime, half_life_days: u64) -> f64 {
+        self.entries
+            .get(name)
+            .map_or(0.0, |e| decayed(e, unix_secs(now), half_life_days))
+    }
+
+    /// The most recently visited try other than `except`.
+    pub(crate) fn latest(&self, except: Option<&str>) -> Option<&str> {
+        self.entries
+            .iter()
+            .filter(|(name, _)| Some(name.as_str()) != except)
+            .max_by_key(|(name, e)| (e.last, *name))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Records a visit to `name` at `now`, ageing its earlier visits first.
+    pub(crate) fn record(&mut self, name: &str, now: SystemTime, half_life_days: u64) {
+        let now = unix_secs(now);
+        let count = self
+            .entries
+            .get(name)
+            .map_or(0.0, |e| decayed(e, now, half_life_days));
+        self.entries.insert(
+            name.to_string(),
+            Entry {
+                count: count + 1.0,
+                last: now,
+            },
+        );
+    }
+
+    /// Writes the log, forgetting tries whose visits have all but decayed away.
+    pub(crate) fn save(&self, now: SystemTime, half_life_days: u64) -> io::Result<()> {
+        let now = unix_secs(now);
+        let mut lines: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| decayed(e, now, half_life_days) >= FORGET_BELOW)
+            .map(|(name, e)| format!("{name}\t{:.3}\t{}", e.count, e.last))
+            .collect();
+        lines.sort();
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, lines.join("\n") + "\n")
+    }
+}
+
+/// Records a visit to the try at `dir` in its tries directory's log. Failures are ignored:
+/// a read-only tries directory should not stop `cd`.
+pub(crate) fn record_visit(base_path: &Path, dir: &Path) {
+    let Some(name) = dir.file_name() else {
+        return;
+    };
+    let (now, half_life) = (SystemTime::now(), half_life_days());
+    let mut visits = Visits::load(base_path);
+    visits.record(&name.to_string_lossy(), now, half_life);
+    let _ = visits.save(now, half_life);
+}
+
+fn decayed(e: &Entry, now: u64, half_life_days: u64) -> f64 {
+    if half_life_days == 0 {
+        return 0.0;
+    }
+    let days = now.saturating_sub(e.last) as f64 / SECONDS_PER_DAY;
+    e.count * 0.5f64.powf(days / half_life_days as f64)
+}
+
+fn unix_secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}